@@ -0,0 +1,140 @@
+use crate::store::StatsStore;
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// An entry of `whitelist.json`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WhitelistEntry {
+    pub uuid: String,
+    pub name: String,
+}
+
+/// An entry of `ops.json`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OpEntry {
+    pub uuid: String,
+    pub name: String,
+    /// Permission level from 1 to 4, see `op-permission-level` in `server.properties`
+    pub level: u8,
+    /// Whether the player can join when the server is full
+    #[serde(rename = "bypassesPlayerLimit", default)]
+    pub bypasses_player_limit: bool,
+}
+
+/// When, by whom, why and until when something was banned, shared by
+/// `banned-players.json` and `banned-ips.json`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BanDetails {
+    /// e.g. `2024-05-01 12:00:00 +0000`
+    pub created: String,
+    /// Who banned, e.g. a player's name, `Server` or `(Unknown)`
+    pub source: String,
+    /// End of the ban, or `forever`
+    pub expires: String,
+    pub reason: String,
+}
+
+impl BanDetails {
+    pub fn is_permanent(&self) -> bool {
+        self.expires == "forever"
+    }
+}
+
+/// An entry of `banned-players.json`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BannedPlayer {
+    pub uuid: String,
+    pub name: String,
+    #[serde(flatten)]
+    pub ban: BanDetails,
+}
+
+/// An entry of `banned-ips.json`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BannedIp {
+    pub ip: String,
+    #[serde(flatten)]
+    pub ban: BanDetails,
+}
+
+/// The player lists the server keeps next to `usercache.json`. The server
+/// rewrites these files itself, so they are only read here, and changes go
+/// through commands like `/whitelist add`.
+#[derive(Debug, Clone, Default)]
+pub struct AccessLists {
+    pub whitelist: Vec<WhitelistEntry>,
+    pub ops: Vec<OpEntry>,
+    pub banned_players: Vec<BannedPlayer>,
+    pub banned_ips: Vec<BannedIp>,
+}
+
+impl AccessLists {
+    /// Loads the lists from the server's directory. Missing files are empty lists.
+    pub fn load(server_dir: &Path) -> Result<Self> {
+        Ok(AccessLists {
+            whitelist: load_list(&server_dir.join("whitelist.json"))?,
+            ops: load_list(&server_dir.join("ops.json"))?,
+            banned_players: load_list(&server_dir.join("banned-players.json"))?,
+            banned_ips: load_list(&server_dir.join("banned-ips.json"))?,
+        })
+    }
+
+    pub fn is_whitelisted(&self, uuid: &str) -> bool {
+        self.whitelist
+            .iter()
+            .any(|entry| entry.uuid.eq_ignore_ascii_case(uuid))
+    }
+
+    pub fn op(&self, uuid: &str) -> Option<&OpEntry> {
+        self.ops
+            .iter()
+            .find(|entry| entry.uuid.eq_ignore_ascii_case(uuid))
+    }
+
+    pub fn ban(&self, uuid: &str) -> Option<&BannedPlayer> {
+        self.banned_players
+            .iter()
+            .find(|entry| entry.uuid.eq_ignore_ascii_case(uuid))
+    }
+
+    /// Whitelisted players without a stats file, who have never joined
+    pub fn never_joined(&self, store: &StatsStore) -> Vec<&WhitelistEntry> {
+        self.whitelist
+            .iter()
+            .filter(|entry| !has_joined(store, &entry.uuid))
+            .collect()
+    }
+
+    /// UUIDs of players with a stats file who are not on the whitelist, e.g.
+    /// players who joined before it was turned on
+    pub fn not_whitelisted(&self, store: &StatsStore) -> Vec<String> {
+        store
+            .players()
+            .into_iter()
+            .map(|(uuid, _)| uuid)
+            .filter(|uuid| !self.is_whitelisted(uuid))
+            .collect()
+    }
+}
+
+/// Whether a name could be a Minecraft username: 1 to 16 letters, digits or
+/// underscores. Names are checked before being put into server commands.
+pub fn is_valid_username(name: &str) -> bool {
+    (1..=16).contains(&name.len()) && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Players get a stats file the first time the world is saved after they join
+pub fn has_joined(store: &StatsStore, uuid: &str) -> bool {
+    store.get(uuid).is_some()
+}
+
+fn load_list<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let json = std::fs::read_to_string(path)
+        .with_context(|| format!("Could not read {}", path.display()))?;
+    serde_json::from_str(&json).with_context(|| format!("Could not parse {}", path.display()))
+}
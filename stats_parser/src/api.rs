@@ -0,0 +1,420 @@
+use crate::query::{server_report, stat_full};
+use crate::{SERVER_HOST, query_port_of};
+use axum::extract::{FromRef, Path, Query, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use fnv::FnvHasher;
+use poise::ChoiceParameter;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use stats_parser::leaderboard::LeaderboardTarget;
+use stats_parser::report::{
+    RowOrder, StatFilter, leaderboard_report, player_stats_report, profile_report, timetable_report,
+};
+use stats_parser::store::StatsStore;
+use stats_parser::{Day, GetStatsOption, Section, StatGroup, read_timetable};
+use std::hash::Hasher;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// What the routes share
+#[derive(Clone)]
+struct ApiState {
+    store: Arc<StatsStore>,
+    /// The server's directory, with its `server.properties`
+    server_dir: Arc<PathBuf>,
+}
+
+impl FromRef<ApiState> for Arc<StatsStore> {
+    fn from_ref(state: &ApiState) -> Self {
+        Arc::clone(&state.store)
+    }
+}
+
+/// Serves the stats as JSON on `addr` until the process exits. The server's
+/// status is queried on the port set in `server_dir`'s `server.properties`.
+pub async fn run_api(
+    addr: SocketAddr,
+    store: Arc<StatsStore>,
+    server_dir: PathBuf,
+) -> anyhow::Result<()> {
+    let app = Router::new()
+        .route("/players", get(players))
+        .route("/players/{uuid}/stats", get(player_stats))
+        .route("/players/{uuid}/profile", get(player_profile))
+        .route("/leaderboard/{category}/{stat}", get(leaderboard))
+        .route("/server/status", get(server_status))
+        .route("/timetable/{section}/{day}", get(timetable))
+        .route("/openapi.json", get(openapi))
+        .with_state(ApiState {
+            store,
+            server_dir: Arc::new(server_dir),
+        });
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+/// An error as a JSON body, e.g. `{"error": "Unknown player"}`
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+fn not_found(message: impl Into<String>) -> ApiError {
+    ApiError(StatusCode::NOT_FOUND, message.into())
+}
+
+fn bad_request(message: impl Into<String>) -> ApiError {
+    ApiError(StatusCode::BAD_REQUEST, message.into())
+}
+
+/// Responds with `body` and an ETag of its contents. If the client already
+/// has this version (`If-None-Match`), only `304 Not Modified` is sent. The
+/// ETag is an FNV-1a hash, which unlike `DefaultHasher` stays the same across
+/// restarts and Rust versions.
+fn cached_json(headers: &HeaderMap, body: Value) -> Response {
+    let body = body.to_string();
+    let mut hasher = FnvHasher::default();
+    hasher.write(body.as_bytes());
+    let etag = format!("\"{:016x}\"", hasher.finish());
+    let etag_header = HeaderValue::from_str(&etag).expect("ETag is ASCII");
+    let matches = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| {
+            value
+                .split(',')
+                .any(|tag| tag.trim() == etag || tag.trim() == "*")
+        });
+    if matches {
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag_header)]).into_response();
+    }
+    (
+        [
+            (header::ETAG, etag_header),
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+fn parse_choice<T: ChoiceParameter>(kind: &str, name: &str) -> Result<T, ApiError> {
+    T::from_name(name).ok_or_else(|| bad_request(format!("Unknown {} {}", kind, name)))
+}
+
+async fn players(State(store): State<Arc<StatsStore>>, headers: HeaderMap) -> Response {
+    let players = store
+        .players()
+        .into_iter()
+        .map(|(uuid, _)| json!({ "name": store.username(&uuid), "uuid": uuid }))
+        .collect::<Vec<_>>();
+    cached_json(&headers, json!(players))
+}
+
+#[derive(Deserialize)]
+struct StatsQuery {
+    /// Category, e.g. `mined`. All categories if left out.
+    category: Option<String>,
+    /// `value`, `value_asc` or `name`
+    sort: Option<String>,
+    /// Substring of the stat name, or `/regex/`
+    filter: Option<String>,
+    /// Stat group, e.g. `ores`
+    group: Option<String>,
+    min: Option<i64>,
+    top: Option<usize>,
+}
+
+async fn player_stats(
+    State(store): State<Arc<StatsStore>>,
+    Path(uuid): Path<String>,
+    Query(query): Query<StatsQuery>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let stats = store
+        .get(&uuid)
+        .ok_or_else(|| not_found(format!("No stats found for {}", uuid)))?;
+    let category = match &query.category {
+        Some(category) => Some(parse_choice::<GetStatsOption>("category", category)?),
+        None => None,
+    };
+    let filter = StatFilter {
+        order: match &query.sort {
+            Some(sort) => parse_choice::<RowOrder>("sort", sort)?,
+            None => RowOrder::default(),
+        },
+        name: match &query.filter {
+            Some(filter) => Some(
+                filter
+                    .parse()
+                    .map_err(|e| bad_request(format!("{:#}", e)))?,
+            ),
+            None => None,
+        },
+        group: match &query.group {
+            Some(group) => Some(parse_choice::<StatGroup>("group", group)?),
+            None => None,
+        },
+        min: query.min,
+        top: query.top,
+    };
+    let name = store.username(&uuid).unwrap_or_else(|| uuid.clone());
+    let report = player_stats_report(&name, &stats, category.map(|c| c.key()), &filter);
+    Ok(cached_json(&headers, report.to_json()))
+}
+
+async fn player_profile(
+    State(store): State<Arc<StatsStore>>,
+    Path(uuid): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let name = store.username(&uuid).unwrap_or_else(|| uuid.clone());
+    let report = profile_report(&store, &uuid, &name)
+        .ok_or_else(|| not_found(format!("No stats found for {}", uuid)))?;
+    Ok(cached_json(&headers, report.to_json()))
+}
+
+#[derive(Deserialize)]
+struct LeaderboardQuery {
+    /// Leaves out players with less playtime
+    min_hours: Option<f64>,
+}
+
+async fn leaderboard(
+    State(store): State<Arc<StatsStore>>,
+    Path((category, stat)): Path<(String, String)>,
+    Query(query): Query<LeaderboardQuery>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let category = parse_choice::<GetStatsOption>("category", &category)?;
+    let stat = if stat.contains(':') {
+        stat
+    } else {
+        format!("minecraft:{}", stat)
+    };
+    let target = LeaderboardTarget::Stat {
+        category: category.key().to_string(),
+        stat,
+    };
+    let report = leaderboard_report(&store, &target, query.min_hours.unwrap_or(0.0));
+    Ok(cached_json(&headers, report.to_json()))
+}
+
+async fn server_status(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let port = query_port_of(&state.server_dir)
+        .map_err(|e| ApiError(StatusCode::SERVICE_UNAVAILABLE, e.to_string()))?;
+    let response = stat_full(SERVER_HOST, port)
+        .await
+        .map_err(|e| ApiError(StatusCode::BAD_GATEWAY, e.to_string()))?;
+    Ok(cached_json(&headers, server_report(&response).to_json()))
+}
+
+async fn timetable(
+    Path((section, day)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let section = String::from(parse_choice::<Section>("section", &section)?);
+    let day = parse_choice::<Day>("day", &day)?;
+    // Reads the timetable's file, so keep it off the async runtime
+    let timetable = tokio::task::spawn_blocking({
+        let section = section.clone();
+        move || read_timetable(&section)
+    })
+    .await
+    .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .map_err(|e| not_found(e.to_string()))?;
+    let report = timetable_report(&section, &timetable.get_day(day));
+    Ok(cached_json(&headers, report.to_json()))
+}
+
+async fn openapi(headers: HeaderMap) -> Response {
+    cached_json(&headers, openapi_spec())
+}
+
+/// OpenAPI 3.1 description of every route
+fn openapi_spec() -> Value {
+    let path_param = |name: &str, description: &str| {
+        json!({
+            "name": name,
+            "in": "path",
+            "required": true,
+            "description": description,
+            "schema": { "type": "string" },
+        })
+    };
+    let report = |summary: &str, parameters: Vec<Value>| {
+        json!({
+            "get": {
+                "summary": summary,
+                "parameters": parameters,
+                "responses": {
+                    "200": {
+                        "description": "The report",
+                        "content": { "application/json": { "schema": { "$ref": "#/components/schemas/StatsReport" } } },
+                    },
+                    "304": { "description": "Not modified since the ETag in If-None-Match" },
+                    "400": { "$ref": "#/components/responses/Error" },
+                    "404": { "$ref": "#/components/responses/Error" },
+                },
+            },
+        })
+    };
+    let category = "Stats category, e.g. mined, killed_by or custom";
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "Minecraft server stats",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": {
+            "/players": {
+                "get": {
+                    "summary": "Every player with a stats file",
+                    "responses": {
+                        "200": {
+                            "description": "Players",
+                            "content": { "application/json": { "schema": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "properties": {
+                                        "uuid": { "type": "string" },
+                                        "name": { "type": ["string", "null"] },
+                                    },
+                                },
+                            } } },
+                        },
+                        "304": { "description": "Not modified since the ETag in If-None-Match" },
+                    },
+                },
+            },
+            "/players/{uuid}/stats": report("A player's stats by category", vec![
+                path_param("uuid", "Player UUID"),
+                json!({ "name": "category", "in": "query", "required": false, "description": category, "schema": { "type": "string" } }),
+                json!({ "name": "sort", "in": "query", "required": false, "schema": { "type": "string", "enum": ["value", "value_asc", "name"] } }),
+                json!({ "name": "filter", "in": "query", "required": false, "description": "Substring of the stat name, or /regex/", "schema": { "type": "string" } }),
+                json!({ "name": "group", "in": "query", "required": false, "schema": { "type": "string", "enum": ["ores", "logs", "hostile_mobs"] } }),
+                json!({ "name": "min", "in": "query", "required": false, "description": "Minimum value", "schema": { "type": "integer" } }),
+                json!({ "name": "top", "in": "query", "required": false, "description": "Stats shown per category", "schema": { "type": "integer", "minimum": 0 } }),
+            ]),
+            "/players/{uuid}/profile": report("A player's headline stats and highlights", vec![
+                path_param("uuid", "Player UUID"),
+            ]),
+            "/leaderboard/{category}/{stat}": report("Players ranked by a stat", vec![
+                path_param("category", category),
+                path_param("stat", "Stat, e.g. diamond_ore"),
+                json!({ "name": "min_hours", "in": "query", "required": false, "description": "Minimum playtime in hours", "schema": { "type": "number" } }),
+            ]),
+            "/server/status": report("Version and online players of the Minecraft server", vec![]),
+            "/timetable/{section}/{day}": report("Classes of a section on a day", vec![
+                path_param("section", "Section, e.g. everest"),
+                path_param("day", "Day of the week, e.g. monday"),
+            ]),
+        },
+        "components": {
+            "schemas": {
+                "StatsReport": {
+                    "type": "object",
+                    "properties": {
+                        "title": { "type": "string" },
+                        "description": { "type": ["string", "null"] },
+                        "footer": { "type": ["string", "null"] },
+                        "sections": { "type": "array", "items": { "$ref": "#/components/schemas/ReportSection" } },
+                    },
+                },
+                "ReportSection": {
+                    "type": "object",
+                    "properties": {
+                        "title": { "type": "string" },
+                        "columns": { "type": "array", "items": { "type": "string" } },
+                        "rows": { "type": "array", "items": { "$ref": "#/components/schemas/ReportRow" } },
+                    },
+                },
+                "ReportRow": {
+                    "type": "object",
+                    "properties": {
+                        "label": { "type": "string" },
+                        "values": { "type": "array", "items": { "$ref": "#/components/schemas/ReportValue" } },
+                        "best": { "type": ["integer", "null"], "description": "Index of the best value" },
+                        "note": { "type": ["string", "null"] },
+                    },
+                },
+                "ReportValue": {
+                    "type": "object",
+                    "required": ["display"],
+                    "properties": {
+                        "value": { "type": "number" },
+                        "unit": { "type": "string", "enum": ["count", "ticks", "centimetres", "ratio", "per_hour", "centimetres_per_hour"] },
+                        "display": { "type": "string", "description": "The value formatted for people" },
+                    },
+                },
+            },
+            "responses": {
+                "Error": {
+                    "description": "Bad or unknown parameters",
+                    "content": { "application/json": { "schema": {
+                        "type": "object",
+                        "properties": { "error": { "type": "string" } },
+                    } } },
+                },
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get(if_none_match: Option<&str>) -> Response {
+        let mut headers = HeaderMap::new();
+        if let Some(etag) = if_none_match {
+            headers.insert(header::IF_NONE_MATCH, HeaderValue::from_str(etag).unwrap());
+        }
+        cached_json(&headers, json!({ "players": ["Steve", "Alex"] }))
+    }
+
+    #[test]
+    fn etag_turns_repeat_requests_into_not_modified() {
+        let first = get(None);
+        assert_eq!(first.status(), StatusCode::OK);
+        assert_eq!(first.headers()[header::CONTENT_TYPE], "application/json");
+        let etag = first.headers()[header::ETAG].to_str().unwrap().to_string();
+        assert!(etag.starts_with('"') && etag.ends_with('"'));
+
+        let second = get(Some(&etag));
+        assert_eq!(second.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(second.headers()[header::ETAG], etag.as_str());
+
+        let listed = get(Some(&format!("\"0000000000000000\", {}", etag)));
+        assert_eq!(listed.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(get(Some("*")).status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(get(Some("\"0000000000000000\"")).status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn etag_depends_on_the_body() {
+        let etag = |body: Value| {
+            cached_json(&HeaderMap::new(), body).headers()[header::ETAG]
+                .to_str()
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(etag(json!([1, 2])), etag(json!([1, 2])));
+        assert_ne!(etag(json!([1, 2])), etag(json!([2, 1])));
+    }
+}
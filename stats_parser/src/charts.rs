@@ -0,0 +1,337 @@
+use crate::i18n::tr;
+use anyhow::{Result, anyhow};
+use image::{ImageFormat, RgbImage};
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use plotters::style::{FontStyle, register_font};
+use std::f64::consts::PI;
+use std::io::Cursor;
+use std::sync::Once;
+
+/// Size of every chart in pixels
+pub const CHART_SIZE: (u32, u32) = (800, 500);
+
+/// Pie charts group everything after this many slices into "Other"
+const MAX_PIE_SLICES: usize = 8;
+
+pub(crate) const FONT: &str = "sans-serif";
+/// Noto Sans has Devanagari as well as Latin glyphs, for charts in Hindi.
+/// Glyphs are drawn one by one without shaping, so conjuncts are not joined.
+const FONT_REGULAR: &[u8] = include_bytes!("../assets/fonts/NotoSans-Regular.ttf");
+/// Only used for player names on profile cards, which are always ASCII, so
+/// this cut of Noto Sans Bold leaves out Devanagari
+const FONT_BOLD: &[u8] = include_bytes!("../assets/fonts/NotoSans-Bold.ttf");
+
+static REGISTER_FONTS: Once = Once::new();
+
+/// Registers the bundled fonts, so charts render the same on every machine
+/// without looking up system fonts
+pub fn register_fonts() {
+    REGISTER_FONTS.call_once(|| {
+        register_font(FONT, FontStyle::Normal, FONT_REGULAR)
+            .and_then(|_| register_font(FONT, FontStyle::Bold, FONT_BOLD))
+            .unwrap_or_else(|_| panic!("Bundled fonts are invalid"));
+    });
+}
+
+pub(crate) fn palette_color(i: usize) -> RGBColor {
+    let (r, g, b) = Palette99::pick(i).rgb();
+    RGBColor(r, g, b)
+}
+
+/// Draws on a white canvas of the given size and encodes it as a PNG
+pub(crate) fn render(
+    size: (u32, u32),
+    draw: impl FnOnce(&DrawingArea<BitMapBackend, Shift>) -> Result<()>,
+) -> Result<Vec<u8>> {
+    register_fonts();
+    let (width, height) = size;
+    let mut buf = vec![0u8; (width * height * 3) as usize];
+    {
+        let root = BitMapBackend::with_buffer(&mut buf, size).into_drawing_area();
+        root.fill(&WHITE)?;
+        draw(&root)?;
+        root.present()?;
+    }
+    let image = RgbImage::from_raw(width, height, buf)
+        .ok_or_else(|| anyhow!("Chart buffer has the wrong size"))?;
+    let mut png = Cursor::new(vec![]);
+    image.write_to(&mut png, ImageFormat::Png)?;
+    Ok(png.into_inner())
+}
+
+/// Horizontal bar chart with the first entry at the top, e.g. for leaderboards
+pub fn bar_chart(title: &str, entries: &[(String, f64)]) -> Result<Vec<u8>> {
+    render(CHART_SIZE, |root| {
+        let count = entries.len().max(1);
+        let max = entries.iter().map(|(_, value)| *value).fold(0.0, f64::max);
+        let max = if max > 0.0 { max * 1.1 } else { 1.0 };
+        let mut chart = ChartBuilder::on(root)
+            .caption(title, (FONT, 28))
+            .margin(20)
+            .x_label_area_size(30)
+            .build_cartesian_2d(0.0..max, 0.0..count as f64)?;
+        chart
+            .configure_mesh()
+            .disable_y_mesh()
+            .y_labels(0)
+            .x_max_light_lines(1)
+            .label_style((FONT, 14))
+            .draw()?;
+        // Row 0 is at the bottom, so the entries are drawn in reverse
+        let row = |i: usize| (count - 1 - i) as f64;
+        chart.draw_series(entries.iter().enumerate().map(|(i, (_, value))| {
+            Rectangle::new(
+                [(0.0, row(i) + 0.1), (*value, row(i) + 0.9)],
+                palette_color(i).filled(),
+            )
+        }))?;
+        let label_style = TextStyle::from((FONT, 16).into_font())
+            .color(&BLACK)
+            .pos(Pos::new(HPos::Left, VPos::Center));
+        chart.draw_series(entries.iter().enumerate().map(|(i, (name, _))| {
+            Text::new(
+                format!(" {}", name),
+                (0.0, row(i) + 0.5),
+                label_style.clone(),
+            )
+        }))?;
+        Ok(())
+    })
+}
+
+/// Pie chart of the share of each entry, e.g. a player's `killed_by` breakdown
+pub fn pie_chart(title: &str, entries: &[(String, f64)]) -> Result<Vec<u8>> {
+    let mut entries = entries
+        .iter()
+        .filter(|(_, value)| *value > 0.0)
+        .cloned()
+        .collect::<Vec<_>>();
+    entries.sort_by(|a, b| b.1.total_cmp(&a.1));
+    if entries.len() > MAX_PIE_SLICES {
+        let other = entries
+            .drain(MAX_PIE_SLICES - 1..)
+            .map(|(_, value)| value)
+            .sum();
+        entries.push((tr("chart.other"), other));
+    }
+    render(CHART_SIZE, |root| {
+        let root = root.titled(title, (FONT, 28))?;
+        if entries.is_empty() {
+            root.draw_text(
+                &tr("chart.no_data"),
+                &(FONT, 20).into_text_style(&root),
+                (20, 20),
+            )?;
+            return Ok(());
+        }
+        let (width, height) = root.dim_in_pixel();
+        let center = (width as i32 / 2, height as i32 / 2);
+        let radius = height as f64 * 0.35;
+        let sizes = entries.iter().map(|(_, value)| *value).collect::<Vec<_>>();
+        let colors = (0..entries.len()).map(palette_color).collect::<Vec<_>>();
+        let labels = entries
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        let mut pie = Pie::new(&center, &radius, &sizes, &colors, &labels);
+        pie.start_angle(-90.0);
+        pie.label_style((FONT, 16).into_font().color(&BLACK));
+        pie.percentages((FONT, 14).into_font().color(&WHITE));
+        root.draw(&pie)?;
+        Ok(())
+    })
+}
+
+/// Line chart of values over time, e.g. playtime from snapshots.
+/// `points` are `(unix seconds, value)`, drawn as days since the first point.
+pub fn line_chart(title: &str, y_label: &str, points: &[(u64, f64)]) -> Result<Vec<u8>> {
+    render(CHART_SIZE, |root| {
+        let start = points.iter().map(|(time, _)| *time).min().unwrap_or(0);
+        let points = points
+            .iter()
+            .map(|(time, value)| ((time - start) as f64 / 86_400.0, *value))
+            .collect::<Vec<_>>();
+        let max_x = points.iter().map(|(x, _)| *x).fold(0.0, f64::max).max(1.0);
+        let max_y = points.iter().map(|(_, y)| *y).fold(0.0, f64::max);
+        let max_y = if max_y > 0.0 { max_y * 1.1 } else { 1.0 };
+        let mut chart = ChartBuilder::on(root)
+            .caption(title, (FONT, 28))
+            .margin(20)
+            .x_label_area_size(40)
+            .y_label_area_size(60)
+            .build_cartesian_2d(0.0..max_x, 0.0..max_y)?;
+        chart
+            .configure_mesh()
+            .x_desc(tr("chart.days"))
+            .y_desc(y_label)
+            .label_style((FONT, 14))
+            .draw()?;
+        let color = palette_color(0);
+        chart.draw_series(LineSeries::new(
+            points.iter().copied(),
+            color.stroke_width(3),
+        ))?;
+        chart.draw_series(
+            points
+                .iter()
+                .map(|point| Circle::new(*point, 4, color.filled())),
+        )?;
+        Ok(())
+    })
+}
+
+/// Radar chart comparing several series over the same axes, e.g. for `compare`.
+/// Values are expected to be normalised to `0.0..=1.0`.
+pub fn radar_chart(title: &str, axes: &[String], series: &[(String, Vec<f64>)]) -> Result<Vec<u8>> {
+    render(CHART_SIZE, |root| {
+        let root = root.titled(title, (FONT, 28))?;
+        let (width, height) = root.dim_in_pixel();
+        let (cx, cy) = (width as f64 / 2.0, height as f64 / 2.0 + 10.0);
+        let radius = height as f64 * 0.32;
+        let axis_count = axes.len().max(3);
+        let point = |axis: usize, value: f64| {
+            let angle = 2.0 * PI * axis as f64 / axis_count as f64 - PI / 2.0;
+            let value = value.clamp(0.0, 1.0);
+            (
+                (cx + radius * value * angle.cos()).round() as i32,
+                (cy + radius * value * angle.sin()).round() as i32,
+            )
+        };
+
+        // Grid rings and spokes
+        for ring in 1..=4 {
+            let mut ring_points = (0..axis_count)
+                .map(|axis| point(axis, ring as f64 / 4.0))
+                .collect::<Vec<_>>();
+            ring_points.push(ring_points[0]);
+            root.draw(&PathElement::new(ring_points, BLACK.mix(0.2)))?;
+        }
+        let label_style = (FONT, 15).into_text_style(&root);
+        for (axis, name) in axes.iter().enumerate() {
+            root.draw(&PathElement::new(
+                vec![point(axis, 0.0), point(axis, 1.0)],
+                BLACK.mix(0.3),
+            ))?;
+            let (x, y) = point(axis, 1.12);
+            let (text_width, _) = root.estimate_text_size(name, &label_style)?;
+            root.draw_text(name, &label_style, (x - text_width as i32 / 2, y - 8))?;
+        }
+
+        for (i, (name, values)) in series.iter().enumerate() {
+            let color = palette_color(i);
+            let mut shape = (0..axis_count)
+                .map(|axis| point(axis, values.get(axis).copied().unwrap_or(0.0)))
+                .collect::<Vec<_>>();
+            root.draw(&Polygon::new(shape.clone(), color.mix(0.25).filled()))?;
+            shape.push(shape[0]);
+            root.draw(&PathElement::new(shape, color.stroke_width(3)))?;
+            // Legend in the top left corner
+            let y = 10 + i as i32 * 24;
+            root.draw(&Rectangle::new([(10, y), (28, y + 18)], color.filled()))?;
+            root.draw_text(name, &label_style, (36, y + 1))?;
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Compares a chart with `tests/golden/<name>.png` pixel by pixel. Run the
+    /// tests with `UPDATE_GOLDEN=1` to write the images after changing a chart.
+    pub(crate) fn assert_golden(name: &str, png: &[u8]) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/golden")
+            .join(format!("{}.png", name));
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::write(&path, png).unwrap();
+            return;
+        }
+        let golden = std::fs::read(&path)
+            .unwrap_or_else(|e| panic!("Could not read {}: {}", path.display(), e));
+        let expected = image::load_from_memory(&golden).unwrap().to_rgb8();
+        let actual = image::load_from_memory(png).unwrap().to_rgb8();
+        assert_eq!(actual.dimensions(), expected.dimensions());
+        assert!(
+            actual == expected,
+            "{} does not match {}",
+            name,
+            path.display()
+        );
+    }
+
+    fn entries(entries: &[(&str, f64)]) -> Vec<(String, f64)> {
+        entries
+            .iter()
+            .map(|(name, value)| (name.to_string(), *value))
+            .collect()
+    }
+
+    #[test]
+    fn bar_chart_matches_golden() {
+        let entries = entries(&[
+            ("Notch", 120.0),
+            ("jeb_", 95.5),
+            ("Dinnerbone", 60.0),
+            ("Grumm", 12.25),
+            ("Steve", 0.0),
+        ]);
+        assert_golden("bar_chart", &bar_chart("Playtime", &entries).unwrap());
+    }
+
+    #[test]
+    fn pie_chart_matches_golden() {
+        // More slices than fit, so the smallest are grouped into "Other"
+        let entries = entries(&[
+            ("Zombie", 30.0),
+            ("Skeleton", 25.0),
+            ("Creeper", 20.0),
+            ("Fall", 12.0),
+            ("Lava", 8.0),
+            ("Drowning", 6.0),
+            ("Enderman", 4.0),
+            ("Spider", 3.0),
+            ("Witch", 2.0),
+            ("Cactus", 1.0),
+            ("Wither", 0.0),
+        ]);
+        assert_golden("pie_chart", &pie_chart("Killed by", &entries).unwrap());
+    }
+
+    #[test]
+    fn empty_pie_chart_matches_golden() {
+        assert_golden("pie_chart_empty", &pie_chart("Killed by", &[]).unwrap());
+    }
+
+    #[test]
+    fn line_chart_matches_golden() {
+        let day = 86_400;
+        let start = 1_745_301_719;
+        let points = [
+            (start, 0.0),
+            (start + day, 3.5),
+            (start + 3 * day, 4.0),
+            (start + 4 * day, 9.25),
+            (start + 7 * day, 15.0),
+        ];
+        let png = line_chart("Playtime of Notch", "Hours", &points).unwrap();
+        assert_golden("line_chart", &png);
+    }
+
+    #[test]
+    fn radar_chart_matches_golden() {
+        let axes = ["Playtime", "Mined", "Kills", "Deaths", "Distance"]
+            .map(String::from)
+            .to_vec();
+        let series = vec![
+            ("Notch".to_string(), vec![1.0, 0.4, 0.8, 0.2, 0.6]),
+            ("jeb_".to_string(), vec![0.5, 1.0, 0.1, 0.9, 1.0]),
+        ];
+        let png = radar_chart("Notch vs jeb_", &axes, &series).unwrap();
+        assert_golden("radar_chart", &png);
+    }
+}
@@ -0,0 +1,427 @@
+use crate::query::{server_report, stat_full};
+use crate::{
+    LANG_DIR, NAME_HISTORY_PATH, NAMES_DIR, SCOREBOARD_PATH, SERVER_DIR, SERVER_HOST, STATS_DIR,
+    USERCACHE_PATH,
+};
+use anyhow::{Result, anyhow};
+use clap::{Parser, Subcommand};
+use poise::ChoiceParameter;
+use stats_parser::access_lists::AccessLists;
+use stats_parser::export::{ExportFormat, StatsMatrix};
+use stats_parser::i18n::{Language, tr_with};
+use stats_parser::leaderboard::LeaderboardTarget;
+use stats_parser::metrics::{DEFAULT_MIN_PLAYTIME_HOURS, DerivedMetric};
+use stats_parser::report::{
+    NameFilter, RowOrder, StatFilter, StatsReport, bans_report, leaderboard_report,
+    objective_report, ops_report, player_stats_report, playtime_report, profile_report,
+    scoreboard_report, server_properties_report, team_report, timetable_report, whitelist_report,
+};
+use stats_parser::scoreboard::Scoreboard;
+use stats_parser::server_properties::ServerProperties;
+use stats_parser::store::StatsStore;
+use stats_parser::uuids;
+use stats_parser::{Day, GetStatsOption, Section, StatGroup, read_timetable};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Minecraft server stats, as a Discord bot or from the terminal.
+/// Without a subcommand the bot is started.
+#[derive(Parser)]
+#[command(version)]
+pub struct Cli {
+    /// Print JSON instead of tables
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// Directory with the `<uuid>.json` stats files
+    #[arg(long, global = true, default_value = STATS_DIR)]
+    pub stats_dir: PathBuf,
+
+    /// The server's usercache.json
+    #[arg(long, global = true, default_value = USERCACHE_PATH)]
+    pub usercache: PathBuf,
+
+    /// Where every name seen in the usercache is kept, so players who fall out
+    /// of it can still be found
+    #[arg(long, global = true, default_value = NAME_HISTORY_PATH)]
+    pub name_history: PathBuf,
+
+    /// The server's directory, with server.properties, whitelist.json, ops.json
+    /// and the ban lists
+    #[arg(long, global = true, default_value = SERVER_DIR)]
+    pub server_dir: PathBuf,
+
+    /// The world's data/scoreboard.dat
+    #[arg(long, global = true, default_value = SCOREBOARD_PATH)]
+    pub scoreboard_file: PathBuf,
+
+    /// Directory with Minecraft language files for stat names, e.g.
+    /// en_us.json and hi_in.json. Missing files are skipped.
+    #[arg(long, global = true, default_value = LANG_DIR)]
+    pub lang_dir: PathBuf,
+
+    /// Directory with `<namespace>.toml` files naming the stats of mods and
+    /// datapacks. Missing files are skipped.
+    #[arg(long, global = true, default_value = NAMES_DIR)]
+    pub names_dir: PathBuf,
+
+    /// Language of the output: en or hi
+    #[arg(long, global = true, value_parser = choice::<Language>)]
+    pub language: Option<Language>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Start the Discord bot (the default)
+    Bot,
+    /// Show a player's stats
+    Stats {
+        /// Username or UUID
+        player: String,
+        /// Category, e.g. mined. All categories if left out.
+        #[arg(value_parser = choice::<GetStatsOption>)]
+        category: Option<GetStatsOption>,
+        /// Order of the stats: value, value-asc or name
+        #[arg(long, value_parser = choice::<RowOrder>)]
+        sort: Option<RowOrder>,
+        /// Only stats whose name contains this, or matches /regex/
+        #[arg(long)]
+        filter: Option<NameFilter>,
+        /// Only stats in a group: ores, logs or hostile-mobs
+        #[arg(long, value_parser = choice::<StatGroup>)]
+        group: Option<StatGroup>,
+        /// Only stats with at least this value
+        #[arg(long)]
+        min: Option<i64>,
+        /// Only the first this many stats of each category
+        #[arg(long)]
+        top: Option<usize>,
+    },
+    /// Show the playtime of every player
+    Playtime,
+    /// Rank players by a stat or a derived metric
+    Leaderboard {
+        #[command(subcommand)]
+        target: LeaderboardCommand,
+    },
+    /// Show a player's profile
+    Profile {
+        /// Username or UUID
+        player: String,
+    },
+    /// Query a server for its version and online players
+    Server {
+        /// Address of the server's query port. The query.port of server.properties if left out.
+        address: Option<String>,
+    },
+    /// Show the main settings of server.properties
+    Serverinfo,
+    /// Show the timetable of a section
+    Timetable {
+        #[arg(value_parser = choice::<Section>)]
+        section: Section,
+        #[arg(value_parser = choice::<Day>)]
+        day: Day,
+    },
+    /// Serve the stats as a JSON HTTP API without the bot
+    #[cfg(feature = "api")]
+    Api {
+        #[arg(default_value = "127.0.0.1:8080")]
+        addr: std::net::SocketAddr,
+    },
+    /// Export Prometheus metrics of the Minecraft server without the bot
+    #[cfg(feature = "prometheus")]
+    Metrics {
+        #[arg(default_value = "127.0.0.1:9100")]
+        addr: std::net::SocketAddr,
+        /// Address of the server's query port. The query.port of server.properties if left out.
+        #[arg(long)]
+        query: Option<String>,
+    },
+    /// List the scoreboard's objectives and teams, or rank the scores of one objective
+    Scoreboard {
+        /// Objective, e.g. deaths. Every objective if left out.
+        objective: Option<String>,
+    },
+    /// Show the members of a scoreboard team
+    Team {
+        /// Name of the team
+        name: String,
+    },
+    /// Show the whitelist, who on it never joined and who joined without being on it
+    Whitelist,
+    /// Show the server's operators
+    Ops,
+    /// Show the banned players and IPs
+    Bans,
+    /// Write every player's stats to stdout as a spreadsheet-friendly file
+    Export {
+        /// csv, ndjson or json
+        #[arg(long, value_parser = choice::<ExportFormat>, default_value = "csv")]
+        format: ExportFormat,
+        /// Category, e.g. mined. All categories if left out.
+        #[arg(value_parser = choice::<GetStatsOption>)]
+        category: Option<GetStatsOption>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum LeaderboardCommand {
+    /// Rank by a single stat, e.g. `stat mined diamond_ore`
+    Stat {
+        #[arg(value_parser = choice::<GetStatsOption>)]
+        category: GetStatsOption,
+        stat: String,
+    },
+    /// Rank by a derived metric, e.g. `metric kd-ratio`
+    Metric {
+        #[arg(value_parser = choice::<DerivedMetric>)]
+        metric: DerivedMetric,
+        /// Minimum playtime in hours
+        #[arg(long, default_value_t = DEFAULT_MIN_PLAYTIME_HOURS)]
+        min_hours: f64,
+    },
+}
+
+/// Parses a bot choice from the command line. Besides the names the bot shows,
+/// short names are accepted with dashes or underscores, e.g. `kd-ratio` or
+/// `kd_ratio`, and so are the names without spaces or punctuation, e.g. `pvpkdratio`.
+fn choice<T: ChoiceParameter>(input: &str) -> Result<T, String> {
+    let simplify = |name: &str| {
+        name.chars()
+            .filter(char::is_ascii_alphanumeric)
+            .collect::<String>()
+            .to_lowercase()
+    };
+    if let Some(choice) = T::from_name(input).or_else(|| T::from_name(&input.replace('-', "_"))) {
+        return Ok(choice);
+    }
+    let names = T::list();
+    names
+        .iter()
+        .position(|choice| simplify(&choice.name) == simplify(input))
+        .and_then(T::from_index)
+        .ok_or_else(|| {
+            let names = names
+                .iter()
+                .map(|choice| choice.name.as_str())
+                .collect::<Vec<_>>();
+            format!("expected one of: {}", names.join(", "))
+        })
+}
+
+/// Runs a subcommand without Discord
+pub async fn run(cli: Cli) -> Result<()> {
+    let Some(command) = cli.command else {
+        return Ok(());
+    };
+    let load_store =
+        || StatsStore::load_with_name_history(&cli.stats_dir, &cli.usercache, &cli.name_history);
+    let report = match command {
+        Command::Bot => return Ok(()),
+        Command::Stats {
+            player,
+            category,
+            sort,
+            filter,
+            group,
+            min,
+            top,
+        } => {
+            let store = load_store()?;
+            let (uuid, name) = find_player(&store, &player)?;
+            let player_stats = store
+                .get(&uuid)
+                .ok_or_else(|| anyhow!(tr_with("error.no_stats", &[("player", &name)])))?;
+            let filter = StatFilter {
+                order: sort.unwrap_or_default(),
+                name: filter,
+                group,
+                min,
+                top,
+            };
+            player_stats_report(&name, &player_stats, category.map(|c| c.key()), &filter)
+        }
+        Command::Playtime => playtime_report(&load_store()?),
+        Command::Leaderboard { target } => {
+            let (target, min_hours) = match target {
+                LeaderboardCommand::Stat { category, stat } => {
+                    let stat = if stat.contains(':') {
+                        stat
+                    } else {
+                        format!("minecraft:{}", stat)
+                    };
+                    let target = LeaderboardTarget::Stat {
+                        category: category.key().to_string(),
+                        stat,
+                    };
+                    (target, 0.0)
+                }
+                LeaderboardCommand::Metric { metric, min_hours } => {
+                    (LeaderboardTarget::Metric(metric), min_hours)
+                }
+            };
+            leaderboard_report(&load_store()?, &target, min_hours)
+        }
+        Command::Profile { player } => {
+            let store = load_store()?;
+            let (uuid, name) = find_player(&store, &player)?;
+            profile_report(&store, &uuid, &name)
+                .ok_or_else(|| anyhow!(tr_with("error.no_stats", &[("player", &name)])))?
+        }
+        Command::Server { address } => {
+            let (host, port) = query_address(&cli.server_dir, address)?;
+            server_report(&stat_full(&host, port).await?)
+        }
+        Command::Serverinfo => server_properties_report(&ServerProperties::load(
+            &cli.server_dir.join("server.properties"),
+        )?),
+        Command::Scoreboard { objective } => {
+            let scoreboard = Scoreboard::load(&cli.scoreboard_file)?;
+            match objective {
+                Some(name) => {
+                    let objective = scoreboard.objective(&name).ok_or_else(|| {
+                        anyhow!(tr_with(
+                            "scoreboard.unknown_objective",
+                            &[("objective", &name)]
+                        ))
+                    })?;
+                    objective_report(&scoreboard, objective)
+                }
+                None => scoreboard_report(&scoreboard),
+            }
+        }
+        Command::Team { name } => {
+            let scoreboard = Scoreboard::load(&cli.scoreboard_file)?;
+            let team = scoreboard
+                .team(&name)
+                .ok_or_else(|| anyhow!(tr_with("team.unknown", &[("team", &name)])))?;
+            team_report(team)
+        }
+        Command::Whitelist => {
+            whitelist_report(&AccessLists::load(&cli.server_dir)?, &load_store()?)
+        }
+        Command::Ops => ops_report(&AccessLists::load(&cli.server_dir)?, &load_store()?),
+        Command::Bans => bans_report(&AccessLists::load(&cli.server_dir)?),
+        Command::Timetable { section, day } => {
+            let section = String::from(section);
+            let timetable = read_timetable(&section)?;
+            timetable_report(&section, &timetable.get_day(day))
+        }
+        #[cfg(feature = "api")]
+        Command::Api { addr } => {
+            let store = std::sync::Arc::new(load_store()?);
+            let _watcher = store.watch()?;
+            return crate::api::run_api(addr, store, cli.server_dir).await;
+        }
+        #[cfg(feature = "prometheus")]
+        Command::Metrics { addr, query } => {
+            let server = query_address(&cli.server_dir, query)?;
+            let store = std::sync::Arc::new(load_store()?);
+            let _watcher = store.watch()?;
+            let telemetry = crate::telemetry::Telemetry::new(store, Some(server))?;
+            return crate::telemetry::run_metrics(addr, std::sync::Arc::new(telemetry)).await;
+        }
+        Command::Export { format, category } => {
+            let matrix = StatsMatrix::build(&load_store()?, category.map(|c| c.key()));
+            std::io::stdout().write_all(&matrix.export(format)?)?;
+            return Ok(());
+        }
+    };
+    print_report(&report, cli.json)
+}
+
+/// Splits a `host:port` address, or reads the query port of the server's
+/// server.properties if there is none
+fn query_address(server_dir: &Path, address: Option<String>) -> Result<(String, u16)> {
+    if let Some(address) = address {
+        let (host, port) = address
+            .rsplit_once(':')
+            .ok_or_else(|| anyhow!("Expected host:port, got {}", address))?;
+        return Ok((host.to_string(), port.parse()?));
+    }
+    let properties = ServerProperties::load(&server_dir.join("server.properties"))?;
+    if !properties.query_enabled() {
+        return Err(anyhow!(
+            "The server does not answer queries, set enable-query=true in server.properties"
+        ));
+    }
+    Ok((SERVER_HOST.to_string(), properties.query_port()))
+}
+
+fn print_report(report: &StatsReport, json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report.to_json())?);
+    } else {
+        print!("{}", report.to_terminal());
+    }
+    Ok(())
+}
+
+/// Finds a player by username or UUID, returning `(uuid, username)`
+fn find_player(store: &StatsStore, player: &str) -> Result<(String, String)> {
+    if let Some(uuid) = uuids::normalise(player)
+        && store.get(&uuid).is_some()
+    {
+        let name = store.username(&uuid).unwrap_or_else(|| uuid.clone());
+        return Ok((uuid, name));
+    }
+    let uuid = store
+        .uuid(player)
+        .ok_or_else(|| anyhow!(tr_with("error.unknown_player", &[("player", &player)])))?;
+    // Names are matched ignoring case, so show the one the player uses
+    let name = store.username(&uuid).unwrap_or_else(|| player.to_string());
+    Ok((uuid, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Cli {
+        Cli::try_parse_from(["stats_parser"].iter().chain(args)).unwrap()
+    }
+
+    fn metric(name: &str) -> DerivedMetric {
+        match parse(&["leaderboard", "metric", name]).command {
+            Some(Command::Leaderboard {
+                target: LeaderboardCommand::Metric { metric, .. },
+            }) => metric,
+            _ => panic!("not a metric leaderboard"),
+        }
+    }
+
+    #[test]
+    fn parses_metric_names() {
+        for name in [
+            "kd-ratio",
+            "kd_ratio",
+            "pvpkdratio",
+            "PvP K/D Ratio",
+            "pvp-kd-ratio",
+        ] {
+            assert_eq!(metric(name), DerivedMetric::KillDeathRatio, "{}", name);
+        }
+        assert_eq!(metric("blocks-per-hour"), DerivedMetric::BlocksMinedPerHour);
+        assert!(Cli::try_parse_from(["stats_parser", "leaderboard", "metric", "kdr"]).is_err());
+    }
+
+    #[test]
+    fn parses_other_choices() {
+        assert_eq!(
+            choice::<RowOrder>("value-asc"),
+            Ok(RowOrder::ValueAscending)
+        );
+        assert_eq!(
+            choice::<StatGroup>("hostile-mobs"),
+            Ok(StatGroup::HostileMobs)
+        );
+        assert!(matches!(
+            choice::<GetStatsOption>("picked-up"),
+            Ok(GetStatsOption::PickedUp)
+        ));
+    }
+}
@@ -0,0 +1,102 @@
+use poise::serenity_prelude as serenity;
+use stats_parser::deaths::{DeathEvent, parse_death_line};
+use stats_parser::i18n::tr_with;
+use stats_parser::store::StatsStore;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+#[cfg(not(unix))]
+use std::time::SystemTime;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Tails the server's `latest.log` and posts every new death message to `channel`.
+/// Only lines written after the feed starts are posted.
+pub async fn run_death_feed(
+    http: Arc<serenity::Http>,
+    channel: serenity::ChannelId,
+    log_path: PathBuf,
+    store: Arc<StatsStore>,
+) {
+    let metadata = fs::metadata(&log_path).ok();
+    let mut offset = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
+    let mut identity = metadata.as_ref().and_then(file_identity);
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        let Ok(metadata) = fs::metadata(&log_path) else {
+            continue;
+        };
+        // The server moved `latest.log` into an archive and started a new one.
+        // The new log may already be longer than the old one by the next poll,
+        // so it is told apart by its identity rather than its length.
+        let current = file_identity(&metadata);
+        if current != identity || metadata.len() < offset {
+            identity = current;
+            offset = 0;
+        }
+        if metadata.len() == offset {
+            continue;
+        }
+        let new_text = match read_from(&log_path, offset) {
+            Ok(buf) => buf,
+            Err(e) => {
+                tracing::warn!("Could not read {}: {}", log_path.display(), e);
+                continue;
+            }
+        };
+        // Only consume complete lines, the rest is picked up on the next poll
+        let Some(end) = new_text.iter().rposition(|b| *b == b'\n') else {
+            continue;
+        };
+        offset += end as u64 + 1;
+
+        let known_players = store.usernames();
+        for line in String::from_utf8_lossy(&new_text[..end]).lines() {
+            if let Some(death) = parse_death_line(line, &known_players) {
+                let message = serenity::CreateMessage::new().embed(death_embed(&death));
+                if let Err(e) = channel.send_message(&http, message).await {
+                    tracing::warn!("Could not post death of {}: {}", death.player, e);
+                }
+            }
+        }
+    }
+}
+
+/// What stays the same for a file while it is appended to, and differs for a
+/// new file at the same path: the inode
+#[cfg(unix)]
+fn file_identity(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+/// What stays the same for a file while it is appended to, and differs for a
+/// new file at the same path: the creation time
+#[cfg(not(unix))]
+fn file_identity(metadata: &fs::Metadata) -> Option<SystemTime> {
+    metadata.created().ok()
+}
+
+fn read_from(path: &PathBuf, offset: u64) -> std::io::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut buf = vec![];
+    file.read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+fn death_embed(death: &DeathEvent) -> serenity::CreateEmbed {
+    serenity::CreateEmbed::new()
+        .title(tr_with("death_feed.title", &[("player", &death.player)]))
+        .description(tr_with(
+            "death_feed.message",
+            &[("player", &death.player), ("message", &death.message)],
+        ))
+        .color(serenity::Colour::DARK_RED)
+        .footer(serenity::CreateEmbedFooter::new(tr_with(
+            "death_feed.footer",
+            &[("cause", &death.cause), ("time", &death.time)],
+        )))
+}
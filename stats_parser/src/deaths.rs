@@ -0,0 +1,405 @@
+use crate::i18n::{tr, tr_with};
+use flate2::read::GzDecoder;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+/// How a player died, as classified from a vanilla death message
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DeathCause {
+    Fall,
+    Lava,
+    Fire,
+    Drowning,
+    Void,
+    Explosion,
+    Suffocation,
+    Starvation,
+    Freezing,
+    Lightning,
+    Magic,
+    Wither,
+    Contact,
+    Kinetic,
+    FallingBlock,
+    /// Killed by a mob, e.g. "Zombie"
+    Mob(String),
+    /// Killed by another player (PvP)
+    Player(String),
+    Other,
+}
+
+impl fmt::Display for DeathCause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let key = match self {
+            DeathCause::Fall => "death_cause.fall",
+            DeathCause::Lava => "death_cause.lava",
+            DeathCause::Fire => "death_cause.fire",
+            DeathCause::Drowning => "death_cause.drowning",
+            DeathCause::Void => "death_cause.void",
+            DeathCause::Explosion => "death_cause.explosion",
+            DeathCause::Suffocation => "death_cause.suffocation",
+            DeathCause::Starvation => "death_cause.starvation",
+            DeathCause::Freezing => "death_cause.freezing",
+            DeathCause::Lightning => "death_cause.lightning",
+            DeathCause::Magic => "death_cause.magic",
+            DeathCause::Wither => "death_cause.wither",
+            DeathCause::Contact => "death_cause.contact",
+            DeathCause::Kinetic => "death_cause.kinetic",
+            DeathCause::FallingBlock => "death_cause.falling_block",
+            DeathCause::Mob(mob) => return write!(f, "{}", mob),
+            DeathCause::Player(player) => {
+                return write!(
+                    f,
+                    "{}",
+                    tr_with("death_cause.player", &[("player", player)])
+                );
+            }
+            DeathCause::Other => "death_cause.other",
+        };
+        write!(f, "{}", tr(key))
+    }
+}
+
+/// A single death parsed from the server log
+#[derive(Debug, Clone)]
+pub struct DeathEvent {
+    /// The `[HH:MM:SS]` timestamp of the log line
+    pub time: String,
+    pub player: String,
+    /// The death message without the player name, e.g. "fell from a high place"
+    pub message: String,
+    pub cause: DeathCause,
+}
+
+/// Death message phrases in the order they are checked, so phrases sharing a
+/// prefix with a longer one must come after it. `None` marks a phrase that is
+/// followed by the name of the killer, e.g. "was slain by Zombie".
+const DEATH_PHRASES: [(&str, Option<DeathCause>); 55] = [
+    ("was squashed by a falling", Some(DeathCause::FallingBlock)),
+    (
+        "was skewered by a falling stalactite",
+        Some(DeathCause::FallingBlock),
+    ),
+    ("was burned to a crisp", Some(DeathCause::Fire)),
+    ("was frozen to death", Some(DeathCause::Freezing)),
+    ("was killed by even more magic", Some(DeathCause::Magic)),
+    ("was killed by magic", Some(DeathCause::Magic)),
+    (
+        "was killed by [Intentional Game Design]",
+        Some(DeathCause::Explosion),
+    ),
+    ("was blown up by ", Some(DeathCause::Explosion)),
+    ("was slain by ", None),
+    ("was shot by a skull from ", None),
+    ("was shot by ", None),
+    ("was pummeled by ", None),
+    ("was fireballed by ", None),
+    ("was killed by ", None),
+    // Thorns
+    ("was killed while trying to hurt ", None),
+    // A mace
+    ("was smashed by ", None),
+    ("was spitballed by ", None),
+    ("was impaled by ", None),
+    ("was stung to death by ", None),
+    ("was stung to death", Some(DeathCause::Other)),
+    ("was squashed by ", None),
+    ("was skewered by ", None),
+    ("hit the ground too hard", Some(DeathCause::Fall)),
+    ("fell from a high place", Some(DeathCause::Fall)),
+    ("fell off ", Some(DeathCause::Fall)),
+    ("fell while ", Some(DeathCause::Fall)),
+    ("was doomed to fall", Some(DeathCause::Fall)),
+    ("fell too far", Some(DeathCause::Fall)),
+    ("was impaled on a stalagmite", Some(DeathCause::Fall)),
+    ("tried to swim in lava", Some(DeathCause::Lava)),
+    ("went up in flames", Some(DeathCause::Fire)),
+    ("burned to death", Some(DeathCause::Fire)),
+    ("walked into fire", Some(DeathCause::Fire)),
+    ("walked into the danger zone", Some(DeathCause::Fire)),
+    ("discovered the floor was lava", Some(DeathCause::Fire)),
+    ("drowned", Some(DeathCause::Drowning)),
+    ("fell out of the world", Some(DeathCause::Void)),
+    (
+        "didn't want to live in the same world as",
+        Some(DeathCause::Void),
+    ),
+    ("left the confines of this world", Some(DeathCause::Void)),
+    ("blew up", Some(DeathCause::Explosion)),
+    ("went off with a bang", Some(DeathCause::Explosion)),
+    ("suffocated in a wall", Some(DeathCause::Suffocation)),
+    ("was squished too much", Some(DeathCause::Suffocation)),
+    ("starved to death", Some(DeathCause::Starvation)),
+    ("froze to death", Some(DeathCause::Freezing)),
+    ("was struck by lightning", Some(DeathCause::Lightning)),
+    ("was roasted in dragon's breath", Some(DeathCause::Magic)),
+    ("withered away", Some(DeathCause::Wither)),
+    ("was pricked to death", Some(DeathCause::Contact)),
+    ("hugged a cactus", Some(DeathCause::Contact)),
+    ("walked into a cactus", Some(DeathCause::Contact)),
+    (
+        "was poked to death by a sweet berry bush",
+        Some(DeathCause::Contact),
+    ),
+    ("experienced kinetic energy", Some(DeathCause::Kinetic)),
+    ("was killed", Some(DeathCause::Other)),
+    ("died", Some(DeathCause::Other)),
+];
+
+/// Classifies a death message (without the player name) into a [`DeathCause`].
+/// `known_players` is used to tell PvP kills apart from mob kills.
+pub fn classify_death(message: &str, known_players: &[String]) -> Option<DeathCause> {
+    if message.starts_with("was obliterated by a sonically-charged shriek") {
+        return Some(DeathCause::Mob("Warden".to_string()));
+    }
+    let (phrase, cause) = DEATH_PHRASES
+        .iter()
+        .find(|(phrase, _)| message.starts_with(phrase))?;
+    if let Some(cause) = cause {
+        return Some(cause.clone());
+    }
+    let killer = strip_killer_suffix(&message[phrase.len()..]);
+    if killer.is_empty() {
+        Some(DeathCause::Other)
+    } else if known_players.iter().any(|player| player == killer) {
+        Some(DeathCause::Player(killer.to_string()))
+    } else {
+        Some(DeathCause::Mob(killer.to_string()))
+    }
+}
+
+/// Removes trailing clauses like " using [Diamond Sword]" from a killer name
+fn strip_killer_suffix(killer: &str) -> &str {
+    let end = [" using ", " with ", " while ", " whilst "]
+        .iter()
+        .filter_map(|suffix| killer.find(suffix))
+        .min()
+        .unwrap_or(killer.len());
+    killer[..end].trim()
+}
+
+/// Parses a single line of the server log, e.g.
+/// `[12:34:56] [Server thread/INFO]: Steve fell from a high place`.
+/// Returns `None` if the line isn't a death message of a known player.
+pub fn parse_death_line(line: &str, known_players: &[String]) -> Option<DeathEvent> {
+    let (prefix, text) = line.split_once("]: ")?;
+    if !prefix.contains("[Server thread/INFO") {
+        return None;
+    }
+    let time = prefix
+        .strip_prefix('[')
+        .and_then(|rest| rest.split(']').next())
+        .unwrap_or_default()
+        .to_string();
+    // Chat messages start with `<name>`, so they never match a bare player name
+    let player = known_players.iter().find(|player| {
+        text.strip_prefix(player.as_str())
+            .is_some_and(|rest| rest.starts_with(' '))
+    })?;
+    let message = text[player.len() + 1..].trim().to_string();
+    let cause = classify_death(&message, known_players)?;
+    Some(DeathEvent {
+        time,
+        player: player.clone(),
+        message,
+        cause,
+    })
+}
+
+/// Parses every death from the given log text
+pub fn parse_deaths(log: &str, known_players: &[String]) -> Vec<DeathEvent> {
+    log.lines()
+        .filter_map(|line| parse_death_line(line, known_players))
+        .collect()
+}
+
+/// Reads every death from a server `logs` directory, including the
+/// gzipped archives the server rotates old logs into
+pub fn read_deaths_from_logs(
+    logs_dir: &Path,
+    known_players: &[String],
+) -> anyhow::Result<Vec<DeathEvent>> {
+    let mut files = fs::read_dir(logs_dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let name = path.to_string_lossy();
+            name.ends_with(".log") || name.ends_with(".log.gz")
+        })
+        .collect::<Vec<_>>();
+    // Archives are named `YYYY-MM-DD-N.log.gz`, so sorting puts them in order
+    // and `latest.log` last
+    files.sort();
+    let mut deaths = vec![];
+    for file in files {
+        let text = if file.to_string_lossy().ends_with(".gz") {
+            let mut text = String::new();
+            GzDecoder::new(fs::File::open(&file)?).read_to_string(&mut text)?;
+            text
+        } else {
+            String::from_utf8_lossy(&fs::read(&file)?).to_string()
+        };
+        deaths.extend(parse_deaths(&text, known_players));
+    }
+    Ok(deaths)
+}
+
+/// Counts deaths per cause for a single player, sorted by count (descending)
+pub fn death_breakdown(deaths: &[DeathEvent], player: &str) -> Vec<(DeathCause, u64)> {
+    let mut counts: HashMap<DeathCause, u64> = HashMap::new();
+    for death in deaths
+        .iter()
+        .filter(|d| d.player.eq_ignore_ascii_case(player))
+    {
+        *counts.entry(death.cause.clone()).or_default() += 1;
+    }
+    let mut counts = counts.into_iter().collect::<Vec<_>>();
+    counts.sort_by(|a, b| {
+        b.1.cmp(&a.1)
+            .then_with(|| a.0.to_string().cmp(&b.0.to_string()))
+    });
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn players() -> Vec<String> {
+        vec!["Steve".to_string(), "Alex".to_string()]
+    }
+
+    fn cause(message: &str) -> Option<DeathCause> {
+        classify_death(message, &players())
+    }
+
+    fn mob(name: &str) -> Option<DeathCause> {
+        Some(DeathCause::Mob(name.to_string()))
+    }
+
+    #[test]
+    fn classifies_environmental_deaths() {
+        let cases = [
+            ("hit the ground too hard", DeathCause::Fall),
+            ("fell from a high place", DeathCause::Fall),
+            ("was doomed to fall by Skeleton", DeathCause::Fall),
+            ("tried to swim in lava to escape Zombie", DeathCause::Lava),
+            ("went up in flames", DeathCause::Fire),
+            (
+                "was burned to a crisp whilst fighting Blaze",
+                DeathCause::Fire,
+            ),
+            ("discovered the floor was lava", DeathCause::Fire),
+            ("drowned", DeathCause::Drowning),
+            ("fell out of the world", DeathCause::Void),
+            ("left the confines of this world", DeathCause::Void),
+            ("blew up", DeathCause::Explosion),
+            ("was blown up by Creeper", DeathCause::Explosion),
+            (
+                "was killed by [Intentional Game Design]",
+                DeathCause::Explosion,
+            ),
+            ("suffocated in a wall", DeathCause::Suffocation),
+            ("was squished too much", DeathCause::Suffocation),
+            ("starved to death", DeathCause::Starvation),
+            ("froze to death", DeathCause::Freezing),
+            ("was frozen to death by Stray", DeathCause::Freezing),
+            ("was struck by lightning", DeathCause::Lightning),
+            ("was killed by magic", DeathCause::Magic),
+            ("was killed by even more magic", DeathCause::Magic),
+            ("was roasted in dragon's breath", DeathCause::Magic),
+            ("withered away", DeathCause::Wither),
+            ("was pricked to death", DeathCause::Contact),
+            (
+                "walked into a cactus while trying to escape Zombie",
+                DeathCause::Contact,
+            ),
+            (
+                "was poked to death by a sweet berry bush",
+                DeathCause::Contact,
+            ),
+            ("experienced kinetic energy", DeathCause::Kinetic),
+            ("was squashed by a falling anvil", DeathCause::FallingBlock),
+            (
+                "was skewered by a falling stalactite",
+                DeathCause::FallingBlock,
+            ),
+            ("was killed", DeathCause::Other),
+            ("died", DeathCause::Other),
+            ("was stung to death", DeathCause::Other),
+        ];
+        for (message, expected) in cases {
+            assert_eq!(cause(message), Some(expected), "{}", message);
+        }
+        assert_eq!(cause("joined the game"), None);
+    }
+
+    #[test]
+    fn classifies_killers() {
+        assert_eq!(cause("was slain by Zombie"), mob("Zombie"));
+        assert_eq!(cause("was shot by Skeleton"), mob("Skeleton"));
+        assert_eq!(cause("was shot by a skull from Wither"), mob("Wither"));
+        assert_eq!(cause("was stung to death by Bee"), mob("Bee"));
+        assert_eq!(cause("was spitballed by Llama"), mob("Llama"));
+        assert_eq!(cause("was killed by Witch using magic"), mob("Witch"));
+        assert_eq!(
+            cause("was obliterated by a sonically-charged shriek"),
+            mob("Warden")
+        );
+        assert_eq!(
+            cause("was slain by Alex using [Diamond Sword]"),
+            Some(DeathCause::Player("Alex".to_string()))
+        );
+        assert_eq!(
+            cause("was killed while trying to hurt Alex"),
+            Some(DeathCause::Player("Alex".to_string()))
+        );
+        assert_eq!(
+            cause("was smashed by Alex with [Mace]"),
+            Some(DeathCause::Player("Alex".to_string()))
+        );
+        assert_eq!(
+            cause("was smashed by Alex"),
+            Some(DeathCause::Player("Alex".to_string()))
+        );
+    }
+
+    #[test]
+    fn parses_log_lines() {
+        let log = "\
+[12:00:00] [Server thread/INFO]: Steve joined the game
+[12:01:00] [Server thread/INFO]: Steve fell from a high place
+[12:02:00] [Server thread/INFO]: <Alex> Steve drowned
+[12:03:00] [Server thread/INFO]: Alex was slain by Steve
+[12:04:00] [Server thread/INFO]: Herobrine drowned
+[12:05:00] [Render thread/INFO]: Steve drowned
+[12:06:00] [Server thread/INFO]: Steve fell from a high place
+";
+        let deaths = parse_deaths(log, &players());
+        let summary = deaths
+            .iter()
+            .map(|death| {
+                (
+                    death.time.as_str(),
+                    death.player.as_str(),
+                    death.cause.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                ("12:01:00", "Steve", DeathCause::Fall),
+                ("12:03:00", "Alex", DeathCause::Player("Steve".to_string())),
+                ("12:06:00", "Steve", DeathCause::Fall),
+            ]
+        );
+        assert_eq!(deaths[0].message, "fell from a high place");
+
+        let breakdown = death_breakdown(&deaths, "steve");
+        assert_eq!(breakdown, [(DeathCause::Fall, 2)]);
+    }
+}
@@ -0,0 +1,488 @@
+use crate::pagination::PageSet;
+use poise::serenity_prelude::{Colour, CreateEmbed, CreateEmbedFooter};
+use stats_parser::i18n::{self, tr_with};
+use stats_parser::report::{ReportRow, SectionStyle, StatsReport};
+
+/// Rows of a paged section shown on one page
+const PAGE_ROWS: usize = 20;
+/// Inline fields shown on one page, a multiple of 3 so the grid stays even
+const PAGE_FIELDS: usize = 24;
+
+// Discord's limits, counted in characters
+const TITLE_LIMIT: usize = 256;
+const DESCRIPTION_LIMIT: usize = 4096;
+const FIELD_NAME_LIMIT: usize = 256;
+const FIELD_VALUE_LIMIT: usize = 1024;
+const FOOTER_LIMIT: usize = 2048;
+/// Title, description, fields and footer of an embed together
+const EMBED_LIMIT: usize = 6000;
+/// Room kept in the footer for the page number, e.g. ` • Page 12/345`, in any language
+const PAGE_NUMBER_LEN: usize = " • Page 9999/9999".len();
+
+type Field = (String, String, bool);
+
+/// The text of one embed before it is built
+#[derive(Default)]
+struct Page {
+    description: Option<String>,
+    lines: Vec<String>,
+    rows: usize,
+    fields: Vec<Field>,
+}
+
+impl Page {
+    fn description(&self) -> Option<String> {
+        let lines = (!self.lines.is_empty()).then(|| self.lines.join("\n"));
+        match (&self.description, lines) {
+            (Some(description), Some(lines)) => Some(format!("{}\n\n{}", description, lines)),
+            (description, lines) => description.clone().or(lines),
+        }
+    }
+
+    fn description_len(&self) -> usize {
+        self.description()
+            .map_or(0, |description| description.chars().count())
+    }
+
+    fn len(&self) -> usize {
+        let fields = self
+            .fields
+            .iter()
+            .map(|(name, value, _)| name.chars().count() + value.chars().count())
+            .sum::<usize>();
+        self.description_len() + fields
+    }
+
+    /// Whether `line` fits in the description without going over `budget` for the whole embed
+    fn fits_line(&self, line: &str, budget: usize) -> bool {
+        // A newline, or a blank line after the report's description
+        let added = line.chars().count() + 2;
+        self.rows < PAGE_ROWS
+            && self.description_len() + added <= DESCRIPTION_LIMIT
+            && self.len() + added <= budget
+    }
+
+    fn fits_field(&self, (name, value, _): &Field, budget: usize) -> bool {
+        self.fields.len() < PAGE_FIELDS
+            && self.len() + name.chars().count() + value.chars().count() <= budget
+    }
+}
+
+/// Cuts `text` to at most `limit` characters, ending it with `…` if anything was cut
+fn truncate(text: &str, limit: usize) -> String {
+    if text.chars().count() <= limit {
+        return text.to_string();
+    }
+    let mut text = text.chars().take(limit - 1).collect::<String>();
+    text.push('…');
+    text
+}
+
+/// Splits a field whose value is too long into several, at line breaks where possible
+fn split_field((name, value, inline): Field) -> Vec<Field> {
+    let name = truncate(&name, FIELD_NAME_LIMIT);
+    if value.is_empty() {
+        // Discord rejects empty field values
+        return vec![(name, "\u{200b}".to_string(), inline)];
+    }
+    let mut values = vec![];
+    let mut current = String::new();
+    for line in value.lines() {
+        let line = truncate(line, FIELD_VALUE_LIMIT);
+        if !current.is_empty()
+            && current.chars().count() + 1 + line.chars().count() > FIELD_VALUE_LIMIT
+        {
+            values.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(&line);
+    }
+    values.push(current);
+    let continued = truncate(&tr_with("continued", &[("name", &name)]), FIELD_NAME_LIMIT);
+    values
+        .into_iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let name = if i == 0 {
+                name.clone()
+            } else {
+                continued.clone()
+            };
+            (name, value, inline)
+        })
+        .collect()
+}
+
+/// Renders a report as embeds. Inline and block sections become fields, paged
+/// sections become description lines, 20 per page. The first page also has the
+/// report's description and the first lines of the paged sections. Fields and
+/// lines are packed so that no embed goes over Discord's limits, long field
+/// values are split into several fields. The page each section starts on is
+/// noted for the section menu.
+pub fn report_pages(report: &StatsReport, colour: Colour) -> PageSet {
+    // Fields with the index of their section
+    let mut fields = vec![];
+    for (index, section) in report.sections.iter().enumerate() {
+        match section.style {
+            SectionStyle::Inline => {
+                fields.extend(section.rows.iter().map(|row| {
+                    let value = if section.columns.is_empty() {
+                        row.values
+                            .iter()
+                            .map(|value| value.format())
+                            .collect::<Vec<_>>()
+                            .join(" | ")
+                    } else {
+                        column_lines(row, &section.columns)
+                    };
+                    let value = match &row.note {
+                        Some(note) => format!("{}\n{}", value, note),
+                        None => value,
+                    };
+                    (index, (row.label.clone(), value, true))
+                }));
+            }
+            SectionStyle::Block if !section.rows.is_empty() => {
+                let lines = section.rows.iter().map(list_line).collect::<Vec<_>>();
+                fields.push((index, (section.title.clone(), lines.join("\n"), false)));
+            }
+            SectionStyle::Block | SectionStyle::Paged => {}
+        }
+    }
+
+    let title = truncate(&report.title, TITLE_LIMIT);
+    let footer = report
+        .footer
+        .as_ref()
+        .map(|footer| truncate(footer, FOOTER_LIMIT - PAGE_NUMBER_LEN));
+    let budget = EMBED_LIMIT
+        - title.chars().count()
+        - footer.as_ref().map_or(0, |footer| footer.chars().count())
+        - PAGE_NUMBER_LEN;
+
+    let mut pages = vec![Page {
+        description: report
+            .description
+            .as_ref()
+            .map(|description| truncate(description, DESCRIPTION_LIMIT.min(budget))),
+        ..Default::default()
+    }];
+    let mut sections = vec![];
+    for (index, field) in fields {
+        for field in split_field(field) {
+            if !pages
+                .last()
+                .is_some_and(|page| page.fits_field(&field, budget))
+            {
+                pages.push(Page::default());
+            }
+            if sections.last().is_none_or(|(section, _)| *section != index) {
+                sections.push((index, pages.len() - 1));
+            }
+            pages.last_mut().unwrap().fields.push(field);
+        }
+    }
+    let field_pages = pages.len();
+
+    // The paged sections start on the first page, then continue after the
+    // fields. Each section starts on a new page headed by its title when there
+    // are several.
+    let paged = report
+        .sections
+        .iter()
+        .enumerate()
+        .filter(|(_, section)| section.style == SectionStyle::Paged && !section.rows.is_empty())
+        .collect::<Vec<_>>();
+    let mut current = 0;
+    for (index, section) in &paged {
+        let heading = (paged.len() > 1)
+            .then(|| format!("**{}**", truncate(&section.title, FIELD_NAME_LIMIT)));
+        if !pages[current].lines.is_empty() {
+            current = (current + 1).max(field_pages);
+        }
+        let mut started = false;
+        for row in &section.rows {
+            let line = truncate(&list_line(row), FIELD_VALUE_LIMIT);
+            loop {
+                if current == pages.len() {
+                    pages.push(Page::default());
+                }
+                let page = &mut pages[current];
+                let heading = heading.as_ref().filter(|_| page.lines.is_empty());
+                let fits = match heading {
+                    Some(heading) => page.fits_line(&format!("{}\n{}", heading, line), budget),
+                    None => page.fits_line(&line, budget),
+                };
+                if fits {
+                    page.lines.extend(heading.cloned());
+                    page.lines.push(line);
+                    page.rows += 1;
+                    if !started {
+                        sections.push((*index, current));
+                        started = true;
+                    }
+                    break;
+                }
+                current = (current + 1).max(field_pages);
+            }
+        }
+    }
+
+    let page_count = pages.len();
+    sections.sort_by_key(|(_, page)| *page);
+    let sections = sections
+        .into_iter()
+        .map(|(index, page)| (report.sections[index].title.clone(), page))
+        .collect();
+    let pages = pages
+        .into_iter()
+        .enumerate()
+        .map(|(i, page)| {
+            let mut embed = CreateEmbed::new().title(&title).color(colour);
+            if let Some(description) = page.description() {
+                embed = embed.description(description);
+            }
+            embed = embed.fields(page.fields);
+            let page_number = tr_with("page", &[("page", &(i + 1)), ("count", &page_count)]);
+            let footer = match (&footer, page_count) {
+                (Some(footer), 1) => Some(footer.clone()),
+                (Some(footer), _) => Some(format!("{} • {}", footer, page_number)),
+                (None, 1) => None,
+                (None, _) => Some(page_number),
+            };
+            if let Some(footer) = footer {
+                embed = embed.footer(CreateEmbedFooter::new(footer));
+            }
+            embed
+        })
+        .collect();
+    PageSet {
+        label: String::new(),
+        pages,
+        sections,
+        language: i18n::current(),
+    }
+}
+
+/// Formats a row of a list, e.g. `**Stone Mined**: 42` or `- Item`
+fn list_line(row: &ReportRow) -> String {
+    if row.values.is_empty() {
+        return format!("- {}", row.label);
+    }
+    let values = row
+        .values
+        .iter()
+        .map(|value| value.format())
+        .collect::<Vec<_>>()
+        .join(" | ");
+    match &row.note {
+        Some(note) => format!("**{}**: {} ({})", row.label, values, note),
+        None => format!("**{}**: {}", row.label, values),
+    }
+}
+
+/// One line per column, with the best value in bold
+fn column_lines(row: &ReportRow, columns: &[String]) -> String {
+    columns
+        .iter()
+        .zip(&row.values)
+        .enumerate()
+        .map(|(i, (column, value))| {
+            if row.best == Some(i) {
+                format!("**{}: {}**", column, value.format())
+            } else {
+                format!("{}: {}", column, value.format())
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use stats_parser::i18n::Language;
+    use stats_parser::report::{ReportSection, ReportValue};
+
+    fn chars(value: &Value) -> usize {
+        value.as_str().map_or(0, |text| text.chars().count())
+    }
+
+    /// A report with every section style and text over every limit
+    fn huge_report() -> StatsReport {
+        let long = |prefix: &str, len: usize| format!("{} {}", prefix, "x".repeat(len));
+        let inline = (0..60)
+            .map(|i| {
+                let row = ReportRow::new(long(&i.to_string(), 300), ReportValue::count(i));
+                if i % 7 == 0 {
+                    // Several lines, too long for one field value
+                    row.note(vec![long("note", 400); 5].join("\n"))
+                } else {
+                    row
+                }
+            })
+            .collect();
+        let block = (0..200)
+            .map(|i| ReportRow::item(long(&format!("item {}", i), 40)))
+            .collect();
+        let paged = |rows: usize, len: usize| {
+            (0..rows)
+                .map(|i| ReportRow::new(long(&i.to_string(), len), ReportValue::count(i as u32)))
+                .collect::<Vec<_>>()
+        };
+        StatsReport::new(long("title", 300))
+            .description(long("description", 5000))
+            .footer(long("footer", 2100))
+            .section(ReportSection::new("Inline", SectionStyle::Inline, inline))
+            .section(ReportSection::new(
+                long("Block", 300),
+                SectionStyle::Block,
+                block,
+            ))
+            .section(ReportSection::new(
+                "Short rows",
+                SectionStyle::Paged,
+                paged(450, 10),
+            ))
+            .section(ReportSection::new(
+                "Long rows",
+                SectionStyle::Paged,
+                paged(120, 1500),
+            ))
+            .section(ReportSection::new("Empty", SectionStyle::Paged, vec![]))
+    }
+
+    #[test]
+    fn report_pages_stay_within_discord_limits() {
+        let report = huge_report();
+        let pages = report_pages(&report, Colour::BLUE);
+        let count = pages.pages.len();
+        assert!(count > 10, "only {} pages", count);
+
+        let mut rows = 0;
+        for (i, page) in pages.pages.iter().enumerate() {
+            let embed = serde_json::to_value(page).unwrap();
+            let fields = embed["fields"].as_array().cloned().unwrap_or_default();
+            let footer = embed["footer"]["text"].as_str().unwrap();
+            assert!(chars(&embed["title"]) <= TITLE_LIMIT);
+            assert!(chars(&embed["description"]) <= DESCRIPTION_LIMIT);
+            assert!(footer.chars().count() <= FOOTER_LIMIT);
+            assert!(
+                fields.len() <= 25,
+                "page {} has {} fields",
+                i + 1,
+                fields.len()
+            );
+            for field in &fields {
+                assert!(chars(&field["name"]) <= FIELD_NAME_LIMIT);
+                assert!((1..=FIELD_VALUE_LIMIT).contains(&chars(&field["value"])));
+            }
+            let total = chars(&embed["title"])
+                + chars(&embed["description"])
+                + footer.chars().count()
+                + fields
+                    .iter()
+                    .map(|field| chars(&field["name"]) + chars(&field["value"]))
+                    .sum::<usize>();
+            assert!(
+                total <= EMBED_LIMIT,
+                "page {} has {} characters",
+                i + 1,
+                total
+            );
+            assert!(
+                footer.ends_with(&format!(" • Page {}/{}", i + 1, count)),
+                "page {} has the footer {}",
+                i + 1,
+                footer
+            );
+            // Pages of a paged section start with its title
+            let lines = embed["description"]
+                .as_str()
+                .map_or(vec![], |text| text.lines().collect::<Vec<_>>());
+            rows += lines
+                .iter()
+                .filter(|line| line.starts_with("**"))
+                .filter(|line| !["**Short rows**", "**Long rows**"].contains(line))
+                .count();
+        }
+        assert_eq!(rows, 450 + 120);
+
+        let titles = pages
+            .sections
+            .iter()
+            .map(|(title, _)| title.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(titles.len(), 4);
+        assert!(!titles.contains(&"Empty"));
+        assert!(pages.sections.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+        assert!(pages.sections.iter().all(|(_, page)| *page < count));
+    }
+
+    #[test]
+    fn report_pages_without_overflow_are_one_page() {
+        let report = StatsReport::new("Title")
+            .footer("Footer")
+            .section(ReportSection::new(
+                "Rows",
+                SectionStyle::Paged,
+                vec![ReportRow::new("Stone Mined", ReportValue::count(42))],
+            ));
+        let pages = report_pages(&report, Colour::BLUE);
+        assert_eq!(pages.pages.len(), 1);
+        let embed = serde_json::to_value(&pages.pages[0]).unwrap();
+        assert_eq!(embed["description"], "**Stone Mined**: 42");
+        assert_eq!(embed["footer"]["text"], "Footer");
+        assert_eq!(pages.sections, [("Rows".to_string(), 0)]);
+    }
+
+    #[test]
+    fn split_field_continues_long_values() {
+        let line = "y".repeat(600);
+        let value = [line.as_str(); 3].join("\n");
+        let fields = split_field(("Name".to_string(), value, true));
+        assert_eq!(fields.len(), 3);
+        assert_eq!(fields[0].0, "Name");
+        assert_eq!(fields[1].0, "Name (cont.)");
+        assert_eq!(fields[2].0, "Name (cont.)");
+        assert!(
+            fields
+                .iter()
+                .all(|(_, value, inline)| *value == line && *inline)
+        );
+
+        // Lines that fit together share a field
+        let value = ["a", "b", &"c".repeat(1023)].join("\n");
+        let fields = split_field(("Name".to_string(), value, false));
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0].1, "a\nb");
+
+        // A single line over the limit is cut
+        let fields = split_field(("Name".to_string(), "z".repeat(2000), false));
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields[0].1.chars().count(), FIELD_VALUE_LIMIT);
+        assert!(fields[0].1.ends_with('…'));
+
+        let fields = split_field(("Name".to_string(), String::new(), false));
+        assert_eq!(fields[0].1, "\u{200b}");
+    }
+
+    #[test]
+    fn split_field_names_continuations_in_the_current_language() {
+        let value = ["y".repeat(1000), "y".repeat(1000)].join("\n");
+        let fields = Language::Hindi.scope(|| split_field(("नाम".to_string(), value, true)));
+        assert_eq!(fields[1].0, "नाम (जारी)");
+
+        // The name is cut to fit, even with the suffix
+        let value = ["y".repeat(1000), "y".repeat(1000)].join("\n");
+        let fields = split_field(("n".repeat(300), value, true));
+        assert!(
+            fields
+                .iter()
+                .all(|(name, _, _)| name.chars().count() <= FIELD_NAME_LIMIT)
+        );
+    }
+}
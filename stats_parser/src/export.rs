@@ -0,0 +1,330 @@
+use crate::store::StatsStore;
+use crate::{STAT_CATEGORIES, convert_to_readable, readable_stat_name};
+use anyhow::Result;
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+
+/// File formats stats can be exported as
+#[derive(poise::ChoiceParameter, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    #[name = "CSV"]
+    #[name = "csv"]
+    Csv,
+    #[name = "NDJSON"]
+    #[name = "ndjson"]
+    Ndjson,
+    #[name = "JSON"]
+    #[name = "json"]
+    Json,
+}
+
+impl ExportFormat {
+    /// File extension, without the dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+/// Players × stats table, one row per player and one column per stat
+#[derive(Debug, Clone)]
+pub struct StatsMatrix {
+    /// `(category, key)` of each column, e.g. `("minecraft:mined", "minecraft:stone")`
+    pub columns: Vec<(String, String)>,
+    /// Readable stat names, in column order. Only CSV files use them, JSON
+    /// keeps the keys of the stats files.
+    pub headers: Vec<String>,
+    /// `(username, uuid, values)`, with the values in the same order as `columns`
+    pub rows: Vec<(String, String, Vec<i64>)>,
+}
+
+impl StatsMatrix {
+    /// Builds the table from every player in the store. With a category, e.g.
+    /// `minecraft:mined`, only its stats become columns. Without one, every
+    /// category is included and the headers are prefixed with the category name.
+    pub fn build(store: &StatsStore, category: Option<&str>) -> Self {
+        let players = store.players();
+        let categories = match category {
+            Some(category) => vec![category],
+            // Modded categories only exist in some players' files
+            None => STAT_CATEGORIES
+                .iter()
+                .copied()
+                .chain(
+                    players
+                        .iter()
+                        .flat_map(|(_, stats)| stats.stats.modded.keys().map(String::as_str))
+                        .collect::<BTreeSet<_>>(),
+                )
+                .collect(),
+        };
+        // Every stat any player has, so the columns line up across rows
+        let columns = categories
+            .iter()
+            .flat_map(|category| {
+                let keys = players
+                    .iter()
+                    .filter_map(|(_, stats)| stats.stats.category(category))
+                    .flat_map(|stats| stats.keys().cloned())
+                    .collect::<BTreeSet<_>>();
+                keys.into_iter().map(move |key| (category.to_string(), key))
+            })
+            .collect::<Vec<_>>();
+        let headers = columns
+            .iter()
+            .map(|(category_key, key)| {
+                let name = readable_stat_name(category_key, key);
+                if category.is_some() {
+                    name
+                } else {
+                    format!("{}: {}", convert_to_readable(category_key), name)
+                }
+            })
+            .collect();
+        let mut rows = players
+            .iter()
+            .map(|(uuid, stats)| {
+                let values = columns
+                    .iter()
+                    .map(|(category, key)| stats.stats.get(category, key))
+                    .collect();
+                let name = store.username(uuid).unwrap_or_else(|| uuid.clone());
+                (name, uuid.clone(), values)
+            })
+            .collect::<Vec<_>>();
+        rows.sort_by_key(|(name, _, _)| name.to_lowercase());
+        StatsMatrix {
+            columns,
+            headers,
+            rows,
+        }
+    }
+
+    /// Encodes the table in the given format
+    pub fn export(&self, format: ExportFormat) -> Result<Vec<u8>> {
+        let out = match format {
+            ExportFormat::Csv => self.to_csv(),
+            ExportFormat::Ndjson => self
+                .to_objects()
+                .iter()
+                .map(|object| Ok(serde_json::to_string(object)? + "\n"))
+                .collect::<Result<String>>()?,
+            ExportFormat::Json => serde_json::to_string_pretty(&self.to_objects())?,
+        };
+        Ok(out.into_bytes())
+    }
+
+    fn to_csv(&self) -> String {
+        let mut out = String::new();
+        let header = ["Player", "UUID"]
+            .into_iter()
+            .chain(self.headers.iter().map(String::as_str))
+            .map(csv_field)
+            .collect::<Vec<_>>();
+        out.push_str(&header.join(","));
+        out.push_str("\r\n");
+        for (name, uuid, values) in &self.rows {
+            let row = [csv_field(name), csv_field(uuid)]
+                .into_iter()
+                .chain(values.iter().map(|value| value.to_string()))
+                .collect::<Vec<_>>();
+            out.push_str(&row.join(","));
+            out.push_str("\r\n");
+        }
+        out
+    }
+
+    /// One object per player, with the stats by category and key like in
+    /// the stats files, e.g. `{"name": .., "uuid": .., "stats": {"minecraft:mined": {..}}}`
+    fn to_objects(&self) -> Vec<Map<String, Value>> {
+        self.rows
+            .iter()
+            .map(|(name, uuid, values)| {
+                let mut stats = Map::new();
+                for ((category, key), value) in self.columns.iter().zip(values) {
+                    let category = stats
+                        .entry(category.clone())
+                        .or_insert_with(|| Value::Object(Map::new()));
+                    if let Value::Object(category) = category {
+                        category.insert(key.clone(), Value::from(*value));
+                    }
+                }
+                let mut object = Map::new();
+                object.insert("name".to_string(), Value::from(name.as_str()));
+                object.insert("uuid".to_string(), Value::from(uuid.as_str()));
+                object.insert("stats".to_string(), Value::Object(stats));
+                object
+            })
+            .collect()
+    }
+}
+
+/// Quotes a CSV field if it contains a separator, quote or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const STEVE: &str = "069a79f4-44e9-4726-a5be-fca90e38aaf5";
+    const ALEX: &str = "853c80ef-3c37-49fd-aa49-938b674adae6";
+
+    /// A store of `(name, uuid, stats json)` players in a fresh directory
+    fn store(name: &str, players: &[(&str, &str, Value)]) -> StatsStore {
+        let dir = std::env::temp_dir().join(format!("export-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        let stats_dir = dir.join("stats");
+        std::fs::create_dir_all(&stats_dir).unwrap();
+        let usercache = players
+            .iter()
+            .map(|(name, uuid, stats)| {
+                let json = json!({ "DataVersion": 3953, "stats": stats });
+                std::fs::write(stats_dir.join(format!("{}.json", uuid)), json.to_string()).unwrap();
+                json!({ "name": name, "uuid": uuid, "expiresOn": "2999-01-01 00:00:00 +0000" })
+            })
+            .collect::<Vec<_>>();
+        let usercache_path = dir.join("usercache.json");
+        std::fs::write(&usercache_path, Value::from(usercache).to_string()).unwrap();
+        StatsStore::load(stats_dir, usercache_path).unwrap()
+    }
+
+    fn players() -> StatsStore {
+        store(
+            "players",
+            &[
+                (
+                    "steve",
+                    STEVE,
+                    json!({
+                        "minecraft:mined": { "minecraft:stone": 10 },
+                        "minecraft:custom": { "minecraft:deaths": 2 },
+                        "create:stats": { "create:wrench_used": 5 }
+                    }),
+                ),
+                (
+                    "Alex",
+                    ALEX,
+                    json!({ "minecraft:mined": { "minecraft:dirt": 3 } }),
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn builds_one_category() {
+        let matrix = StatsMatrix::build(&players(), Some("minecraft:mined"));
+        assert_eq!(
+            matrix.columns,
+            [
+                ("minecraft:mined".to_string(), "minecraft:dirt".to_string()),
+                ("minecraft:mined".to_string(), "minecraft:stone".to_string()),
+            ]
+        );
+        assert_eq!(matrix.headers.len(), 2);
+        // Sorted by name ignoring case, with missing stats as zero
+        assert_eq!(
+            matrix.rows,
+            [
+                ("Alex".to_string(), ALEX.to_string(), vec![3, 0]),
+                ("steve".to_string(), STEVE.to_string(), vec![0, 10]),
+            ]
+        );
+    }
+
+    #[test]
+    fn builds_every_category() {
+        let matrix = StatsMatrix::build(&players(), None);
+        let categories = matrix
+            .columns
+            .iter()
+            .map(|(category, _)| category.as_str())
+            .collect::<BTreeSet<_>>();
+        assert_eq!(
+            categories,
+            BTreeSet::from(["create:stats", "minecraft:custom", "minecraft:mined"])
+        );
+        assert!(matrix.headers.iter().all(|header| header.contains(": ")));
+    }
+
+    fn matrix() -> StatsMatrix {
+        StatsMatrix {
+            columns: vec![
+                (
+                    "minecraft:custom".to_string(),
+                    "minecraft:deaths".to_string(),
+                ),
+                ("minecraft:mined".to_string(), "minecraft:stone".to_string()),
+            ],
+            headers: vec!["Deaths, total".to_string(), "Stone \"mined\"".to_string()],
+            rows: vec![
+                ("Alex".to_string(), ALEX.to_string(), vec![1, 2]),
+                ("Steve".to_string(), STEVE.to_string(), vec![-3, 4]),
+            ],
+        }
+    }
+
+    #[test]
+    fn quotes_csv_fields() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field("cr\r"), "\"cr\r\"");
+    }
+
+    #[test]
+    fn exports_csv() {
+        let csv = String::from_utf8(matrix().export(ExportFormat::Csv).unwrap()).unwrap();
+        assert_eq!(
+            csv,
+            format!(
+                "Player,UUID,\"Deaths, total\",\"Stone \"\"mined\"\"\"\r\n\
+                 Alex,{},1,2\r\n\
+                 Steve,{},-3,4\r\n",
+                ALEX, STEVE
+            )
+        );
+    }
+
+    #[test]
+    fn exports_json_by_raw_keys() {
+        let expected = json!([
+            {
+                "name": "Alex",
+                "uuid": ALEX,
+                "stats": {
+                    "minecraft:custom": { "minecraft:deaths": 1 },
+                    "minecraft:mined": { "minecraft:stone": 2 }
+                }
+            },
+            {
+                "name": "Steve",
+                "uuid": STEVE,
+                "stats": {
+                    "minecraft:custom": { "minecraft:deaths": -3 },
+                    "minecraft:mined": { "minecraft:stone": 4 }
+                }
+            }
+        ]);
+        let json = matrix().export(ExportFormat::Json).unwrap();
+        assert_eq!(serde_json::from_slice::<Value>(&json).unwrap(), expected);
+
+        let ndjson = String::from_utf8(matrix().export(ExportFormat::Ndjson).unwrap()).unwrap();
+        assert!(ndjson.ends_with('\n'));
+        let lines = ndjson
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(Value::from(lines), expected);
+    }
+}
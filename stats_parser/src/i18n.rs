@@ -0,0 +1,176 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Languages the bot can answer in. Strings missing from a language fall back
+/// to English.
+#[derive(
+    poise::ChoiceParameter, Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    #[default]
+    #[name = "English"]
+    #[name = "en"]
+    English,
+
+    #[name = "हिन्दी (Hindi)"]
+    #[name = "hi"]
+    Hindi,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Hindi];
+
+    /// Discord's locale, used for localised command names and descriptions
+    pub fn discord_locale(&self) -> &'static str {
+        match self {
+            Language::English => "en-US",
+            Language::Hindi => "hi",
+        }
+    }
+
+    /// Name of Minecraft's language file, e.g. `hi_in` for `hi_in.json`
+    pub fn minecraft_code(&self) -> &'static str {
+        match self {
+            Language::English => "en_us",
+            Language::Hindi => "hi_in",
+        }
+    }
+
+    /// Picks the language of a Discord or Minecraft locale, e.g. `hi` or `en-GB`
+    pub fn from_locale(locale: &str) -> Option<Self> {
+        let code = locale.split(['-', '_']).next()?.to_lowercase();
+        match code.as_str() {
+            "en" => Some(Language::English),
+            "hi" => Some(Language::Hindi),
+            _ => None,
+        }
+    }
+
+    fn catalogue(&self) -> &'static HashMap<String, String> {
+        static CATALOGUES: OnceLock<HashMap<Language, HashMap<String, String>>> = OnceLock::new();
+        let catalogues = CATALOGUES.get_or_init(|| {
+            let parse = |json: &str| serde_json::from_str(json).expect("invalid message catalogue");
+            HashMap::from([
+                (Language::English, parse(include_str!("../locales/en.json"))),
+                (Language::Hindi, parse(include_str!("../locales/hi.json"))),
+            ])
+        });
+        &catalogues[self]
+    }
+
+    /// Gets a message of this language, or `None` if it has not been translated
+    pub fn message(&self, key: &str) -> Option<&'static str> {
+        self.catalogue().get(key).map(String::as_str)
+    }
+
+    /// Runs `f` with this as the [`current`] language of the thread
+    pub fn scope<T>(self, f: impl FnOnce() -> T) -> T {
+        let previous = CURRENT.replace(Some(self));
+        let out = f();
+        CURRENT.set(previous);
+        out
+    }
+}
+
+static DEFAULT: OnceLock<Language> = OnceLock::new();
+
+thread_local! {
+    static CURRENT: Cell<Option<Language>> = const { Cell::new(None) };
+}
+
+/// Sets the language used outside of [`Language::scope`], e.g. for the CLI
+pub fn set_default(language: Language) {
+    let _ = DEFAULT.set(language);
+}
+
+/// The language strings are translated into: the one of the surrounding
+/// [`Language::scope`], or else the default
+pub fn current() -> Language {
+    CURRENT
+        .get()
+        .or_else(|| DEFAULT.get().copied())
+        .unwrap_or_default()
+}
+
+/// Translates a message into the current language, e.g. `tr("playtime.title")`.
+/// Untranslated messages fall back to English, and unknown keys to the key itself.
+pub fn tr(key: &str) -> String {
+    tr_with(key, &[])
+}
+
+/// Translates a message with placeholders, e.g.
+/// `tr_with("stats.title", &[("player", &name)])` for `Stats for {player}`
+pub fn tr_with(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    let template = current()
+        .message(key)
+        .or_else(|| Language::English.message(key))
+        .unwrap_or(key);
+    args.iter()
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), &value.to_string())
+        })
+}
+
+/// Languages picked with the bot's `/language` command, by Discord id
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct LanguagePreferences {
+    #[serde(default)]
+    pub users: HashMap<u64, Language>,
+    #[serde(default)]
+    pub guilds: HashMap<u64, Language>,
+}
+
+impl LanguagePreferences {
+    /// Loads the preferences, or none if the file does not exist yet
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// The user's language, then the server's, then the one of their Discord client
+    pub fn resolve(&self, user: u64, guild: Option<u64>, locale: Option<&str>) -> Language {
+        self.users
+            .get(&user)
+            .or_else(|| guild.and_then(|guild| self.guilds.get(&guild)))
+            .copied()
+            .or_else(|| locale.and_then(Language::from_locale))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_message_is_translated() {
+        for key in Language::English.catalogue().keys() {
+            assert!(
+                Language::Hindi.message(key).is_some(),
+                "{} is not translated",
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn translates_in_scope() {
+        assert_eq!(tr_with("page", &[("page", &1), ("count", &2)]), "Page 1/2");
+        let hindi = Language::Hindi.scope(|| tr_with("page", &[("page", &1), ("count", &2)]));
+        assert_eq!(hindi, "पृष्ठ 1/2");
+        assert_eq!(tr("no.such.key"), "no.such.key");
+    }
+}
@@ -0,0 +1,176 @@
+use crate::i18n::{self, Language};
+use crate::stat_key::StatKey;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+static STAT_NAMES: OnceLock<HashMap<Language, StatNames>> = OnceLock::new();
+static NAME_OVERRIDES: OnceLock<NameOverrides> = OnceLock::new();
+
+/// Readable names from a Minecraft language file, e.g. `en_us.json` or
+/// `hi_in.json` from the client jar's `assets/minecraft/lang`. Mods ship their own files, which can
+/// be merged in to name modded blocks and mobs.
+#[derive(Debug, Default)]
+pub struct StatNames {
+    translations: HashMap<String, String>,
+}
+
+impl StatNames {
+    /// Parses a language file: one object of translation keys, e.g.
+    /// `{"block.minecraft.stone": "Stone"}`
+    pub fn parse(json: &str) -> Result<Self> {
+        Ok(StatNames {
+            translations: serde_json::from_str(json)?,
+        })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read {}", path.display()))?;
+        Self::parse(&json).with_context(|| format!("Could not parse {}", path.display()))
+    }
+
+    /// Adds the names of another file, e.g. a mod's, replacing existing ones
+    pub fn merge(&mut self, other: StatNames) {
+        self.translations.extend(other.translations);
+    }
+
+    pub fn translation(&self, key: &str) -> Option<&str> {
+        self.translations.get(key).map(String::as_str)
+    }
+
+    /// Names a stat of a category, e.g. `Stone Mined` for `minecraft:stone`
+    /// in `minecraft:mined`
+    pub fn stat_name(&self, category: &str, key: &str) -> Option<String> {
+        let key = StatKey::from_ids(category, key);
+        let lookup =
+            |kind: &str| self.translation(&format!("{}.{}.{}", kind, key.namespace, key.path));
+        let name = match category {
+            "minecraft:custom" => return lookup("stat").map(str::to_string),
+            // Blocks and items share ids, but only blocks can be mined
+            "minecraft:mined" => lookup("block").or_else(|| lookup("item")),
+            "minecraft:killed" | "minecraft:killed_by" | "minecraft:custom_entity" => {
+                lookup("entity")
+            }
+            _ => lookup("item").or_else(|| lookup("block")),
+        }?;
+        let template = format!("stat_name.{}", category);
+        if i18n::Language::English.message(&template).is_none() {
+            return Some(name.to_string());
+        }
+        Some(i18n::tr_with(&template, &[("name", &name)]))
+    }
+
+    /// Loads the language file of every [`Language`] found in `dir`, e.g.
+    /// `en_us.json` and `hi_in.json`
+    pub fn load_dir(dir: impl AsRef<Path>) -> Result<HashMap<Language, StatNames>> {
+        let mut names = HashMap::new();
+        for language in Language::ALL {
+            let path = dir
+                .as_ref()
+                .join(format!("{}.json", language.minecraft_code()));
+            if path.exists() {
+                names.insert(language, Self::load(path)?);
+            }
+        }
+        Ok(names)
+    }
+}
+
+/// Makes [`crate::readable_stat_name`] use these names, by language. Can only
+/// be done once, at startup.
+pub fn install(names: HashMap<Language, StatNames>) -> Result<()> {
+    STAT_NAMES
+        .set(names)
+        .map_err(|_| anyhow::anyhow!("Stat names are already installed"))
+}
+
+/// Gets the installed names of a language, if its language file was loaded
+pub fn stat_names(language: Language) -> Option<&'static StatNames> {
+    STAT_NAMES.get()?.get(&language)
+}
+
+/// Names for the stats of one mod or datapack, from `<namespace>.toml`, e.g. `create.toml`:
+///
+/// ```toml
+/// # The `create:goggles` category
+/// [categories]
+/// goggles = "Goggle Stats"
+///
+/// # The `create:wrench_uses` stat in `minecraft:custom`
+/// [stats."minecraft:custom"]
+/// wrench_uses = "Wrench Uses"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+struct NamespaceNames {
+    #[serde(default)]
+    categories: HashMap<String, String>,
+    /// Stat names by category key, then by path
+    #[serde(default)]
+    stats: HashMap<String, HashMap<String, String>>,
+}
+
+/// Names supplied by admins for stats of mods and datapacks, which have no
+/// built-in names and are often missing from language files
+#[derive(Debug, Default)]
+pub struct NameOverrides {
+    namespaces: HashMap<String, NamespaceNames>,
+}
+
+impl NameOverrides {
+    /// Loads every `<namespace>.toml` in `dir`. A missing directory has no overrides.
+    pub fn load_dir(dir: impl AsRef<Path>) -> Result<Self> {
+        let mut namespaces = HashMap::new();
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Ok(Self::default());
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_none_or(|extension| extension != "toml") {
+                continue;
+            }
+            let Some(namespace) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let text = std::fs::read_to_string(&path)
+                .with_context(|| format!("Could not read {}", path.display()))?;
+            let names = toml::from_str(&text)
+                .with_context(|| format!("Could not parse {}", path.display()))?;
+            namespaces.insert(namespace.to_string(), names);
+        }
+        Ok(NameOverrides { namespaces })
+    }
+
+    /// Names a category, e.g. `create:goggles`
+    pub fn category_name(&self, category: &str) -> Option<&str> {
+        let (namespace, path) = category.split_once(':')?;
+        let names = self.namespaces.get(namespace)?;
+        names.categories.get(path).map(String::as_str)
+    }
+
+    /// Names a stat of a category, e.g. `create:wrench_uses` in `minecraft:custom`
+    pub fn stat_name(&self, category: &str, key: &str) -> Option<&str> {
+        let key = StatKey::from_ids(category, key);
+        let names = self.namespaces.get(&key.namespace)?;
+        names
+            .stats
+            .get(category)?
+            .get(&key.path)
+            .map(String::as_str)
+    }
+}
+
+/// Makes [`crate::readable_stat_name`] and [`crate::MinecraftStats::get_readable_name`]
+/// prefer these names. Can only be done once, at startup.
+pub fn install_overrides(names: NameOverrides) -> Result<()> {
+    NAME_OVERRIDES
+        .set(names)
+        .map_err(|_| anyhow::anyhow!("Name overrides are already installed"))
+}
+
+pub fn name_overrides() -> Option<&'static NameOverrides> {
+    NAME_OVERRIDES.get()
+}
@@ -0,0 +1,74 @@
+use crate::metrics::{DerivedMetric, playtime_hours};
+use crate::report::Unit;
+use crate::{MinecraftStats, readable_stat_name};
+use std::sync::Arc;
+
+/// What a leaderboard or comparison ranks players by
+#[derive(Debug, Clone, PartialEq)]
+pub enum LeaderboardTarget {
+    /// A raw stat, e.g. `minecraft:mined` / `minecraft:diamond_ore`
+    Stat {
+        category: String,
+        stat: String,
+    },
+    Metric(DerivedMetric),
+}
+
+impl LeaderboardTarget {
+    pub fn get_name(&self) -> String {
+        match self {
+            LeaderboardTarget::Stat { category, stat } => readable_stat_name(category, stat),
+            LeaderboardTarget::Metric(metric) => metric.get_name(),
+        }
+    }
+
+    pub fn value(&self, stats: &MinecraftStats) -> Option<f64> {
+        match self {
+            LeaderboardTarget::Stat { category, stat } => {
+                Some(stats.stats.get(category, stat) as f64)
+            }
+            LeaderboardTarget::Metric(metric) => metric.compute(&stats.stats),
+        }
+    }
+
+    pub fn unit(&self) -> Unit {
+        match self {
+            LeaderboardTarget::Stat { category, stat } => Unit::of_stat(category, stat),
+            LeaderboardTarget::Metric(metric) => metric.unit(),
+        }
+    }
+
+    pub fn format(&self, value: f64) -> String {
+        self.unit().format(value)
+    }
+
+    fn higher_is_better(&self) -> bool {
+        match self {
+            LeaderboardTarget::Stat { .. } => true,
+            LeaderboardTarget::Metric(metric) => metric.higher_is_better(),
+        }
+    }
+}
+
+/// Ranks players by `target`, best first. Players with less than
+/// `min_playtime_hours` of playtime, or without a value, are left out.
+pub fn leaderboard(
+    players: &[(String, Arc<MinecraftStats>)],
+    target: &LeaderboardTarget,
+    min_playtime_hours: f64,
+) -> Vec<(String, f64)> {
+    let mut ranking = players
+        .iter()
+        .filter(|(_, stats)| playtime_hours(&stats.stats) >= min_playtime_hours)
+        .filter_map(|(uuid, stats)| Some((uuid.clone(), target.value(stats)?)))
+        .collect::<Vec<_>>();
+    ranking.sort_by(|a, b| {
+        let order = a.1.total_cmp(&b.1);
+        if target.higher_is_better() {
+            order.reverse()
+        } else {
+            order
+        }
+    });
+    ranking
+}
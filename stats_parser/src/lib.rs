@@ -1,15 +1,40 @@
 #![allow(dead_code)]
+pub mod access_lists;
+pub mod charts;
+pub mod deaths;
+pub mod export;
+pub mod i18n;
+pub mod lang;
+pub mod leaderboard;
+pub mod metrics;
+pub mod nbt;
+pub mod profile_card;
+pub mod report;
+pub mod schema;
+pub mod scoreboard;
+pub mod server_properties;
+pub mod snapshots;
+pub mod stat_key;
+pub mod store;
+pub mod superlatives;
+pub mod totals;
+pub mod users;
+pub mod uuids;
+
+use i18n::Language;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use stat_key::{CustomStat, StatKey};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::LazyLock;
 
 /// Main structure for Minecraft player statistics
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MinecraftStats {
     /// The player's statistics, organized by category
     pub stats: StatCategories,
-    /// The player's data version
+    /// The DataVersion of the Minecraft version that wrote the file, missing before 1.13
     #[serde(rename = "DataVersion")]
-    pub data_version: i32,
+    pub data_version: Option<i32>,
 }
 
 /// Categories of statistics as defined by Minecraft
@@ -17,47 +42,134 @@ pub struct MinecraftStats {
 pub struct StatCategories {
     /// Custom statistics, including movement, deaths, and time played
     #[serde(rename = "minecraft:custom")]
-    pub custom: HashMap<String, i32>,
+    pub custom: HashMap<String, i64>,
 
     /// Blocks broken/mined by the player
     #[serde(rename = "minecraft:mined")]
-    pub mined: Option<HashMap<String, i32>>,
+    pub mined: Option<HashMap<String, i64>>,
 
     /// Items crafted by the player
     #[serde(rename = "minecraft:crafted")]
-    pub crafted: Option<HashMap<String, i32>>,
+    pub crafted: Option<HashMap<String, i64>>,
 
     /// Items used by the player
     #[serde(rename = "minecraft:used")]
-    pub used: Option<HashMap<String, i32>>,
+    pub used: Option<HashMap<String, i64>>,
 
     /// Items broken by the player (tools, armor, etc.)
     #[serde(rename = "minecraft:broken")]
-    pub broken: Option<HashMap<String, i32>>,
+    pub broken: Option<HashMap<String, i64>>,
 
     /// Items picked up by the player
     #[serde(rename = "minecraft:picked_up")]
-    pub picked_up: Option<HashMap<String, i32>>,
+    pub picked_up: Option<HashMap<String, i64>>,
 
     /// Items dropped by the player
     #[serde(rename = "minecraft:dropped")]
-    pub dropped: Option<HashMap<String, i32>>,
+    pub dropped: Option<HashMap<String, i64>>,
 
     /// Mobs/players killed by the player
     #[serde(rename = "minecraft:killed")]
-    pub killed: Option<HashMap<String, i32>>,
+    pub killed: Option<HashMap<String, i64>>,
 
     /// Times the player was killed by mobs/players
     #[serde(rename = "minecraft:killed_by")]
-    pub killed_by: Option<HashMap<String, i32>>,
+    pub killed_by: Option<HashMap<String, i64>>,
 
     /// Custom entity interactions (like trading with villagers)
     #[serde(rename = "minecraft:custom_entity")]
-    pub custom_entity: Option<HashMap<String, i32>>,
+    pub custom_entity: Option<HashMap<String, i64>>,
+
+    /// Categories added by mods and datapacks, e.g. `create:goggles`, by key
+    #[serde(flatten)]
+    pub modded: BTreeMap<String, HashMap<String, i64>>,
+}
+
+/// Keys of every category in [`StatCategories`], in display order
+pub const STAT_CATEGORIES: [&str; 10] = [
+    "minecraft:custom",
+    "minecraft:mined",
+    "minecraft:crafted",
+    "minecraft:used",
+    "minecraft:broken",
+    "minecraft:picked_up",
+    "minecraft:dropped",
+    "minecraft:killed",
+    "minecraft:killed_by",
+    "minecraft:custom_entity",
+];
+
+impl StatCategories {
+    /// Gets a category by its key, e.g. `minecraft:mined` or a modded `create:goggles`
+    pub fn category(&self, key: &str) -> Option<&HashMap<String, i64>> {
+        match key {
+            "minecraft:custom" => Some(&self.custom),
+            "minecraft:mined" => self.mined.as_ref(),
+            "minecraft:crafted" => self.crafted.as_ref(),
+            "minecraft:used" => self.used.as_ref(),
+            "minecraft:broken" => self.broken.as_ref(),
+            "minecraft:picked_up" => self.picked_up.as_ref(),
+            "minecraft:dropped" => self.dropped.as_ref(),
+            "minecraft:killed" => self.killed.as_ref(),
+            "minecraft:killed_by" => self.killed_by.as_ref(),
+            "minecraft:custom_entity" => self.custom_entity.as_ref(),
+            _ => self.modded.get(key),
+        }
+    }
+
+    /// Keys of every category, the vanilla ones first and then the modded ones by name
+    pub fn category_keys(&self) -> impl Iterator<Item = &str> {
+        STAT_CATEGORIES
+            .iter()
+            .copied()
+            .chain(self.modded.keys().map(String::as_str))
+    }
+
+    /// Gets a single stat, e.g. `get("minecraft:mined", "minecraft:stone")`.
+    /// Missing stats count as 0.
+    pub fn get(&self, category: &str, stat: &str) -> i64 {
+        self.category(category)
+            .and_then(|stats| stats.get(stat))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Gets a single stat by its typed key. Missing stats count as 0.
+    pub fn value(&self, key: &StatKey) -> i64 {
+        self.get(&key.category, &key.id())
+    }
+
+    /// Gets a stat of the `minecraft:custom` category, e.g. [`CustomStat::PlayTime`]
+    pub fn custom_stat(&self, stat: CustomStat) -> i64 {
+        self.custom.get(stat.id()).copied().unwrap_or(0)
+    }
+
+    /// Iterates over every stat of every category, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (StatKey, i64)> + '_ {
+        self.category_keys().flat_map(move |category| {
+            self.category(category)
+                .into_iter()
+                .flatten()
+                .map(move |(id, value)| (StatKey::from_ids(category, id), *value))
+        })
+    }
+
+    /// Sums every stat in a category. Negative values, which only corrupted
+    /// or edited files have, count as zero.
+    pub fn category_total(&self, category: &str) -> u64 {
+        self.category(category)
+            .map(|stats| {
+                stats
+                    .values()
+                    .map(|value| u64::try_from(*value).unwrap_or(0))
+                    .sum()
+            })
+            .unwrap_or(0)
+    }
 }
 
 /// Options for the bot command
-#[derive(poise::ChoiceParameter)]
+#[derive(poise::ChoiceParameter, Debug, Clone, Copy)]
 pub enum GetStatsOption {
     #[name = "Custom Statistics"]
     #[name = "custom"]
@@ -101,6 +213,22 @@ pub enum GetStatsOption {
 }
 
 impl GetStatsOption {
+    /// Gets the key of the category in the stats file, e.g. `minecraft:mined`
+    pub fn key(&self) -> &'static str {
+        match self {
+            GetStatsOption::Custom => "minecraft:custom",
+            GetStatsOption::Mined => "minecraft:mined",
+            GetStatsOption::Crafted => "minecraft:crafted",
+            GetStatsOption::Used => "minecraft:used",
+            GetStatsOption::Broken => "minecraft:broken",
+            GetStatsOption::PickedUp => "minecraft:picked_up",
+            GetStatsOption::Dropped => "minecraft:dropped",
+            GetStatsOption::Killed => "minecraft:killed",
+            GetStatsOption::KilledBy => "minecraft:killed_by",
+            GetStatsOption::CustomEntity => "minecraft:custom_entity",
+        }
+    }
+
     pub fn get_name(&self) -> String {
         match self {
             GetStatsOption::Custom => "Custom Statistic".to_string(),
@@ -117,379 +245,227 @@ impl GetStatsOption {
     }
 }
 
-/// Common custom statistics as defined by the Minecraft Wiki
-/// These are commonly used statistics that are part of the "minecraft:custom" category
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CommonCustomStats {
-    // Movement statistics
-    #[serde(rename = "minecraft:aviate_one_cm")]
-    pub aviate_one_cm: Option<i32>,
-    #[serde(rename = "minecraft:boat_one_cm")]
-    pub boat_one_cm: Option<i32>,
-    #[serde(rename = "minecraft:climb_one_cm")]
-    pub climb_one_cm: Option<i32>,
-    #[serde(rename = "minecraft:crouch_one_cm")]
-    pub crouch_one_cm: Option<i32>,
-    #[serde(rename = "minecraft:fall_one_cm")]
-    pub fall_one_cm: Option<i32>,
-    #[serde(rename = "minecraft:fly_one_cm")]
-    pub fly_one_cm: Option<i32>,
-    #[serde(rename = "minecraft:horse_one_cm")]
-    pub horse_one_cm: Option<i32>,
-    #[serde(rename = "minecraft:minecart_one_cm")]
-    pub minecart_one_cm: Option<i32>,
-    #[serde(rename = "minecraft:pig_one_cm")]
-    pub pig_one_cm: Option<i32>,
-    #[serde(rename = "minecraft:sprint_one_cm")]
-    pub sprint_one_cm: Option<i32>,
-    #[serde(rename = "minecraft:strider_one_cm")]
-    pub strider_one_cm: Option<i32>,
-    #[serde(rename = "minecraft:swim_one_cm")]
-    pub swim_one_cm: Option<i32>,
-    #[serde(rename = "minecraft:walk_on_water_one_cm")]
-    pub walk_on_water_one_cm: Option<i32>,
-    #[serde(rename = "minecraft:walk_under_water_one_cm")]
-    pub walk_under_water_one_cm: Option<i32>,
-    #[serde(rename = "minecraft:walk_one_cm")]
-    pub walk_one_cm: Option<i32>,
+impl MinecraftStats {
+    /// Gets the readable name of a category in the current language. Modded
+    /// categories without a name override are named after their key, e.g.
+    /// `Goggles (create)` for `create:goggles`.
+    pub fn get_readable_name(category: &str) -> String {
+        if let Some(name) = lang::name_overrides().and_then(|names| names.category_name(category)) {
+            return name.to_string();
+        }
+        let key = format!("category.{}", category);
+        if Language::English.message(&key).is_some() {
+            i18n::tr(&key)
+        } else if let Some((namespace, _)) = category.split_once(':') {
+            format!("{} ({})", convert_to_readable(category), namespace)
+        } else {
+            i18n::tr("category.unknown")
+        }
+    }
+}
 
-    // Interaction statistics
-    #[serde(rename = "minecraft:animals_bred")]
-    pub animals_bred: Option<i32>,
-    #[serde(rename = "minecraft:bell_ring")]
-    pub bell_ring: Option<i32>,
-    #[serde(rename = "minecraft:eat_cake_slice")]
-    pub eat_cake_slice: Option<i32>,
-    #[serde(rename = "minecraft:cauldron_filled")]
-    pub cauldron_filled: Option<i32>,
-    #[serde(rename = "minecraft:cauldron_used")]
-    pub cauldron_used: Option<i32>,
-    #[serde(rename = "minecraft:chest_opened")]
-    pub chest_opened: Option<i32>,
-    #[serde(rename = "minecraft:clean_armor")]
-    pub clean_armor: Option<i32>,
-    #[serde(rename = "minecraft:clean_banner")]
-    pub clean_banner: Option<i32>,
-    #[serde(rename = "minecraft:clean_shulker_box")]
-    pub clean_shulker_box: Option<i32>,
-    #[serde(rename = "minecraft:enchant_item")]
-    pub enchant_item: Option<i32>,
-    #[serde(rename = "minecraft:fish_caught")]
-    pub fish_caught: Option<i32>,
-    #[serde(rename = "minecraft:inspect_dispenser")]
-    pub inspect_dispenser: Option<i32>,
-    #[serde(rename = "minecraft:inspect_dropper")]
-    pub inspect_dropper: Option<i32>,
-    #[serde(rename = "minecraft:inspect_hopper")]
-    pub inspect_hopper: Option<i32>,
-    #[serde(rename = "minecraft:interact_with_anvil")]
-    pub interact_with_anvil: Option<i32>,
-    #[serde(rename = "minecraft:interact_with_beacon")]
-    pub interact_with_beacon: Option<i32>,
-    #[serde(rename = "minecraft:interact_with_blast_furnace")]
-    pub interact_with_blast_furnace: Option<i32>,
-    #[serde(rename = "minecraft:interact_with_brewingstand")]
-    pub interact_with_brewingstand: Option<i32>,
-    #[serde(rename = "minecraft:interact_with_campfire")]
-    pub interact_with_campfire: Option<i32>,
-    #[serde(rename = "minecraft:interact_with_cartography_table")]
-    pub interact_with_cartography_table: Option<i32>,
-    #[serde(rename = "minecraft:interact_with_crafting_table")]
-    pub interact_with_crafting_table: Option<i32>,
-    #[serde(rename = "minecraft:interact_with_furnace")]
-    pub interact_with_furnace: Option<i32>,
-    #[serde(rename = "minecraft:interact_with_grindstone")]
-    pub interact_with_grindstone: Option<i32>,
-    #[serde(rename = "minecraft:interact_with_lectern")]
-    pub interact_with_lectern: Option<i32>,
-    #[serde(rename = "minecraft:interact_with_loom")]
-    pub interact_with_loom: Option<i32>,
-    #[serde(rename = "minecraft:interact_with_smithing_table")]
-    pub interact_with_smithing_table: Option<i32>,
-    #[serde(rename = "minecraft:interact_with_smoker")]
-    pub interact_with_smoker: Option<i32>,
-    #[serde(rename = "minecraft:interact_with_stonecutter")]
-    pub interact_with_stonecutter: Option<i32>,
-    #[serde(rename = "minecraft:open_barrel")]
-    pub open_barrel: Option<i32>,
-    #[serde(rename = "minecraft:open_enderchest")]
-    pub open_enderchest: Option<i32>,
-    #[serde(rename = "minecraft:play_noteblock")]
-    pub play_noteblock: Option<i32>,
-    #[serde(rename = "minecraft:play_record")]
-    pub play_record: Option<i32>,
-    #[serde(rename = "minecraft:pot_flower")]
-    pub pot_flower: Option<i32>,
-    #[serde(rename = "minecraft:shulker_box_opened")]
-    pub shulker_box_opened: Option<i32>,
-    #[serde(rename = "minecraft:sleep_in_bed")]
-    pub sleep_in_bed: Option<i32>,
-    #[serde(rename = "minecraft:talked_to_villager")]
-    pub talked_to_villager: Option<i32>,
-    #[serde(rename = "minecraft:target_hit")]
-    pub target_hit: Option<i32>,
-    #[serde(rename = "minecraft:traded_with_villager")]
-    pub traded_with_villager: Option<i32>,
-    #[serde(rename = "minecraft:trigger_trapped_chest")]
-    pub trigger_trapped_chest: Option<i32>,
-    #[serde(rename = "minecraft:tune_noteblock")]
-    pub tune_noteblock: Option<i32>,
-    #[serde(rename = "minecraft:use_cauldron")]
-    pub use_cauldron: Option<i32>,
+/// Turns a key into a title, e.g. `minecraft:diamond_ore` into `Diamond Ore`.
+/// Mods and datapacks also use `/` and `.` in their keys, e.g. `mymod:tools/drill`.
+pub fn convert_to_readable(key: &str) -> String {
+    let path = key.split_once(':').map_or(key, |(_, path)| path);
+    path.split(['_', '/', '.'])
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let chars = word.chars().collect::<Vec<char>>();
+            let mut out = String::new();
+            out.push_str(&chars[0].to_uppercase().to_string());
+            for char in &chars[1..] {
+                out.push_str(&char.to_lowercase().to_string());
+            }
+            out
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
 
-    // Combat statistics
-    #[serde(rename = "minecraft:damage_absorbed")]
-    pub damage_absorbed: Option<i32>,
-    #[serde(rename = "minecraft:damage_blocked_by_shield")]
-    pub damage_blocked_by_shield: Option<i32>,
-    #[serde(rename = "minecraft:damage_dealt")]
-    pub damage_dealt: Option<i32>,
-    #[serde(rename = "minecraft:damage_dealt_absorbed")]
-    pub damage_dealt_absorbed: Option<i32>,
-    #[serde(rename = "minecraft:damage_dealt_resisted")]
-    pub damage_dealt_resisted: Option<i32>,
-    #[serde(rename = "minecraft:damage_resisted")]
-    pub damage_resisted: Option<i32>,
-    #[serde(rename = "minecraft:damage_taken")]
-    pub damage_taken: Option<i32>,
-    #[serde(rename = "minecraft:deaths")]
-    pub deaths: Option<i32>,
-    #[serde(rename = "minecraft:mob_kills")]
-    pub mob_kills: Option<i32>,
-    #[serde(rename = "minecraft:player_kills")]
-    pub player_kills: Option<i32>,
+/// Gets the readable name of a stat in the current language (see [`i18n`]).
+/// Name overrides for mods come first (see [`lang::install_overrides`]), then
+/// the built-in name tables for English, then the installed language files
+/// (see [`lang::install`]), and finally [`convert_to_readable`].
+pub fn readable_stat_name(category: &str, key: &str) -> String {
+    let from_file = |language| lang::stat_names(language)?.stat_name(category, key);
+    let language = i18n::current();
+    lang::name_overrides()
+        .and_then(|names| names.stat_name(category, key))
+        .map(str::to_string)
+        .or_else(|| {
+            (language != Language::English)
+                .then(|| from_file(language))
+                .flatten()
+        })
+        .or_else(|| built_in_stat_name(category, key).map(str::to_string))
+        .or_else(|| from_file(Language::English))
+        .unwrap_or_else(|| convert_to_readable(key))
+}
 
-    // Miscellaneous statistics
-    #[serde(rename = "minecraft:crouch_time")]
-    pub crouch_time: Option<i32>,
-    #[serde(rename = "minecraft:jump")]
-    pub jump: Option<i32>,
-    #[serde(rename = "minecraft:leave_game")]
-    pub leave_game: Option<i32>,
-    #[serde(rename = "minecraft:play_time")]
-    pub play_time: Option<i32>,
-    #[serde(rename = "minecraft:sneak_time")]
-    pub sneak_time: Option<i32>,
-    #[serde(rename = "minecraft:time_since_death")]
-    pub time_since_death: Option<i32>,
-    #[serde(rename = "minecraft:time_since_rest")]
-    pub time_since_rest: Option<i32>,
-    #[serde(rename = "minecraft:total_world_time")]
-    pub total_world_time: Option<i32>,
+/// Formats a stat value in its unit: distances in km and times as `HH:MM:SS`
+pub fn fmt_stat_value(category: &str, key: &str, value: i64) -> String {
+    report::Unit::of_stat(category, key).format(value as f64)
+}
 
-    // Raid statistics
-    #[serde(rename = "minecraft:raid_trigger")]
-    pub raid_trigger: Option<i32>,
-    #[serde(rename = "minecraft:raid_win")]
-    pub raid_win: Option<i32>,
+/// Looks up a name in the built-in tables, which are only created once
+fn built_in_stat_name(category: &str, key: &str) -> Option<&'static str> {
+    static NAMES: LazyLock<HashMap<&str, HashMap<String, String>>> = LazyLock::new(|| {
+        STAT_CATEGORIES
+            .iter()
+            .map(|category| (*category, create_stat_names(category)))
+            .collect()
+    });
+    NAMES.get(category)?.get(key).map(String::as_str)
 }
 
-// Helper function to extract common custom stats
-impl MinecraftStats {
-    pub fn get_common_custom_stats(&self) -> CommonCustomStats {
-        CommonCustomStats {
-            // Movement statistics
-            aviate_one_cm: self.stats.custom.get("minecraft:aviate_one_cm").cloned(),
-            boat_one_cm: self.stats.custom.get("minecraft:boat_one_cm").cloned(),
-            climb_one_cm: self.stats.custom.get("minecraft:climb_one_cm").cloned(),
-            crouch_one_cm: self.stats.custom.get("minecraft:crouch_one_cm").cloned(),
-            fall_one_cm: self.stats.custom.get("minecraft:fall_one_cm").cloned(),
-            fly_one_cm: self.stats.custom.get("minecraft:fly_one_cm").cloned(),
-            horse_one_cm: self.stats.custom.get("minecraft:horse_one_cm").cloned(),
-            minecart_one_cm: self.stats.custom.get("minecraft:minecart_one_cm").cloned(),
-            pig_one_cm: self.stats.custom.get("minecraft:pig_one_cm").cloned(),
-            sprint_one_cm: self.stats.custom.get("minecraft:sprint_one_cm").cloned(),
-            strider_one_cm: self.stats.custom.get("minecraft:strider_one_cm").cloned(),
-            swim_one_cm: self.stats.custom.get("minecraft:swim_one_cm").cloned(),
-            walk_on_water_one_cm: self
-                .stats
-                .custom
-                .get("minecraft:walk_on_water_one_cm")
-                .cloned(),
-            walk_under_water_one_cm: self
-                .stats
-                .custom
-                .get("minecraft:walk_under_water_one_cm")
-                .cloned(),
-            walk_one_cm: self.stats.custom.get("minecraft:walk_one_cm").cloned(),
-
-            // Interaction statistics
-            animals_bred: self.stats.custom.get("minecraft:animals_bred").cloned(),
-            bell_ring: self.stats.custom.get("minecraft:bell_ring").cloned(),
-            eat_cake_slice: self.stats.custom.get("minecraft:eat_cake_slice").cloned(),
-            cauldron_filled: self.stats.custom.get("minecraft:cauldron_filled").cloned(),
-            cauldron_used: self.stats.custom.get("minecraft:cauldron_used").cloned(),
-            chest_opened: self.stats.custom.get("minecraft:chest_opened").cloned(),
-            clean_armor: self.stats.custom.get("minecraft:clean_armor").cloned(),
-            clean_banner: self.stats.custom.get("minecraft:clean_banner").cloned(),
-            clean_shulker_box: self
-                .stats
-                .custom
-                .get("minecraft:clean_shulker_box")
-                .cloned(),
-            enchant_item: self.stats.custom.get("minecraft:enchant_item").cloned(),
-            fish_caught: self.stats.custom.get("minecraft:fish_caught").cloned(),
-            inspect_dispenser: self
-                .stats
-                .custom
-                .get("minecraft:inspect_dispenser")
-                .cloned(),
-            inspect_dropper: self.stats.custom.get("minecraft:inspect_dropper").cloned(),
-            inspect_hopper: self.stats.custom.get("minecraft:inspect_hopper").cloned(),
-            interact_with_anvil: self
-                .stats
-                .custom
-                .get("minecraft:interact_with_anvil")
-                .cloned(),
-            interact_with_beacon: self
-                .stats
-                .custom
-                .get("minecraft:interact_with_beacon")
-                .cloned(),
-            interact_with_blast_furnace: self
-                .stats
-                .custom
-                .get("minecraft:interact_with_blast_furnace")
-                .cloned(),
-            interact_with_brewingstand: self
-                .stats
-                .custom
-                .get("minecraft:interact_with_brewingstand")
-                .cloned(),
-            interact_with_campfire: self
-                .stats
-                .custom
-                .get("minecraft:interact_with_campfire")
-                .cloned(),
-            interact_with_cartography_table: self
-                .stats
-                .custom
-                .get("minecraft:interact_with_cartography_table")
-                .cloned(),
-            interact_with_crafting_table: self
-                .stats
-                .custom
-                .get("minecraft:interact_with_crafting_table")
-                .cloned(),
-            interact_with_furnace: self
-                .stats
-                .custom
-                .get("minecraft:interact_with_furnace")
-                .cloned(),
-            interact_with_grindstone: self
-                .stats
-                .custom
-                .get("minecraft:interact_with_grindstone")
-                .cloned(),
-            interact_with_lectern: self
-                .stats
-                .custom
-                .get("minecraft:interact_with_lectern")
-                .cloned(),
-            interact_with_loom: self
-                .stats
-                .custom
-                .get("minecraft:interact_with_loom")
-                .cloned(),
-            interact_with_smithing_table: self
-                .stats
-                .custom
-                .get("minecraft:interact_with_smithing_table")
-                .cloned(),
-            interact_with_smoker: self
-                .stats
-                .custom
-                .get("minecraft:interact_with_smoker")
-                .cloned(),
-            interact_with_stonecutter: self
-                .stats
-                .custom
-                .get("minecraft:interact_with_stonecutter")
-                .cloned(),
-            open_barrel: self.stats.custom.get("minecraft:open_barrel").cloned(),
-            open_enderchest: self.stats.custom.get("minecraft:open_enderchest").cloned(),
-            play_noteblock: self.stats.custom.get("minecraft:play_noteblock").cloned(),
-            play_record: self.stats.custom.get("minecraft:play_record").cloned(),
-            pot_flower: self.stats.custom.get("minecraft:pot_flower").cloned(),
-            shulker_box_opened: self
-                .stats
-                .custom
-                .get("minecraft:shulker_box_opened")
-                .cloned(),
-            sleep_in_bed: self.stats.custom.get("minecraft:sleep_in_bed").cloned(),
-            talked_to_villager: self
-                .stats
-                .custom
-                .get("minecraft:talked_to_villager")
-                .cloned(),
-            target_hit: self.stats.custom.get("minecraft:target_hit").cloned(),
-            traded_with_villager: self
-                .stats
-                .custom
-                .get("minecraft:traded_with_villager")
-                .cloned(),
-            trigger_trapped_chest: self
-                .stats
-                .custom
-                .get("minecraft:trigger_trapped_chest")
-                .cloned(),
-            tune_noteblock: self.stats.custom.get("minecraft:tune_noteblock").cloned(),
-            use_cauldron: self.stats.custom.get("minecraft:use_cauldron").cloned(),
-
-            // Combat statistics
-            damage_absorbed: self.stats.custom.get("minecraft:damage_absorbed").cloned(),
-            damage_blocked_by_shield: self
-                .stats
-                .custom
-                .get("minecraft:damage_blocked_by_shield")
-                .cloned(),
-            damage_dealt: self.stats.custom.get("minecraft:damage_dealt").cloned(),
-            damage_dealt_absorbed: self
-                .stats
-                .custom
-                .get("minecraft:damage_dealt_absorbed")
-                .cloned(),
-            damage_dealt_resisted: self
-                .stats
-                .custom
-                .get("minecraft:damage_dealt_resisted")
-                .cloned(),
-            damage_resisted: self.stats.custom.get("minecraft:damage_resisted").cloned(),
-            damage_taken: self.stats.custom.get("minecraft:damage_taken").cloned(),
-            deaths: self.stats.custom.get("minecraft:deaths").cloned(),
-            mob_kills: self.stats.custom.get("minecraft:mob_kills").cloned(),
-            player_kills: self.stats.custom.get("minecraft:player_kills").cloned(),
-
-            // Miscellaneous statistics
-            crouch_time: self.stats.custom.get("minecraft:crouch_time").cloned(),
-            jump: self.stats.custom.get("minecraft:jump").cloned(),
-            leave_game: self.stats.custom.get("minecraft:leave_game").cloned(),
-            play_time: self.stats.custom.get("minecraft:play_time").cloned(),
-            sneak_time: self.stats.custom.get("minecraft:sneak_time").cloned(),
-            time_since_death: self.stats.custom.get("minecraft:time_since_death").cloned(),
-            time_since_rest: self.stats.custom.get("minecraft:time_since_rest").cloned(),
-            total_world_time: self.stats.custom.get("minecraft:total_world_time").cloned(),
-
-            // Raid statistics
-            raid_trigger: self.stats.custom.get("minecraft:raid_trigger").cloned(),
-            raid_win: self.stats.custom.get("minecraft:raid_win").cloned(),
-        }
+/// Gets the readable name table for a category key, e.g. `minecraft:mined`
+pub fn create_stat_names(category: &str) -> HashMap<String, String> {
+    match category {
+        "minecraft:custom" => create_custom_stat_names(),
+        "minecraft:mined" => create_mined_stat_names(),
+        "minecraft:crafted" => create_crafted_stat_names(),
+        "minecraft:used" => create_used_stat_names(),
+        "minecraft:broken" => create_broken_stat_names(),
+        "minecraft:picked_up" => create_picked_up_stat_names(),
+        "minecraft:dropped" => create_dropped_stat_names(),
+        "minecraft:killed" => create_killed_stat_names(),
+        "minecraft:killed_by" => create_killed_by_stat_names(),
+        _ => HashMap::new(),
     }
-    pub fn get_readable_name(category: &str) -> String {
-        match category {
-            "minecraft:custom" => "Custom Statistic".to_string(),
-            "minecraft:mined" => "Mined Statistic".to_string(),
-            "minecraft:crafted" => "Crafted Statistic".to_string(),
-            "minecraft:used" => "Used Statistic".to_string(),
-            "minecraft:broken" => "Broken Statistic".to_string(),
-            "minecraft:picked_up" => "Picked Up Statistic".to_string(),
-            "minecraft:dropped" => "Dropped Statistic".to_string(),
-            "minecraft:killed" => "Killed Statistic".to_string(),
-            "minecraft:killed_by" => "Killed By Statistic".to_string(),
-            "minecraft:custom_entity" => "Custom Entity Statistic".to_string(),
-            _ => "Unknown Statistic".to_string(),
+}
+
+/// Predefined groups of stats that listings can be narrowed to, e.g. only ores in Blocks Mined
+#[derive(poise::ChoiceParameter, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatGroup {
+    #[name = "Ores"]
+    #[name = "ores"]
+    Ores,
+
+    #[name = "Logs"]
+    #[name = "logs"]
+    Logs,
+
+    #[name = "Hostile Mobs"]
+    #[name = "hostile_mobs"]
+    HostileMobs,
+}
+
+impl StatGroup {
+    /// Gets the stat keys in the group, e.g. `minecraft:iron_ore`
+    pub fn keys(&self) -> Vec<String> {
+        match self {
+            StatGroup::Ores => create_ore_stat_keys(),
+            StatGroup::Logs => create_log_stat_keys(),
+            StatGroup::HostileMobs => create_hostile_mob_stat_keys(),
         }
     }
+
+    pub fn contains(&self, key: &str) -> bool {
+        static KEYS: LazyLock<[HashSet<String>; 3]> = LazyLock::new(|| {
+            [StatGroup::Ores, StatGroup::Logs, StatGroup::HostileMobs]
+                .map(|group| group.keys().into_iter().collect())
+        });
+        KEYS[*self as usize].contains(key)
+    }
+}
+
+/// Creates the keys of ore blocks, as mined or picked up
+pub fn create_ore_stat_keys() -> Vec<String> {
+    [
+        // Overworld
+        "coal_ore",
+        "deepslate_coal_ore",
+        "copper_ore",
+        "deepslate_copper_ore",
+        "iron_ore",
+        "deepslate_iron_ore",
+        "gold_ore",
+        "deepslate_gold_ore",
+        "redstone_ore",
+        "deepslate_redstone_ore",
+        "lapis_ore",
+        "deepslate_lapis_ore",
+        "diamond_ore",
+        "deepslate_diamond_ore",
+        "emerald_ore",
+        "deepslate_emerald_ore",
+        // Nether
+        "nether_gold_ore",
+        "nether_quartz_ore",
+        "ancient_debris",
+    ]
+    .iter()
+    .map(|key| format!("minecraft:{}", key))
+    .collect()
+}
+
+/// Creates the keys of logs and stems, including stripped ones
+pub fn create_log_stat_keys() -> Vec<String> {
+    let woods = [
+        "oak", "spruce", "birch", "jungle", "acacia", "dark_oak", "mangrove", "cherry", "pale_oak",
+    ];
+    let stems = ["crimson", "warped"];
+    woods
+        .iter()
+        .map(|wood| format!("{}_log", wood))
+        .chain(stems.iter().map(|stem| format!("{}_stem", stem)))
+        .flat_map(|log| {
+            [
+                format!("minecraft:{}", log),
+                format!("minecraft:stripped_{}", log),
+            ]
+        })
+        .collect()
+}
+
+/// Creates the keys of hostile mobs, as killed or killed by
+pub fn create_hostile_mob_stat_keys() -> Vec<String> {
+    [
+        // Overworld
+        "zombie",
+        "zombie_villager",
+        "husk",
+        "drowned",
+        "skeleton",
+        "stray",
+        "bogged",
+        "creeper",
+        "spider",
+        "cave_spider",
+        "witch",
+        "slime",
+        "phantom",
+        "silverfish",
+        "guardian",
+        "elder_guardian",
+        "breeze",
+        "creaking",
+        "warden",
+        // Illagers
+        "pillager",
+        "vindicator",
+        "evoker",
+        "vex",
+        "ravager",
+        // Nether
+        "blaze",
+        "ghast",
+        "magma_cube",
+        "wither_skeleton",
+        "piglin_brute",
+        "hoglin",
+        "zoglin",
+        // End
+        "endermite",
+        "shulker",
+        // Bosses
+        "wither",
+        "ender_dragon",
+    ]
+    .iter()
+    .map(|key| format!("minecraft:{}", key))
+    .collect()
 }
 
 pub fn create_killed_by_stat_names() -> HashMap<String, String> {
@@ -683,18 +659,6 @@ pub fn create_custom_stat_names() -> HashMap<String, String> {
         "minecraft:eat_cake_slice".to_string(),
         "Cake Slices Eaten".to_string(),
     );
-    map.insert(
-        "minecraft:cauldron_filled".to_string(),
-        "Cauldrons Filled".to_string(),
-    );
-    map.insert(
-        "minecraft:cauldron_used".to_string(),
-        "Water Taken from Cauldron".to_string(),
-    );
-    map.insert(
-        "minecraft:chest_opened".to_string(),
-        "Chests Opened".to_string(),
-    );
     map.insert(
         "minecraft:clean_armor".to_string(),
         "Armor Pieces Cleaned".to_string(),
@@ -711,6 +675,10 @@ pub fn create_custom_stat_names() -> HashMap<String, String> {
         "minecraft:enchant_item".to_string(),
         "Items Enchanted".to_string(),
     );
+    map.insert(
+        "minecraft:fill_cauldron".to_string(),
+        "Cauldrons Filled".to_string(),
+    );
     map.insert(
         "minecraft:fish_caught".to_string(),
         "Fish Caught".to_string(),
@@ -787,10 +755,18 @@ pub fn create_custom_stat_names() -> HashMap<String, String> {
         "minecraft:open_barrel".to_string(),
         "Barrels Opened".to_string(),
     );
+    map.insert(
+        "minecraft:open_chest".to_string(),
+        "Chests Opened".to_string(),
+    );
     map.insert(
         "minecraft:open_enderchest".to_string(),
         "Ender Chests Opened".to_string(),
     );
+    map.insert(
+        "minecraft:open_shulker_box".to_string(),
+        "Shulker Boxes Opened".to_string(),
+    );
     map.insert(
         "minecraft:play_noteblock".to_string(),
         "Note Blocks Played".to_string(),
@@ -803,10 +779,6 @@ pub fn create_custom_stat_names() -> HashMap<String, String> {
         "minecraft:pot_flower".to_string(),
         "Flowers Potted".to_string(),
     );
-    map.insert(
-        "minecraft:shulker_box_opened".to_string(),
-        "Shulker Boxes Opened".to_string(),
-    );
     map.insert(
         "minecraft:sleep_in_bed".to_string(),
         "Times Slept in a Bed".to_string(),
@@ -833,7 +805,7 @@ pub fn create_custom_stat_names() -> HashMap<String, String> {
     );
     map.insert(
         "minecraft:use_cauldron".to_string(),
-        "Cauldrons Used".to_string(),
+        "Water Taken from Cauldron".to_string(),
     );
 
     // Combat statistics
@@ -1494,8 +1466,23 @@ pub fn create_killed_stat_names() -> HashMap<String, String> {
 pub struct UserCache {
     pub name: String,
     pub uuid: String,
+    /// When the server looks the name up again, e.g. `2025-04-22 06:01:59 +0000`.
+    /// Entries are renewed whenever the player joins.
     #[serde(rename = "expiresOn")]
-    expires_on: String,
+    pub expires_on: String,
+}
+
+impl UserCache {
+    /// Unix timestamp in seconds of `expires_on`
+    pub fn expires_at(&self) -> Option<u64> {
+        users::parse_timestamp(&self.expires_on)
+    }
+
+    /// Whether the entry expired before `now`, a Unix timestamp in seconds.
+    /// Expired entries are still the last name the server knew of.
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at().is_some_and(|expires_at| expires_at < now)
+    }
 }
 
 pub trait ToJson<T> {
@@ -1520,22 +1507,12 @@ pub fn fmt_time<T: Into<u64>>(time: T) -> String {
     format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
 }
 
-pub trait Findable {
-    fn find_player(&self, stats: Vec<UserCache>) -> String;
+/// Formats a distance in centimetres as kilometres
+pub fn fmt_distance(cm: u64) -> String {
+    format!("{:.2} km", cm as f64 / 100_000.0)
 }
 
-impl Findable for String {
-    fn find_player(&self, stats: Vec<UserCache>) -> String {
-        for player in &stats {
-            if player.uuid == *self {
-                return player.name.clone();
-            }
-        }
-        "Unknown".to_string()
-    }
-}
-
-#[derive(poise::ChoiceParameter)]
+#[derive(poise::ChoiceParameter, Debug, Clone, Copy)]
 pub enum Day {
     Monday,
     Tuesday,
@@ -1544,7 +1521,7 @@ pub enum Day {
     Friday,
 }
 
-#[derive(poise::ChoiceParameter, Debug)]
+#[derive(poise::ChoiceParameter, Debug, Clone, Copy)]
 pub enum Section {
     Everest,
     Himalayas,
@@ -1600,6 +1577,12 @@ impl From<&str> for Section {
     }
 }
 
+/// Reads `timetables/<section>.toml`
+pub fn read_timetable(section: &str) -> anyhow::Result<Timetable> {
+    let timetable_raw = std::fs::read_to_string(format!("timetables/{}.toml", section))?;
+    Ok(toml::from_str(&timetable_raw)?)
+}
+
 impl Timetable {
     pub fn get_day(&self, day: Day) -> Vec<String> {
         match day {
@@ -1612,3 +1595,43 @@ impl Timetable {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_stats() {
+        assert_eq!(
+            readable_stat_name("minecraft:custom", "minecraft:open_chest"),
+            "Chests Opened"
+        );
+        assert_eq!(
+            readable_stat_name("minecraft:mined", "minecraft:nope_ore"),
+            "Nope Ore"
+        );
+        assert_eq!(
+            readable_stat_name("create:custom", "create:open_chest"),
+            "Open Chest"
+        );
+    }
+
+    #[test]
+    fn groups_contain_their_keys() {
+        assert!(StatGroup::Ores.contains("minecraft:deepslate_iron_ore"));
+        assert!(StatGroup::Logs.contains("minecraft:oak_log"));
+        assert!(StatGroup::HostileMobs.contains("minecraft:zombie"));
+        assert!(!StatGroup::Ores.contains("minecraft:oak_log"));
+    }
+
+    #[test]
+    fn totals_ignore_negative_values() {
+        let stats = serde_json::from_value::<StatCategories>(serde_json::json!({
+            "minecraft:custom": { "minecraft:deaths": -5 },
+            "minecraft:mined": { "minecraft:stone": 10, "minecraft:dirt": -3 }
+        }))
+        .unwrap();
+        assert_eq!(stats.category_total("minecraft:mined"), 10);
+        assert_eq!(stats.category_total("minecraft:custom"), 0);
+        assert_eq!(totals::TotalStat::Deaths.value(&stats), 0);
+    }
+}
@@ -1,336 +1,946 @@
+#[cfg(feature = "api")]
+mod api;
+mod cli;
+mod death_feed;
+mod embeds;
 mod pagination;
+mod properties_watch;
 mod query;
+mod rcon;
+#[cfg(feature = "prometheus")]
+mod telemetry;
 use anyhow::Result;
-use pagination::paginate;
-use poise::serenity_prelude::{Colour, CreateEmbed, CreateEmbedFooter, Guild};
-use poise::serenity_prelude::{Role, RoleId};
-use poise::{CreateReply, serenity_prelude as serenity};
-use query::stat_full;
-use serde_json::Value;
+use clap::Parser;
+use cli::Cli;
+use death_feed::run_death_feed;
+use embeds::report_pages;
+use notify::RecommendedWatcher;
+use pagination::{PageSet, Paginator};
+use poise::serenity_prelude::Role;
+use poise::serenity_prelude::{Colour, CreateAttachment};
+use poise::{ChoiceParameter, CreateReply, serenity_prelude as serenity};
+use properties_watch::run_properties_watch;
+use query::{server_report, stat_full};
+use rand::seq::SliceRandom;
+use rcon::RconConfig;
 use stats_parser::MinecraftStats;
+use stats_parser::access_lists::{AccessLists, is_valid_username};
+use stats_parser::charts::{bar_chart, line_chart, pie_chart, radar_chart};
+use stats_parser::deaths::{death_breakdown, read_deaths_from_logs};
+use stats_parser::export::{ExportFormat, StatsMatrix};
+use stats_parser::i18n::{self, Language, LanguagePreferences, tr, tr_with};
+use stats_parser::lang::{self, NameOverrides, StatNames};
+use stats_parser::leaderboard::{LeaderboardTarget, leaderboard as rank_players};
+use stats_parser::metrics::{DEFAULT_MIN_PLAYTIME_HOURS, DerivedMetric, playtime_hours};
+use stats_parser::profile_card::ProfileCard;
+use stats_parser::report::{
+    PROFILE_HIGHLIGHTS_SECTION, ReportValue, RowOrder, StatFilter, StatsReport, bans_report,
+    compare_report, deaths_report, leaderboard_report, objective_report, ops_report,
+    player_stats_report, playtime_report, profile_report, scoreboard_report,
+    server_properties_report, superlatives_report, team_report, timetable_report, totals_report,
+    whitelist_report,
+};
+use stats_parser::scoreboard::Scoreboard;
+use stats_parser::server_properties::ServerProperties;
+use stats_parser::snapshots::{PlaytimeSnapshot, append_snapshot, load_snapshots};
+use stats_parser::stat_key::CustomStat;
+use stats_parser::store::StatsStore;
 use stats_parser::*;
-use std::collections::HashMap;
-use std::fs;
-use walkdir::WalkDir;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing_subscriber::EnvFilter;
 
+/// The server's directory, with `server.properties`, `whitelist.json`, `ops.json` and ban lists
+const SERVER_DIR: &str = "../../../school_smp";
+const LOGS_DIR: &str = "../../../school_smp/logs";
+const STATS_DIR: &str = "../../../school_smp/world/stats";
+const USERCACHE_PATH: &str = "../../../school_smp/usercache.json";
+/// Every name seen in the usercache, for players who fall out of it
+const NAME_HISTORY_PATH: &str = "name_history.json";
+const SCOREBOARD_PATH: &str = "../../../school_smp/world/data/scoreboard.dat";
+/// Minecraft language files like `en_us.json` from the client jar's `assets/minecraft/lang`, for stat names
+const LANG_DIR: &str = "lang";
+/// `<namespace>.toml` files naming the stats of mods and datapacks
+const NAMES_DIR: &str = "names";
+/// Languages picked with `/language`
+const LANGUAGES_PATH: &str = "languages.json";
+/// Minecraft server, queried on the `query.port` set in its `server.properties`
+const SERVER_HOST: &str = "141.148.218.223";
+/// Last `server.properties` seen, to warn about changes
+const PROPERTIES_SNAPSHOT_PATH: &str = "server_properties.json";
+/// Stats lists are long, so their page controls keep working for longer
+const STATS_PAGES_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+/// Cached skin textures, named `<uuid>.png`
+const SKINS_DIR: &str = "skins";
+const SNAPSHOTS_PATH: &str = "snapshots.jsonl";
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(3600);
+/// How many players leaderboard charts show
+const CHART_TOP_PLAYERS: usize = 10;
 
-struct Data {} // User data, which is stored and accessible in all command invocations
+// User data, which is stored and accessible in all command invocations
+struct Data {
+    stats: Arc<StatsStore>,
+    /// Keeps `stats` up to date for as long as the bot runs
+    _watcher: RecommendedWatcher,
+    #[cfg(feature = "prometheus")]
+    telemetry: Option<Arc<telemetry::Telemetry>>,
+    languages: RwLock<LanguagePreferences>,
+    /// Set when RCON_ADDR and RCON_PASSWORD are, needed to change the whitelist and bans
+    rcon: Option<RconConfig>,
+}
 type Error = Box<dyn std::error::Error + Send + Sync>;
 type Context<'a> = poise::Context<'a, Data, Error>;
 
-fn convert_to_readable(key: &str) -> String {
-    let split = key.split(':').collect::<Vec<&str>>();
-    let value = split[1].to_string().replace('_', " ");
-    value
-        .split(" ")
-        .map(|word| {
-            let chars = word.chars().collect::<Vec<char>>();
-            let mut out = String::new();
-            out.push_str(&chars[0].to_uppercase().to_string());
-            for char in &chars[1..] {
-                out.push_str(&char.to_lowercase().to_string());
-            }
-            out
-        })
-        .collect::<Vec<String>>()
-        .join(" ")
+/// Gets the name of a player, or the UUID if the player's name was never seen
+fn get_username(store: &StatsStore, uuid: &str) -> String {
+    store.username(uuid).unwrap_or_else(|| uuid.to_string())
 }
 
-fn get_username(uuid: &String) -> Result<String> {
-    let json_str = fs::read_to_string("../../../school_smp/usercache.json")?;
-    let usercache: Vec<UserCache> = serde_json::from_str(&json_str)?;
-    let mut username = String::new();
-    for value in usercache {
-        if value.uuid == *uuid {
-            username = value.name;
-        }
-    }
-    Ok(username)
+fn get_uuid(store: &StatsStore, username: &str) -> Result<String> {
+    store
+        .uuid(username)
+        .ok_or_else(|| anyhow::anyhow!(tr_with("error.unknown_player", &[("player", &username)])))
 }
 
-fn get_uuid(username: &String) -> Result<String> {
-    let json_str = fs::read_to_string("../../../school_smp/usercache.json")?;
-    let usercache: Vec<UserCache> = serde_json::from_str(&json_str)?;
-    let mut uuid = String::new();
-    for value in usercache {
-        if value.name == *username {
-            uuid = value.uuid;
-            return Ok(uuid)
-        }
+fn get_player_stats(store: &StatsStore, uuid: &str) -> Result<Arc<MinecraftStats>> {
+    store
+        .get(uuid)
+        .ok_or_else(|| anyhow::anyhow!(tr_with("error.no_stats", &[("player", &uuid)])))
+}
+
+fn server_properties() -> Result<ServerProperties> {
+    ServerProperties::load(&Path::new(SERVER_DIR).join("server.properties"))
+}
+
+/// Port of the server's query protocol, or an error if `enable-query` is off
+fn query_port() -> Result<u16> {
+    query_port_of(Path::new(SERVER_DIR))
+}
+
+/// Like [`query_port`], for the server in `server_dir`
+fn query_port_of(server_dir: &Path) -> Result<u16> {
+    let properties = ServerProperties::load(&server_dir.join("server.properties"))?;
+    if !properties.query_enabled() {
+        anyhow::bail!(tr("server.query_disabled"));
     }
-    Ok(uuid)
+    Ok(properties.query_port())
+}
+
+/// The language to answer in: the one picked by the user or the server, or else their Discord client's
+fn language(ctx: Context<'_>) -> Language {
+    ctx.data().languages.read().unwrap().resolve(
+        ctx.author().id.get(),
+        ctx.guild_id().map(|guild| guild.get()),
+        ctx.locale(),
+    )
 }
 
 /// Gets the stats of a uuid (player). To find your uuid, login to the server and run `/list uuids`.
 #[poise::command(slash_command, prefix_command)]
+#[allow(clippy::too_many_arguments)]
 async fn get_stats(
     ctx: Context<'_>,
     #[description = "UUID"] uuid: String,
     #[description = "What stats category to display"] stats: Option<GetStatsOption>,
+    #[description = "Order of the stats (default highest first)"] sort: Option<RowOrder>,
+    #[description = "Text in the stat name, or a /regex/"] filter: Option<String>,
+    #[description = "Only stats in a group, e.g. ores"] group: Option<StatGroup>,
+    #[description = "Only stats with at least this value"] min: Option<i64>,
+    #[description = "Only the first this many stats of each category"] top: Option<u32>,
 ) -> Result<(), Error> {
-    let username = get_username(&uuid)?;
-    ctx.say(format!("Username: {}", username)).await?;
-    let response = test_main(uuid, stats)?;
-    paginate(ctx, response).await?;
+    let store = &ctx.data().stats;
+    let username = get_username(store, &uuid);
+    let language = language(ctx);
+    ctx.say(language.scope(|| tr_with("stats.username", &[("player", &username)])))
+        .await?;
+    let (filter, report) = language.scope(|| -> Result<_> {
+        let player_stats = get_player_stats(store, &uuid)?;
+        let filter = stat_filter(sort, filter, group, min, top)?;
+        let report = player_stats_report(&username, &player_stats, stats.map(|s| s.key()), &filter);
+        Ok((filter, report))
+    })?;
+    send_stats(ctx, language, &filter, report).await?;
 
     Ok(())
 }
 
 /// Gets the stats of a username (player). To find your uuid, login to the server and run `/list uuids`.
 #[poise::command(slash_command, prefix_command)]
+#[allow(clippy::too_many_arguments)]
 async fn get_stats_username(
     ctx: Context<'_>,
     #[description = "Username"] username: String,
     #[description = "What stats category to display"] stats: Option<GetStatsOption>,
+    #[description = "Order of the stats (default highest first)"] sort: Option<RowOrder>,
+    #[description = "Text in the stat name, or a /regex/"] filter: Option<String>,
+    #[description = "Only stats in a group, e.g. ores"] group: Option<StatGroup>,
+    #[description = "Only stats with at least this value"] min: Option<i64>,
+    #[description = "Only the first this many stats of each category"] top: Option<u32>,
 ) -> Result<(), Error> {
-    ctx.say(format!("Username: {}", username)).await?;
-    let uuid = get_uuid(&username)?;
-    let response = test_main(uuid, stats)?;
-    paginate(ctx, response).await?;
+    let language = language(ctx);
+    ctx.say(language.scope(|| tr_with("stats.username", &[("player", &username)])))
+        .await?;
+    let store = &ctx.data().stats;
+    let (filter, report) = language.scope(|| -> Result<_> {
+        let uuid = get_uuid(store, &username)?;
+        let player_stats = get_player_stats(store, &uuid)?;
+        let filter = stat_filter(sort, filter, group, min, top)?;
+        let report = player_stats_report(&username, &player_stats, stats.map(|s| s.key()), &filter);
+        Ok((filter, report))
+    })?;
+    send_stats(ctx, language, &filter, report).await?;
 
     Ok(())
 }
 
+fn stat_filter(
+    order: Option<RowOrder>,
+    name: Option<String>,
+    group: Option<StatGroup>,
+    min: Option<i64>,
+    top: Option<u32>,
+) -> Result<StatFilter> {
+    Ok(StatFilter {
+        order: order.unwrap_or_default(),
+        name: name.map(|name| name.parse()).transpose()?,
+        group,
+        min,
+        top: top.map(|top| top as usize),
+    })
+}
+
+/// Sends a player's stats with buttons to switch between the orders,
+/// starting with the one picked
+async fn send_stats(
+    ctx: Context<'_>,
+    language: Language,
+    filter: &StatFilter,
+    report: StatsReport,
+) -> Result<(), Error> {
+    let orders = [
+        RowOrder::Value,
+        RowOrder::ValueAscending,
+        RowOrder::Alphabetical,
+    ];
+    let paginator = language.scope(|| {
+        let first = report_pages(&report, Colour::DARK_GREEN).label(filter.order.label());
+        let mut paginator = Paginator::new(first);
+        for order in orders.into_iter().filter(|order| *order != filter.order) {
+            let mut sorted = report.clone();
+            sorted.sort_rows(order);
+            let pages = report_pages(&sorted, Colour::DARK_GREEN).label(order.label());
+            paginator = paginator.order(pages);
+        }
+        paginator
+    });
+    paginator.timeout(STATS_PAGES_TIMEOUT).run(ctx).await?;
+    Ok(())
+}
+
 /// [USE THIS] Gets the stats of the server.
 #[poise::command(slash_command, prefix_command)]
 async fn server(ctx: Context<'_>) -> Result<(), Error> {
-    let response = stat_full("141.148.218.223", 25566).await?;
-    let mut players = String::new();
-    for player in response.players {
-        players.push_str(&format!("- {}\n", player));
+    let port = language(ctx).scope(query_port)?;
+    let response = stat_full(SERVER_HOST, port).await?;
+    let pages = language(ctx).scope(|| {
+        let report = server_report(&response).footer("Owned by FRXGFA");
+        report_pages(&report, Colour::DARK_GREEN)
+    });
+    Paginator::new(pages).run(ctx).await?;
+
+    Ok(())
+}
+
+/// Shows the server's main settings, like difficulty and view distance.
+#[poise::command(slash_command, prefix_command)]
+async fn serverinfo(ctx: Context<'_>) -> Result<(), Error> {
+    let properties = server_properties()?;
+    let pages = language(ctx)
+        .scope(|| report_pages(&server_properties_report(&properties), Colour::DARK_GREEN));
+    Paginator::new(pages).run(ctx).await?;
+
+    Ok(())
+}
+
+/// Gets the playtime of all the players in the server.
+#[poise::command(slash_command, prefix_command)]
+async fn playtime(ctx: Context<'_>) -> Result<(), Error> {
+    let pages = language(ctx).scope(|| {
+        let report = playtime_report(&ctx.data().stats);
+        report_pages(&report, Colour::TEAL)
+    });
+    Paginator::new(pages).run(ctx).await?;
+
+    Ok(())
+}
+
+/// Gets the profile of an account.
+#[poise::command(slash_command, prefix_command)]
+async fn profile(
+    ctx: Context<'_>,
+    #[description = "Username"] username: String,
+) -> Result<(), Error> {
+    let store = &ctx.data().stats;
+    let (uuid, mut pages) = language(ctx).scope(|| -> Result<_> {
+        let uuid = get_uuid(store, &username)?;
+        let mut report = profile_report(store, &uuid, &username)
+            .ok_or_else(|| anyhow::anyhow!(tr_with("error.no_stats", &[("player", &uuid)])))?;
+        // Show a different few highlights every time
+        let highlights = &mut report.sections[PROFILE_HIGHLIGHTS_SECTION].rows;
+        highlights.shuffle(&mut rand::rng());
+        highlights.truncate(3);
+        Ok((uuid, report_pages(&report, Colour::DARK_GREEN)))
+    })?;
+
+    let card = language(ctx).scope(|| -> Result<_> {
+        let card = ProfileCard::new(&uuid, &username, &store.players(), Path::new(SKINS_DIR));
+        card.map(|card| card.render()).transpose()
+    })?;
+    let mut attachments = vec![];
+    if let Some(card) = card {
+        attachments.push(CreateAttachment::bytes(card, "profile.png"));
+        pages.pages[0] = pages.pages[0].clone().image("attachment://profile.png");
     }
-    let fields = vec![
-        ("Version", response.version, false),
-        (
-            "Players",
-            format!(
-                "{}/{} players online",
-                response.num_players, response.max_players
-            ),
-            false,
-        ),
-        ("Players", players, false),
-    ];
-    let embed = CreateEmbed::new()
-        .title("Server Info")
-        .description(response.motd)
-        .color(Colour::DARK_GREEN)
-        .fields(fields)
-        .footer(CreateEmbedFooter::new("Owned by FRXGFA"));
-
-    let reply = CreateReply {
-        embeds: vec![embed],
-        ..Default::default()
+    Paginator::new(pages)
+        .attachments(attachments)
+        .run(ctx)
+        .await?;
+    Ok(())
+}
+
+/// Gets the server-wide totals of all players, with a breakdown per category.
+#[poise::command(slash_command, prefix_command)]
+async fn totals(ctx: Context<'_>) -> Result<(), Error> {
+    let pages = language(ctx).scope(|| {
+        let report = totals_report(&ctx.data().stats);
+        report_pages(&report, Colour::DARK_GREEN)
+    });
+    Paginator::new(pages).run(ctx).await?;
+
+    Ok(())
+}
+
+/// Gets the top player of every stat on the server.
+#[poise::command(slash_command, prefix_command)]
+async fn superlatives(ctx: Context<'_>) -> Result<(), Error> {
+    let pages = language(ctx).scope(|| {
+        let report = superlatives_report(&ctx.data().stats);
+        report_pages(&report, Colour::GOLD)
+    });
+    Paginator::new(pages).run(ctx).await?;
+
+    Ok(())
+}
+
+/// Ranks players by a stat or a derived metric.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    subcommands("leaderboard_stat", "leaderboard_metric")
+)]
+async fn leaderboard(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Ranks players by a single stat, e.g. `diamond_ore` in Blocks Mined.
+#[poise::command(slash_command, prefix_command, rename = "stat")]
+async fn leaderboard_stat(
+    ctx: Context<'_>,
+    #[description = "What stats category the stat is in"] category: GetStatsOption,
+    #[description = "Stat, e.g. diamond_ore"] stat: String,
+) -> Result<(), Error> {
+    let stat = if stat.contains(':') {
+        stat
+    } else {
+        format!("minecraft:{}", stat)
     };
-    ctx.send(reply).await?;
+    let target = LeaderboardTarget::Stat {
+        category: category.key().to_string(),
+        stat,
+    };
+    let (pages, chart) =
+        language(ctx).scope(|| leaderboard_pages(&ctx.data().stats, &target, 0.0))?;
+    Paginator::new(pages)
+        .attachments(vec![chart])
+        .run(ctx)
+        .await?;
+
+    Ok(())
+}
+
+/// Ranks players by a derived metric like K/D or blocks mined per hour.
+#[poise::command(slash_command, prefix_command, rename = "metric")]
+async fn leaderboard_metric(
+    ctx: Context<'_>,
+    #[description = "What metric to rank by"] metric: DerivedMetric,
+    #[description = "Minimum playtime in hours (default 1)"] min_hours: Option<f64>,
+) -> Result<(), Error> {
+    let min_hours = min_hours.unwrap_or(DEFAULT_MIN_PLAYTIME_HOURS);
+    let target = LeaderboardTarget::Metric(metric);
+    let (pages, chart) =
+        language(ctx).scope(|| leaderboard_pages(&ctx.data().stats, &target, min_hours))?;
+    Paginator::new(pages)
+        .attachments(vec![chart])
+        .run(ctx)
+        .await?;
 
     Ok(())
 }
 
+/// Builds the leaderboard pages and a bar chart of the top players, shown on the first page
+fn leaderboard_pages(
+    store: &StatsStore,
+    target: &LeaderboardTarget,
+    min_hours: f64,
+) -> Result<(PageSet, CreateAttachment)> {
+    let chart_entries = rank_players(&store.players(), target, min_hours)
+        .into_iter()
+        .take(CHART_TOP_PLAYERS)
+        .map(|(uuid, value)| (get_username(store, &uuid), value))
+        .collect::<Vec<_>>();
+    let chart = CreateAttachment::bytes(
+        bar_chart(
+            &tr_with("leaderboard.title", &[("target", &target.get_name())]),
+            &chart_entries,
+        )?,
+        "leaderboard.png",
+    );
+    let report = leaderboard_report(store, target, min_hours);
+    let mut pages = report_pages(&report, Colour::GOLD);
+    pages.pages[0] = pages.pages[0].clone().image("attachment://leaderboard.png");
+    Ok((pages, chart))
+}
+
+/// Compares two players by derived metrics and headline totals.
+#[poise::command(slash_command, prefix_command)]
+async fn compare(
+    ctx: Context<'_>,
+    #[description = "First username"] player_a: String,
+    #[description = "Second username"] player_b: String,
+    #[description = "Minimum playtime in hours for derived metrics (default 1)"] min_hours: Option<
+        f64,
+    >,
+) -> Result<(), Error> {
+    let min_hours = min_hours.unwrap_or(DEFAULT_MIN_PLAYTIME_HOURS);
+    let store = &ctx.data().stats;
+    let language = language(ctx);
+    let (stats_a, stats_b) = language.scope(|| -> Result<_> {
+        Ok((
+            get_player_stats(store, &get_uuid(store, &player_a)?)?,
+            get_player_stats(store, &get_uuid(store, &player_b)?)?,
+        ))
+    })?;
 
-fn test_main(uuid: String, stats_option: Option<GetStatsOption>) -> Result<Vec<CreateEmbed>> {
-    println!("stats/{}.json", uuid);
-    let json_str = fs::read_to_string(format!("../../../school_smp/world/stats/{}.json", uuid))?;
-    println!("../../school_smp/world/stats/{}.json", uuid);
-    let stats = serde_json::from_str::<MinecraftStats>(&json_str)?.stats;
-    let mut embeds = vec![];
-    if let Some(stats_option) = stats_option {
-        let mut temp_output = String::new();
-        let embed = CreateEmbed::new()
-            .title(format!(
-                "{} for {}",
-                stats_option.get_name(),
-                get_username(&uuid)?
-            ))
-            .color(Colour::DARK_GOLD);
-
-        let display_stats = match stats_option {
-            GetStatsOption::Custom => Some(&stats.custom),
-            GetStatsOption::Mined => stats.mined.as_ref(),
-            GetStatsOption::Crafted => stats.crafted.as_ref(),
-            GetStatsOption::Used => stats.used.as_ref(),
-            GetStatsOption::Broken => stats.broken.as_ref(),
-            GetStatsOption::PickedUp => stats.picked_up.as_ref(),
-            GetStatsOption::Dropped => stats.dropped.as_ref(),
-            GetStatsOption::Killed => stats.killed.as_ref(),
-            GetStatsOption::KilledBy => stats.killed_by.as_ref(),
-            GetStatsOption::CustomEntity => stats.custom_entity.as_ref(),
+    let mut radar_a = vec![];
+    let mut radar_b = vec![];
+    for metric in DerivedMetric::ALL {
+        // Metrics of players under the threshold are too noisy to compare
+        let value = |stats: &MinecraftStats| {
+            metric
+                .compute(&stats.stats)
+                .filter(|_| playtime_hours(&stats.stats) >= min_hours)
+                .unwrap_or(0.0)
         };
-        if let Some(display_stats) = display_stats {
-            for (key, value) in serde_json::to_value(display_stats)?.as_object().unwrap() {
-                let readable_key = convert_to_readable(key);
-                temp_output.push_str(&format!("\t**{}**: {}\n", readable_key, value));
-            }
-        }
-        let embed = embed.description(&temp_output);
-        embeds.push(embed);
-        return Ok(embeds);
-    }
-    for (i, (key, value)) in serde_json::to_value(&stats)?
-        .as_object()
-        .unwrap()
-        .iter()
-        .enumerate()
-    {
-
-        let readable_key = MinecraftStats::get_readable_name(key);
-        let mut temp_output = String::new();
-        let value_obj = value.as_object();
-        let converter_function = match &key[..] {
-            "minecraft:custom" => create_used_stat_names(),
-            "minecraft:mined" => create_mined_stat_names(),
-            "minecraft:crafted" => create_crafted_stat_names(),
-            "minecraft:used" => create_used_stat_names(),
-            "minecraft:broken" => create_broken_stat_names(),
-            "minecraft:picked_up" => create_picked_up_stat_names(),
-            "minecraft:dropped" => create_dropped_stat_names(),
-            "minecraft:killed" => create_killed_stat_names(),
-            "minecraft:killed_by" => create_killed_stat_names(),
-            "minecraft:custom_entity" => HashMap::new(),
-            _ => HashMap::new(),
+        // Each radar axis is relative to the better of the two players
+        let (x, y) = (value(&stats_a), value(&stats_b));
+        let (x, y) = if metric.higher_is_better() {
+            (x, y)
+        } else {
+            (1.0 / (1.0 + x), 1.0 / (1.0 + y))
         };
-        if let Some(value_obj) = value_obj {
-            for (sub_key, sub_value) in value_obj {
-                let readable_sub_key = converter_function.get(sub_key);
-                if let Some(readable_sub_key) = readable_sub_key {
-                    temp_output.push_str(&format!("\t**{}**: {}\n", readable_sub_key, sub_value));
-                } else {
-                    let readable_sub_key = convert_to_readable(sub_key);
-                    temp_output.push_str(&format!("\t**{}**: {}\n", readable_sub_key, sub_value));
-                }
-            }
-        }
-        let chunks = temp_output.lines().collect::<Vec<&str>>();
-        let chunks = chunks.chunks(20).collect::<Vec<&[&str]>>();
-        let fields: Vec<(String, String, bool)> = chunks
+        let max = x.max(y);
+        radar_a.push(if max > 0.0 { x / max } else { 0.0 });
+        radar_b.push(if max > 0.0 { y / max } else { 0.0 });
+    }
+    let axes = language.scope(|| {
+        DerivedMetric::ALL
             .iter()
-            .enumerate()
-            .map(|(i, chunk)| {
-                let chunk = chunk.join("\n");
-                (format!("Part {}/{}", i + 1, chunks.len()), chunk, false)
-            })
-            .collect();
+            .map(|metric| metric.get_name())
+            .collect::<Vec<_>>()
+    });
+    let chart = radar_chart(
+        &language.scope(|| tr_with("compare.title", &[("a", &player_a), ("b", &player_b)])),
+        &axes,
+        &[(player_a.clone(), radar_a), (player_b.clone(), radar_b)],
+    )?;
 
-        let embed = CreateEmbed::new()
-            .title(format!("{} for {}", readable_key, get_username(&uuid)?))
-            .color(Colour::DARK_GREEN)
-            .fields(fields)
-            .footer(CreateEmbedFooter::new(format!("Part {}/{}", i + 1, 10)));
+    let mut pages = language.scope(|| {
+        let report = compare_report((&player_a, &stats_a), (&player_b, &stats_b), min_hours);
+        report_pages(&report, Colour::DARK_GREEN)
+    });
+    pages.pages[0] = pages.pages[0].clone().image("attachment://compare.png");
+    Paginator::new(pages)
+        .attachments(vec![CreateAttachment::bytes(chart, "compare.png")])
+        .run(ctx)
+        .await?;
 
-        embeds.push(embed);
-    }
-    Ok(embeds)
+    Ok(())
+}
 
+/// Exports every player's stats as a spreadsheet-friendly file.
+#[poise::command(slash_command, prefix_command)]
+async fn export(
+    ctx: Context<'_>,
+    #[description = "Category to export, all categories if left out"] category: Option<
+        GetStatsOption,
+    >,
+    #[description = "File format, CSV if left out"] format: Option<ExportFormat>,
+) -> Result<(), Error> {
+    let format = format.unwrap_or(ExportFormat::Csv);
+    let file_name = match &category {
+        Some(category) => format!(
+            "stats_{}.{}",
+            category.key().replace("minecraft:", ""),
+            format.extension()
+        ),
+        None => format!("stats.{}", format.extension()),
+    };
+    // The CSV headers are readable stat names, so they're translated too
+    let (matrix, summary) = language(ctx).scope(|| {
+        let matrix = StatsMatrix::build(&ctx.data().stats, category.as_ref().map(|c| c.key()));
+        let summary = tr_with(
+            "export.summary",
+            &[
+                ("players", &matrix.rows.len()),
+                ("columns", &matrix.headers.len()),
+            ],
+        );
+        (matrix, summary)
+    });
+    let reply = CreateReply::default()
+        .content(summary)
+        .attachment(CreateAttachment::bytes(matrix.export(format)?, file_name));
+    ctx.send(reply).await?;
+    Ok(())
 }
 
-/// Gets the playtime of all the players in the server.
+/// Shows a pie chart of what has killed a player.
 #[poise::command(slash_command, prefix_command)]
-async fn playtime(ctx: Context<'_>) -> Result<(), Error> {
-    let usercache_text = fs::read_to_string("../../../school_smp/usercache.json").unwrap();
-    let usercache: Vec<UserCache> = usercache_text.to_json().unwrap();
-    let mut fields: Vec<(String, String, bool)> = Vec::new();
+async fn killed_by(
+    ctx: Context<'_>,
+    #[description = "Username"] username: String,
+) -> Result<(), Error> {
+    let store = &ctx.data().stats;
+    let language = language(ctx);
+    let report = language.scope(|| -> Result<_> {
+        let stats = get_player_stats(store, &get_uuid(store, &username)?)?;
+        Ok(player_stats_report(
+            &username,
+            &stats,
+            Some("minecraft:killed_by"),
+            &StatFilter::default(),
+        ))
+    })?;
+    let entries = report
+        .sections
+        .iter()
+        .flat_map(|section| &section.rows)
+        .filter_map(|row| match row.values.first()? {
+            ReportValue::Number { value, .. } => Some((row.label.clone(), *value)),
+            ReportValue::Text(_) => None,
+        })
+        .collect::<Vec<_>>();
+    let title = language.scope(|| tr_with("killed_by.title", &[("player", &username)]));
+    let chart = pie_chart(&title, &entries)?;
+
+    let mut pages = language.scope(|| report_pages(&StatsReport::new(title), Colour::DARK_RED));
+    pages.pages[0] = pages.pages[0].clone().image("attachment://killed_by.png");
+    Paginator::new(pages)
+        .attachments(vec![CreateAttachment::bytes(chart, "killed_by.png")])
+        .run(ctx)
+        .await?;
 
+    Ok(())
+}
 
-    for entry in WalkDir::new("../../../school_smp/world/stats")
-        .min_depth(1)
+/// Shows a chart of a player's playtime over time.
+#[poise::command(slash_command, prefix_command)]
+async fn playtime_history(
+    ctx: Context<'_>,
+    #[description = "Username"] username: String,
+) -> Result<(), Error> {
+    let store = &ctx.data().stats;
+    let language = language(ctx);
+    let uuid = language.scope(|| get_uuid(store, &username))?;
+    let snapshots = load_snapshots(Path::new(SNAPSHOTS_PATH))?;
+    let points = stats_parser::snapshots::playtime_history(&snapshots, &uuid)
         .into_iter()
-        .filter_map(|e| e.ok())
-    {
-        let file = entry.path();
-        if file.is_file() {
-            let file_name = file.file_name().unwrap().to_str().unwrap();
-            if file_name.ends_with(".json") {
-                let json_str = fs::read_to_string(file).unwrap();
-                let stats: Value = json_str.to_json().unwrap();
-                let player_name = file_name.split(".").collect::<Vec<_>>()[0]
-                    .to_string()
-                    .find_player(usercache.clone());
-                    fields.push((
-                    player_name.clone(),
-                    fmt_time(
-                        stats["stats"]["minecraft:custom"]["minecraft:play_time"]
-                            .to_string()
-                            .parse::<u64>()
-                            .unwrap(),
-                    ),
-                    true,
-                ));
+        .map(|(time, ticks)| (time, ticks as f64 / 72_000.0))
+        .collect::<Vec<_>>();
+    let title = language.scope(|| tr_with("playtime_history.title", &[("player", &username)]));
+    let chart = line_chart(
+        &title,
+        &language.scope(|| tr("playtime_history.axis")),
+        &points,
+    )?;
 
-            }
-        }
-    }
-    let embed = serenity::CreateEmbed::new()
-        .title("Playtime")
-        .fields(fields)
-        .color(serenity::Colour::TEAL);
-    let reply = CreateReply {
-        embeds: vec![embed],
-        ..Default::default()
-    };
-    ctx.send(reply).await?;
+    let mut pages = language.scope(|| {
+        let report = StatsReport::new(title).description(tr_with(
+            "playtime_history.snapshots",
+            &[("count", &points.len())],
+        ));
+        report_pages(&report, Colour::TEAL)
+    });
+    pages.pages[0] = pages.pages[0].clone().image("attachment://playtime.png");
+    Paginator::new(pages)
+        .attachments(vec![CreateAttachment::bytes(chart, "playtime.png")])
+        .run(ctx)
+        .await?;
+
+    Ok(())
+}
+
+/// Shows how a player has died, based on the death messages in the server log.
+#[poise::command(slash_command, prefix_command)]
+async fn deaths(
+    ctx: Context<'_>,
+    #[description = "Username"] username: String,
+) -> Result<(), Error> {
+    let store = &ctx.data().stats;
+    let known_players = store.usernames();
+    // Reading every archived log takes a while, so keep it off the async runtime
+    let all_deaths = tokio::task::spawn_blocking(move || {
+        read_deaths_from_logs(Path::new(LOGS_DIR), &known_players)
+    })
+    .await??;
+    let breakdown = death_breakdown(&all_deaths, &username);
+
+    let pages = language(ctx).scope(|| -> Result<_> {
+        let uuid = get_uuid(store, &username)?;
+        let stats = get_player_stats(store, &uuid)?;
+        let recorded_deaths = stats.stats.custom_stat(CustomStat::Deaths) as u64;
 
+        let report = deaths_report(&username, &breakdown, recorded_deaths);
+        Ok(report_pages(&report, Colour::DARK_RED))
+    })?;
+    Paginator::new(pages).run(ctx).await?;
 
     Ok(())
 }
 
-/// Gets the profile of an account.
+/// Shows the objectives of the server's scoreboard, or the ranking of one.
 #[poise::command(slash_command, prefix_command)]
-async fn profile(
+async fn scoreboard(
+    ctx: Context<'_>,
+    #[description = "Objective, all objectives if left out"]
+    #[autocomplete = "autocomplete_objective"]
+    objective: Option<String>,
+) -> Result<(), Error> {
+    let scoreboard = Scoreboard::load(Path::new(SCOREBOARD_PATH))?;
+    let pages = language(ctx).scope(|| -> Result<_> {
+        let report = match objective {
+            Some(name) => {
+                let objective = scoreboard.objective(&name).ok_or_else(|| {
+                    let message = tr_with("scoreboard.unknown_objective", &[("objective", &name)]);
+                    anyhow::anyhow!(message)
+                })?;
+                objective_report(&scoreboard, objective)
+            }
+            None => scoreboard_report(&scoreboard),
+        };
+        Ok(report_pages(&report, Colour::GOLD))
+    })?;
+    Paginator::new(pages).run(ctx).await?;
+
+    Ok(())
+}
+
+/// Shows the members of a scoreboard team.
+#[poise::command(slash_command, prefix_command)]
+async fn team(
+    ctx: Context<'_>,
+    #[description = "Name of the team"]
+    #[autocomplete = "autocomplete_team"]
+    name: String,
+) -> Result<(), Error> {
+    let scoreboard = Scoreboard::load(Path::new(SCOREBOARD_PATH))?;
+    let pages = language(ctx).scope(|| -> Result<_> {
+        let team = scoreboard
+            .team(&name)
+            .ok_or_else(|| anyhow::anyhow!(tr_with("team.unknown", &[("team", &name)])))?;
+        Ok(report_pages(&team_report(team), Colour::DARK_GREEN))
+    })?;
+    Paginator::new(pages).run(ctx).await?;
+
+    Ok(())
+}
+
+/// Suggests the scoreboard's objectives that start with what was typed
+async fn autocomplete_objective(_ctx: Context<'_>, partial: &str) -> Vec<String> {
+    let scoreboard = Scoreboard::load(Path::new(SCOREBOARD_PATH)).unwrap_or_default();
+    scoreboard
+        .objectives
+        .into_iter()
+        .map(|objective| objective.name)
+        .filter(|name| name.to_lowercase().starts_with(&partial.to_lowercase()))
+        .take(25)
+        .collect()
+}
+
+/// Suggests the scoreboard's teams that start with what was typed
+async fn autocomplete_team(_ctx: Context<'_>, partial: &str) -> Vec<String> {
+    let scoreboard = Scoreboard::load(Path::new(SCOREBOARD_PATH)).unwrap_or_default();
+    scoreboard
+        .teams
+        .into_iter()
+        .map(|team| team.name)
+        .filter(|name| name.to_lowercase().starts_with(&partial.to_lowercase()))
+        .take(25)
+        .collect()
+}
+
+/// Shows or changes the server's whitelist. Changes need RCON.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    subcommands("whitelist_show", "whitelist_add", "whitelist_remove"),
+    guild_only,
+    default_member_permissions = "ADMINISTRATOR",
+    required_permissions = "ADMINISTRATOR"
+)]
+async fn whitelist(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Shows the whitelist, who on it has never joined and who joined without being on it.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "show",
+    guild_only,
+    required_permissions = "ADMINISTRATOR"
+)]
+async fn whitelist_show(ctx: Context<'_>) -> Result<(), Error> {
+    let lists = AccessLists::load(Path::new(SERVER_DIR))?;
+    let pages = language(ctx).scope(|| {
+        let report = whitelist_report(&lists, &ctx.data().stats);
+        report_pages(&report, Colour::DARK_GREEN)
+    });
+    Paginator::new(pages).run(ctx).await?;
+
+    Ok(())
+}
+
+/// Adds a player to the whitelist.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "add",
+    guild_only,
+    required_permissions = "ADMINISTRATOR"
+)]
+async fn whitelist_add(
     ctx: Context<'_>,
     #[description = "Username"] username: String,
 ) -> Result<(), Error> {
-    let uuid = get_uuid(&username)?;
-    let json_str = fs::read_to_string(format!("../../../school_smp/world/stats/{}.json", uuid))?;
-    let stats = serde_json::from_str::<MinecraftStats>(&json_str)?.stats;
-    let playtime_ticks = stats.custom.get("minecraft:play_time");
-    let playtime_string = if let Some(playtime_ticks) = playtime_ticks {
-        let playtime_ticks = *playtime_ticks;
-        let playtime_u64: u64 = playtime_ticks.try_into().unwrap();
-        fmt_time(playtime_u64)
-    } else {
-        "N/A".to_string()
+    change_list(ctx, &username, format!("whitelist add {}", username)).await
+}
+
+/// Removes a player from the whitelist.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "remove",
+    guild_only,
+    required_permissions = "ADMINISTRATOR"
+)]
+async fn whitelist_remove(
+    ctx: Context<'_>,
+    #[description = "Username"] username: String,
+) -> Result<(), Error> {
+    change_list(ctx, &username, format!("whitelist remove {}", username)).await
+}
+
+/// Shows the server's operators and their permission levels.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    default_member_permissions = "ADMINISTRATOR",
+    required_permissions = "ADMINISTRATOR"
+)]
+async fn ops(ctx: Context<'_>) -> Result<(), Error> {
+    let lists = AccessLists::load(Path::new(SERVER_DIR))?;
+    let pages = language(ctx).scope(|| {
+        let report = ops_report(&lists, &ctx.data().stats);
+        report_pages(&report, Colour::GOLD)
+    });
+    Paginator::new(pages).run(ctx).await?;
+
+    Ok(())
+}
+
+/// Shows or changes the server's bans. Changes need RCON.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    subcommands("bans_show", "bans_add", "bans_remove"),
+    guild_only,
+    default_member_permissions = "ADMINISTRATOR",
+    required_permissions = "ADMINISTRATOR"
+)]
+async fn bans(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Shows the banned players and IPs.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "show",
+    guild_only,
+    required_permissions = "ADMINISTRATOR"
+)]
+async fn bans_show(ctx: Context<'_>) -> Result<(), Error> {
+    let lists = AccessLists::load(Path::new(SERVER_DIR))?;
+    let pages = language(ctx).scope(|| report_pages(&bans_report(&lists), Colour::RED));
+    Paginator::new(pages).run(ctx).await?;
+
+    Ok(())
+}
+
+/// Bans a player from the server.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "add",
+    guild_only,
+    required_permissions = "ADMINISTRATOR"
+)]
+async fn bans_add(
+    ctx: Context<'_>,
+    #[description = "Username"] username: String,
+    #[description = "Reason shown to the player"]
+    #[rest]
+    reason: Option<String>,
+) -> Result<(), Error> {
+    let command = match reason {
+        // The reason is the rest of the command, so it can't span lines
+        Some(reason) => format!("ban {} {}", username, reason.replace(['\r', '\n'], " ")),
+        None => format!("ban {}", username),
     };
-    let killed_by = stats.killed_by;
-    let mut deaths = 0;
-    for (_, value) in serde_json::to_value(killed_by)?.as_object().unwrap().iter() {
-        deaths += value.as_u64().unwrap();
+    change_list(ctx, &username, command).await
+}
+
+/// Unbans a player.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "remove",
+    guild_only,
+    required_permissions = "ADMINISTRATOR"
+)]
+async fn bans_remove(
+    ctx: Context<'_>,
+    #[description = "Username"] username: String,
+) -> Result<(), Error> {
+    change_list(ctx, &username, format!("pardon {}", username)).await
+}
+
+/// Runs a server command that changes a player list, e.g. `whitelist add Steve`,
+/// through RCON and replies with the server's answer. The server saves the list itself.
+async fn change_list(ctx: Context<'_>, username: &str, command: String) -> Result<(), Error> {
+    let language = language(ctx);
+    if !is_valid_username(username) {
+        let message =
+            language.scope(|| tr_with("access.invalid_username", &[("player", &username)]));
+        return Err(anyhow::anyhow!(message).into());
     }
-    let custom = stats.custom;
-    let kills = custom.get("minecraft:player_kills");
-    let kills: u64 = if let Some(kills) = kills {
-        let kills = *kills;
-        kills.try_into().unwrap()
-    } else {
-        println!("No kills");
-        0_u64
+    let Some(rcon) = &ctx.data().rcon else {
+        return Err(anyhow::anyhow!(language.scope(|| tr("access.no_rcon"))).into());
     };
+    let response = rcon.run(&command).await?;
+    ctx.say(language.scope(|| tr_with("access.server_replied", &[("response", &response)])))
+        .await?;
+    Ok(())
+}
 
+/// Picks the language the bot answers in, for you or the whole server.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "language",
+    subcommands("language_me", "language_server")
+)]
+async fn set_language(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
 
-    let embed = CreateEmbed::new()
-        .title(format!("{}'s Profile", username))
-        .description(format!("UUID: `{}`", uuid))
-        .fields([
-            ("Playtime".to_string(), playtime_string, true),
-            ("Kills".to_string(), kills.to_string(), true),
-            ("Deaths".to_string(), deaths.to_string(), true),
-        ])
-        .color(Colour::DARK_GREEN);
-
-    let reply = CreateReply {
-        embeds: vec![embed],
-        ..Default::default()
+/// Picks the language the bot answers you in.
+#[poise::command(slash_command, prefix_command, rename = "me")]
+async fn language_me(
+    ctx: Context<'_>,
+    #[description = "Language, shows the current one if left out"] language: Option<Language>,
+) -> Result<(), Error> {
+    let key = match language {
+        Some(language) => {
+            let mut languages = ctx.data().languages.write().unwrap();
+            languages.users.insert(ctx.author().id.get(), language);
+            languages.save(Path::new(LANGUAGES_PATH))?;
+            "language.set_user"
+        }
+        None => "language.current",
     };
-    ctx.send(reply).await?;
+    let language = self::language(ctx);
+    ctx.say(language.scope(|| tr_with(key, &[("language", &language.name())])))
+        .await?;
+    Ok(())
+}
+
+/// Picks the language the bot answers in on this server, for everyone without one of their own.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "server",
+    guild_only,
+    required_permissions = "MANAGE_GUILD"
+)]
+async fn language_server(
+    ctx: Context<'_>,
+    #[description = "Language"] language: Language,
+) -> Result<(), Error> {
+    if let Some(guild) = ctx.guild_id() {
+        let mut languages = ctx.data().languages.write().unwrap();
+        languages.guilds.insert(guild.get(), language);
+        languages.save(Path::new(LANGUAGES_PATH))?;
+    }
+    ctx.say(language.scope(|| tr_with("language.set_guild", &[("language", &language.name())])))
+        .await?;
     Ok(())
 }
 
+/// Fills in the Discord localisations of a command's description and
+/// parameters from the message catalogues, e.g. `command.leaderboard stat`
+fn localise_command(command: &mut poise::Command<Data, Error>) {
+    for language in Language::ALL {
+        let locale = language.discord_locale().to_string();
+        let key = format!("command.{}", command.qualified_name);
+        if let Some(description) = language.message(&key) {
+            command
+                .description_localizations
+                .insert(locale.clone(), description.to_string());
+        }
+        for parameter in &mut command.parameters {
+            if let Some(description) = language.message(&format!("{}.{}", key, parameter.name)) {
+                parameter
+                    .description_localizations
+                    .insert(locale.clone(), description.to_string());
+            }
+        }
+    }
+    command.subcommands.iter_mut().for_each(localise_command);
+}
+
 #[poise::command(slash_command, prefix_command)]
 async fn timetable(
     ctx: Context<'_>,
     #[description = "What day of the week"] day: Day,
     #[description = "Section"] section: Option<Section>,
 ) -> Result<(), Error> {
-    let section = if let None = section {
+    let section = if let Some(section) = section {
+        section
+    } else {
         let user = ctx.author_member().await.unwrap();
         let user_roles = user.roles.clone();
         let server_roles = {
@@ -372,50 +982,188 @@ async fn timetable(
             }
         }
         has_section_role.expect("No section role found")
-    } else {
-        section.unwrap()
     };
 
     let section_string = String::from(section);
-    let toml_file = format!("{}.toml", section_string);
-    let timetable_raw =
-        fs::read_to_string(format!("timetables/{}", toml_file)).expect("Could not read timetable");
-    let timetable: Timetable = toml::from_str(&timetable_raw).expect("Could not parse timetable");
-    let day = timetable.get_day(day);
-    let text = day
-        .iter()
-        .map(|x| format!("- {}", x))
-        .collect::<Vec<String>>()
-        .join("\n");
-    let embed = CreateEmbed::new()
-        .title(format!("Timetable for {}", section_string))
-        .description(format!("**{}**", text))
-        .color(Colour::DARK_GREEN);
-    let reply = CreateReply {
-        embeds: vec![embed],
-        ..Default::default()
-    };
-    ctx.send(reply).await?;
+    let timetable = read_timetable(&section_string)?;
+    let pages = language(ctx).scope(|| {
+        let report = timetable_report(&section_string, &timetable.get_day(day));
+        report_pages(&report, Colour::DARK_GREEN)
+    });
+    Paginator::new(pages).run(ctx).await?;
 
     Ok(())
 }
 
-
-
 #[tokio::main]
 async fn main() {
+    let cli = Cli::parse();
+    // Poise and serenity log through tracing too, e.g. errors in commands
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        )
+        .with_writer(std::io::stderr)
+        .init();
+    // Without language files, names come from the built-in tables
+    if let Err(e) = StatNames::load_dir(&cli.lang_dir).and_then(lang::install) {
+        tracing::warn!("{:#}", e);
+    }
+    if let Err(e) = NameOverrides::load_dir(&cli.names_dir).and_then(lang::install_overrides) {
+        tracing::warn!("{:#}", e);
+    }
+    if let Some(language) = cli.language {
+        i18n::set_default(language);
+    }
+    if !matches!(cli.command, None | Some(cli::Command::Bot)) {
+        if let Err(e) = cli::run(cli).await {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
     let token = std::env::var("DISCORD_TOKEN").expect("missing DISCORD_TOKEN");
     let intents = serenity::GatewayIntents::non_privileged();
 
+    let mut commands = vec![
+        get_stats(),
+        server(),
+        serverinfo(),
+        get_stats_username(),
+        playtime(),
+        profile(),
+        timetable(),
+        deaths(),
+        totals(),
+        superlatives(),
+        leaderboard(),
+        compare(),
+        killed_by(),
+        playtime_history(),
+        export(),
+        scoreboard(),
+        team(),
+        whitelist(),
+        ops(),
+        bans(),
+        set_language(),
+    ];
+    // Localisations are looked up by the full name, e.g. `leaderboard stat`
+    poise::set_qualified_names(&mut commands);
+    commands.iter_mut().for_each(localise_command);
+
     let framework = poise::Framework::builder()
         .options(poise::FrameworkOptions {
-            commands: vec![get_stats(), server(),get_stats_username(), playtime(), profile(), timetable()],
+            commands,
+            #[cfg(feature = "prometheus")]
+            pre_command: |ctx| {
+                Box::pin(async move {
+                    if let Some(telemetry) = &ctx.data().telemetry {
+                        telemetry.command_invoked(&ctx.command().qualified_name);
+                    }
+                })
+            },
+            #[cfg(feature = "prometheus")]
+            on_error: |error| {
+                Box::pin(async move {
+                    if let Some(ctx) = error.ctx()
+                        && let Some(telemetry) = &ctx.data().telemetry
+                    {
+                        telemetry.command_failed(&ctx.command().qualified_name);
+                    }
+                    if let Err(e) = poise::builtins::on_error(error).await {
+                        tracing::error!("Error while handling error: {}", e);
+                    }
+                })
+            },
             ..Default::default()
         })
         .setup(|ctx, _ready, framework| {
             Box::pin(async move {
+                let stats = Arc::new(StatsStore::load_with_name_history(
+                    STATS_DIR,
+                    USERCACHE_PATH,
+                    NAME_HISTORY_PATH,
+                )?);
+                let watcher = stats.watch()?;
+                // Stats files only hold totals, so record playtime for history charts
+                let snapshot_stats = Arc::clone(&stats);
+                tokio::spawn(async move {
+                    let mut interval = tokio::time::interval(SNAPSHOT_INTERVAL);
+                    loop {
+                        interval.tick().await;
+                        let snapshot = PlaytimeSnapshot::take(&snapshot_stats.players());
+                        if let Err(e) = append_snapshot(Path::new(SNAPSHOTS_PATH), &snapshot) {
+                            tracing::warn!("Could not save playtime snapshot: {}", e);
+                        }
+                    }
+                });
                 poise::builtins::register_globally(ctx, &framework.options().commands).await?;
-                Ok(Data {})
+                // Set DEATH_FEED_CHANNEL to a channel ID to post deaths as they happen
+                if let Ok(channel) = std::env::var("DEATH_FEED_CHANNEL") {
+                    let channel = serenity::ChannelId::new(channel.parse()?);
+                    tokio::spawn(run_death_feed(
+                        ctx.http.clone(),
+                        channel,
+                        PathBuf::from(LOGS_DIR).join("latest.log"),
+                        Arc::clone(&stats),
+                    ));
+                }
+                // Set ADMIN_CHANNEL to a channel ID to warn admins when server.properties changes
+                if let Ok(channel) = std::env::var("ADMIN_CHANNEL") {
+                    let channel = serenity::ChannelId::new(channel.parse()?);
+                    tokio::spawn(run_properties_watch(
+                        ctx.http.clone(),
+                        channel,
+                        PathBuf::from(SERVER_DIR).join("server.properties"),
+                        PathBuf::from(PROPERTIES_SNAPSHOT_PATH),
+                    ));
+                }
+                // Set API_ADDR, e.g. 0.0.0.0:8080, to serve the stats over HTTP
+                #[cfg(feature = "api")]
+                if let Ok(addr) = std::env::var("API_ADDR") {
+                    let api_stats = Arc::clone(&stats);
+                    let addr = addr.parse()?;
+                    tokio::spawn(async move {
+                        let server_dir = PathBuf::from(SERVER_DIR);
+                        if let Err(e) = api::run_api(addr, api_stats, server_dir).await {
+                            tracing::error!("HTTP API stopped: {}", e);
+                        }
+                    });
+                }
+                // Set METRICS_ADDR, e.g. 0.0.0.0:9100, to export Prometheus metrics
+                #[cfg(feature = "prometheus")]
+                let telemetry = match std::env::var("METRICS_ADDR") {
+                    Ok(addr) => {
+                        let addr = addr.parse()?;
+                        let server = match query_port() {
+                            Ok(port) => Some((SERVER_HOST.to_string(), port)),
+                            Err(e) => {
+                                tracing::warn!("{}, server metrics will be missing", e);
+                                None
+                            }
+                        };
+                        let telemetry =
+                            Arc::new(telemetry::Telemetry::new(Arc::clone(&stats), server)?);
+                        let exporter = Arc::clone(&telemetry);
+                        tokio::spawn(async move {
+                            if let Err(e) = telemetry::run_metrics(addr, exporter).await {
+                                tracing::error!("Metrics exporter stopped: {}", e);
+                            }
+                        });
+                        Some(telemetry)
+                    }
+                    Err(_) => None,
+                };
+                Ok(Data {
+                    stats,
+                    _watcher: watcher,
+                    languages: RwLock::new(LanguagePreferences::load(Path::new(LANGUAGES_PATH))?),
+                    // Set RCON_ADDR, e.g. 127.0.0.1:25575, and RCON_PASSWORD to change lists
+                    rcon: RconConfig::from_env(),
+                    #[cfg(feature = "prometheus")]
+                    telemetry,
+                })
             })
         })
         .build();
@@ -0,0 +1,123 @@
+use crate::StatCategories;
+use crate::i18n::tr;
+use crate::report::Unit;
+use crate::stat_key::CustomStat;
+
+/// Ticks in an hour of playtime
+const TICKS_PER_HOUR: f64 = 20.0 * 3600.0;
+
+/// Players with less playtime than this are left out of derived-metric rankings,
+/// so a lucky first hour doesn't top the leaderboard
+pub const DEFAULT_MIN_PLAYTIME_HOURS: f64 = 1.0;
+
+/// Stats derived from the raw totals, so new and veteran players compare fairly
+#[derive(poise::ChoiceParameter, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DerivedMetric {
+    #[name = "PvP K/D Ratio"]
+    #[name = "kd_ratio"]
+    KillDeathRatio,
+
+    #[name = "Damage Dealt/Taken Ratio"]
+    #[name = "damage_ratio"]
+    DamageRatio,
+
+    #[name = "Blocks Mined per Hour"]
+    #[name = "blocks_per_hour"]
+    BlocksMinedPerHour,
+
+    #[name = "Deaths per Hour"]
+    #[name = "deaths_per_hour"]
+    DeathsPerHour,
+
+    #[name = "Distance per Hour"]
+    #[name = "distance_per_hour"]
+    DistancePerHour,
+
+    #[name = "Trades per Hour"]
+    #[name = "trades_per_hour"]
+    TradesPerHour,
+}
+
+impl DerivedMetric {
+    pub const ALL: [DerivedMetric; 6] = [
+        DerivedMetric::KillDeathRatio,
+        DerivedMetric::DamageRatio,
+        DerivedMetric::BlocksMinedPerHour,
+        DerivedMetric::DeathsPerHour,
+        DerivedMetric::DistancePerHour,
+        DerivedMetric::TradesPerHour,
+    ];
+
+    pub fn get_name(&self) -> String {
+        tr(match self {
+            DerivedMetric::KillDeathRatio => "metric.kill_death_ratio",
+            DerivedMetric::DamageRatio => "metric.damage_ratio",
+            DerivedMetric::BlocksMinedPerHour => "metric.blocks_mined_per_hour",
+            DerivedMetric::DeathsPerHour => "metric.deaths_per_hour",
+            DerivedMetric::DistancePerHour => "metric.distance_per_hour",
+            DerivedMetric::TradesPerHour => "metric.trades_per_hour",
+        })
+    }
+
+    /// Whether a higher value is better. Used to order leaderboards.
+    pub fn higher_is_better(&self) -> bool {
+        !matches!(self, DerivedMetric::DeathsPerHour)
+    }
+
+    /// Computes the metric for a player. Returns `None` if the player has no
+    /// playtime to divide by. Ratios with a zero denominator use the numerator,
+    /// like most games do for a K/D without deaths. The K/D counts player kills
+    /// against deaths from any cause, the same numbers `/profile` shows.
+    pub fn compute(&self, stats: &StatCategories) -> Option<f64> {
+        let custom = |stat: CustomStat| stats.custom_stat(stat).max(0) as f64;
+        let ratio = |numerator: f64, denominator: f64| {
+            if denominator == 0.0 {
+                numerator
+            } else {
+                numerator / denominator
+            }
+        };
+        let hours = playtime_hours(stats);
+        let per_hour = |value: f64| (hours > 0.0).then(|| value / hours);
+        match self {
+            DerivedMetric::KillDeathRatio => Some(ratio(
+                custom(CustomStat::PlayerKills),
+                custom(CustomStat::Deaths),
+            )),
+            DerivedMetric::DamageRatio => Some(ratio(
+                custom(CustomStat::DamageDealt),
+                custom(CustomStat::DamageTaken),
+            )),
+            DerivedMetric::BlocksMinedPerHour => {
+                per_hour(stats.category_total("minecraft:mined") as f64)
+            }
+            DerivedMetric::DeathsPerHour => per_hour(custom(CustomStat::Deaths)),
+            DerivedMetric::DistancePerHour => per_hour(
+                stats
+                    .custom
+                    .iter()
+                    .filter(|(key, _)| key.ends_with("_one_cm"))
+                    .map(|(_, value)| (*value).max(0) as f64)
+                    .sum(),
+            ),
+            DerivedMetric::TradesPerHour => per_hour(custom(CustomStat::TradedWithVillager)),
+        }
+    }
+
+    pub fn unit(&self) -> Unit {
+        match self {
+            DerivedMetric::DistancePerHour => Unit::CentimetresPerHour,
+            DerivedMetric::KillDeathRatio | DerivedMetric::DamageRatio => Unit::Ratio,
+            _ => Unit::PerHour,
+        }
+    }
+
+    pub fn format(&self, value: f64) -> String {
+        self.unit().format(value)
+    }
+}
+
+/// Gets a player's playtime in hours
+pub fn playtime_hours(stats: &StatCategories) -> f64 {
+    stats.custom_stat(CustomStat::PlayTime).max(0) as f64 / TICKS_PER_HOUR
+}
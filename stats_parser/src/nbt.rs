@@ -0,0 +1,342 @@
+use anyhow::{Result, bail};
+use flate2::read::GzDecoder;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+
+/// A value of Minecraft's binary NBT format, used by files like `level.dat`
+/// and `scoreboard.dat`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    List(Vec<Tag>),
+    Compound(HashMap<String, Tag>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Tag {
+    /// Gets a child of a compound
+    pub fn get(&self, key: &str) -> Option<&Tag> {
+        match self {
+            Tag::Compound(children) => children.get(key),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Tag::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Gets any whole number, e.g. a score or a flag stored as a byte
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Tag::Byte(value) => Some(*value as i64),
+            Tag::Short(value) => Some(*value as i64),
+            Tag::Int(value) => Some(*value as i64),
+            Tag::Long(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Tag]> {
+        match self {
+            Tag::List(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn as_compound(&self) -> Option<&HashMap<String, Tag>> {
+        match self {
+            Tag::Compound(children) => Some(children),
+            _ => None,
+        }
+    }
+}
+
+/// Reads an NBT file, gzipped like most of Minecraft's or not
+pub fn read_file(path: &Path) -> Result<Tag> {
+    parse(&std::fs::read(path)?)
+}
+
+/// Parses NBT data, gunzipping it first if needed. Returns the root tag,
+/// which is a compound with an empty name in every file Minecraft writes.
+pub fn parse(bytes: &[u8]) -> Result<Tag> {
+    if bytes.starts_with(&[0x1f, 0x8b]) {
+        let mut data = vec![];
+        GzDecoder::new(bytes).read_to_end(&mut data)?;
+        return parse(&data);
+    }
+    let mut reader = Reader { bytes, pos: 0 };
+    let id = reader.u8()?;
+    // The root's name is always empty, so it isn't worth returning
+    reader.string()?;
+    reader.payload(id, 0)
+}
+
+/// Nested lists and compounds deeper than this are rejected, like Minecraft does
+const MAX_DEPTH: usize = 512;
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8]> {
+        let Some(bytes) = self.bytes.get(self.pos..self.pos + len) else {
+            bail!("Unexpected end of NBT data at byte {}", self.pos);
+        };
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into()?)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn i32(&mut self) -> Result<i32> {
+        Ok(i32::from_be_bytes(self.array()?))
+    }
+
+    fn i64(&mut self) -> Result<i64> {
+        Ok(i64::from_be_bytes(self.array()?))
+    }
+
+    /// Length of an array or list, which can't be negative
+    fn len(&mut self) -> Result<usize> {
+        Ok(self.i32()?.max(0) as usize)
+    }
+
+    /// Strings are Java's modified UTF-8, which only differs from UTF-8 for
+    /// NUL and characters outside the BMP, so those are decoded lossily
+    fn string(&mut self) -> Result<String> {
+        let len = u16::from_be_bytes(self.array()?) as usize;
+        Ok(String::from_utf8_lossy(self.take(len)?).into_owned())
+    }
+
+    fn payload(&mut self, id: u8, depth: usize) -> Result<Tag> {
+        if depth > MAX_DEPTH {
+            bail!("NBT data is nested too deeply");
+        }
+        Ok(match id {
+            1 => Tag::Byte(self.u8()? as i8),
+            2 => Tag::Short(i16::from_be_bytes(self.array()?)),
+            3 => Tag::Int(self.i32()?),
+            4 => Tag::Long(self.i64()?),
+            5 => Tag::Float(f32::from_be_bytes(self.array()?)),
+            6 => Tag::Double(f64::from_be_bytes(self.array()?)),
+            7 => {
+                let len = self.len()?;
+                Tag::ByteArray(self.take(len)?.iter().map(|b| *b as i8).collect())
+            }
+            8 => Tag::String(self.string()?),
+            9 => {
+                let item_id = self.u8()?;
+                let len = self.len()?;
+                let mut items = Vec::new();
+                for _ in 0..len {
+                    items.push(self.payload(item_id, depth + 1)?);
+                }
+                Tag::List(items)
+            }
+            10 => {
+                let mut children = HashMap::new();
+                loop {
+                    let child_id = self.u8()?;
+                    if child_id == 0 {
+                        break;
+                    }
+                    let name = self.string()?;
+                    children.insert(name, self.payload(child_id, depth + 1)?);
+                }
+                Tag::Compound(children)
+            }
+            11 => {
+                let len = self.len()?;
+                Tag::IntArray((0..len).map(|_| self.i32()).collect::<Result<_>>()?)
+            }
+            12 => {
+                let len = self.len()?;
+                Tag::LongArray((0..len).map(|_| self.i64()).collect::<Result<_>>()?)
+            }
+            id => bail!("Unknown NBT tag type {} at byte {}", id, self.pos),
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    /// Writes a root compound the way Minecraft does, for building test files
+    pub(crate) fn encode(root: &Tag) -> Vec<u8> {
+        let mut bytes = vec![10, 0, 0];
+        payload(root, &mut bytes);
+        bytes
+    }
+
+    fn id(tag: &Tag) -> u8 {
+        match tag {
+            Tag::Byte(_) => 1,
+            Tag::Short(_) => 2,
+            Tag::Int(_) => 3,
+            Tag::Long(_) => 4,
+            Tag::Float(_) => 5,
+            Tag::Double(_) => 6,
+            Tag::ByteArray(_) => 7,
+            Tag::String(_) => 8,
+            Tag::List(_) => 9,
+            Tag::Compound(_) => 10,
+            Tag::IntArray(_) => 11,
+            Tag::LongArray(_) => 12,
+        }
+    }
+
+    fn string(value: &str, bytes: &mut Vec<u8>) {
+        bytes.extend((value.len() as u16).to_be_bytes());
+        bytes.extend(value.as_bytes());
+    }
+
+    fn payload(tag: &Tag, bytes: &mut Vec<u8>) {
+        match tag {
+            Tag::Byte(value) => bytes.push(*value as u8),
+            Tag::Short(value) => bytes.extend(value.to_be_bytes()),
+            Tag::Int(value) => bytes.extend(value.to_be_bytes()),
+            Tag::Long(value) => bytes.extend(value.to_be_bytes()),
+            Tag::Float(value) => bytes.extend(value.to_be_bytes()),
+            Tag::Double(value) => bytes.extend(value.to_be_bytes()),
+            Tag::ByteArray(values) => {
+                bytes.extend((values.len() as i32).to_be_bytes());
+                bytes.extend(values.iter().map(|value| *value as u8));
+            }
+            Tag::String(value) => string(value, bytes),
+            Tag::List(items) => {
+                bytes.push(items.first().map_or(0, id));
+                bytes.extend((items.len() as i32).to_be_bytes());
+                for item in items {
+                    payload(item, bytes);
+                }
+            }
+            Tag::Compound(children) => {
+                for (name, child) in children {
+                    bytes.push(id(child));
+                    string(name, bytes);
+                    payload(child, bytes);
+                }
+                bytes.push(0);
+            }
+            Tag::IntArray(values) => {
+                bytes.extend((values.len() as i32).to_be_bytes());
+                for value in values {
+                    bytes.extend(value.to_be_bytes());
+                }
+            }
+            Tag::LongArray(values) => {
+                bytes.extend((values.len() as i32).to_be_bytes());
+                for value in values {
+                    bytes.extend(value.to_be_bytes());
+                }
+            }
+        }
+    }
+
+    pub(crate) fn compound<const N: usize>(children: [(&str, Tag); N]) -> Tag {
+        Tag::Compound(
+            children
+                .into_iter()
+                .map(|(name, child)| (name.to_string(), child))
+                .collect(),
+        )
+    }
+
+    fn every_tag() -> Tag {
+        compound([
+            ("byte", Tag::Byte(-1)),
+            ("short", Tag::Short(-300)),
+            ("int", Tag::Int(70_000)),
+            ("long", Tag::Long(-5_000_000_000)),
+            ("float", Tag::Float(1.5)),
+            ("double", Tag::Double(-0.25)),
+            ("bytes", Tag::ByteArray(vec![-128, 0, 127])),
+            ("string", Tag::String("Déjà vu".to_string())),
+            ("list", Tag::List(vec![Tag::Int(1), Tag::Int(2)])),
+            ("empty", Tag::List(vec![])),
+            ("compound", compound([("nested", Tag::Byte(1))])),
+            ("ints", Tag::IntArray(vec![i32::MIN, i32::MAX])),
+            ("longs", Tag::LongArray(vec![i64::MIN, 0, i64::MAX])),
+        ])
+    }
+
+    #[test]
+    fn parses_every_tag_type() {
+        let root = every_tag();
+        assert_eq!(parse(&encode(&root)).unwrap(), root);
+    }
+
+    #[test]
+    fn parses_gzipped_data() {
+        let root = every_tag();
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&encode(&root)).unwrap();
+        let gzipped = encoder.finish().unwrap();
+        assert!(gzipped.starts_with(&[0x1f, 0x8b]));
+        assert_eq!(parse(&gzipped).unwrap(), root);
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let bytes = encode(&every_tag());
+        for len in 0..bytes.len() {
+            assert!(parse(&bytes[..len]).is_err(), "{} bytes", len);
+        }
+        // Lengths longer than the data
+        let huge = [10, 0, 0, 11, 0, 1, b'a', 0x7f, 0xff, 0xff, 0xff, 0, 0, 0, 1];
+        assert!(parse(&huge).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_tags() {
+        assert!(parse(&[10, 0, 0, 13, 0, 1, b'a', 0, 0]).is_err());
+    }
+
+    #[test]
+    fn limits_nesting() {
+        let nested = |depth: usize| {
+            // Lists of lists, with the innermost one empty
+            let mut bytes = vec![9, 0, 0];
+            for _ in 0..depth {
+                bytes.extend([9, 0, 0, 0, 1]);
+            }
+            bytes.extend([0, 0, 0, 0, 0]);
+            bytes
+        };
+        assert!(parse(&nested(MAX_DEPTH)).is_ok());
+        assert!(parse(&nested(MAX_DEPTH + 1)).is_err());
+    }
+
+    #[test]
+    fn reads_numbers() {
+        assert_eq!(Tag::Byte(1).as_i64(), Some(1));
+        assert_eq!(Tag::Long(-2).as_i64(), Some(-2));
+        assert_eq!(Tag::Double(1.0).as_i64(), None);
+        assert_eq!(Tag::String("1".to_string()).as_i64(), None);
+    }
+}
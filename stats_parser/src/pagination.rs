@@ -1,63 +1,368 @@
 use poise::serenity_prelude as serenity;
+use stats_parser::i18n::{self, Language};
+use std::time::Duration;
 
-pub async fn paginate<U, E>(
-    ctx: poise::Context<'_, U, E>,
-    embeds: Vec<serenity::CreateEmbed>,
-) -> Result<(), serenity::Error> {
-    // Define some unique identifiers for the navigation buttons
-    let ctx_id = ctx.id();
-    let prev_button_id = format!("{}prev", ctx_id);
-    let next_button_id = format!("{}next", ctx_id);
-
-    // Send the embed with the first page as content
-    let reply = {
-        let components = serenity::CreateActionRow::Buttons(vec![
-            serenity::CreateButton::new(&prev_button_id).emoji('◀'),
-            serenity::CreateButton::new(&next_button_id).emoji('▶'),
-        ]);
-
-        poise::CreateReply::default()
-            .embed(embeds[0].clone())
-            .components(vec![components])
-    };
-
-    ctx.send(reply).await?;
-
-    // Loop through incoming interactions with the navigation buttons
-    let mut current_page = 0;
-    while let Some(press) = serenity::collector::ComponentInteractionCollector::new(ctx)
-        // We defined our button IDs to start with `ctx_id`. If they don't, some other command's
-        // button was pressed
-        .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
-        // Timeout when no navigation button has been pressed for 24 hours
-        .timeout(std::time::Duration::from_secs(3600 * 24))
-        .await
-    {
-        // Depending on which button was pressed, go to next or previous page
-        if press.data.custom_id == next_button_id {
-            current_page += 1;
-            if current_page >= embeds.len() {
-                current_page = 0;
+/// How long the controls keep working after the last interaction
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+/// How long the "go to page" dialog waits for an answer. The other controls
+/// don't work meanwhile, so this is much shorter than the timeout.
+const MODAL_TIMEOUT: Duration = Duration::from_secs(60);
+/// Discord shows at most 25 options in a select menu
+const MAX_SELECT_OPTIONS: usize = 25;
+
+/// One ordering of the pages of a message, e.g. sorted by value
+#[derive(Clone)]
+pub struct PageSet {
+    pub label: String,
+    pub pages: Vec<serenity::CreateEmbed>,
+    /// Title and first page of each section, listed in the select menu
+    pub sections: Vec<(String, usize)>,
+    /// Language of the pages, which the controls are shown in too
+    pub language: Language,
+}
+
+impl PageSet {
+    /// Name of the sort button that shows this ordering
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = label.into();
+        self
+    }
+
+    /// Index of the section the page belongs to
+    fn section_of(&self, page: usize) -> Option<usize> {
+        self.sections
+            .iter()
+            .take(MAX_SELECT_OPTIONS)
+            .rposition(|(_, first)| *first <= page)
+    }
+}
+
+/// Sends pages with buttons to flip through them, a "go to page" dialog and,
+/// when there are several sections, a menu to jump to one. Further orderings
+/// added with [`Paginator::order`] get buttons to switch between them. Only
+/// the person who ran the command can use the controls, and they are disabled
+/// after the timeout.
+pub struct Paginator {
+    sets: Vec<PageSet>,
+    attachments: Vec<serenity::CreateAttachment>,
+    timeout: Duration,
+}
+
+impl Paginator {
+    pub fn new(pages: PageSet) -> Self {
+        Paginator {
+            sets: vec![pages],
+            attachments: vec![],
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Adds another ordering of the same pages
+    pub fn order(mut self, pages: PageSet) -> Self {
+        self.sets.push(pages);
+        self
+    }
+
+    /// Files the pages can show with `attachment://<filename>`. They stay on
+    /// the message while paging.
+    pub fn attachments(mut self, attachments: Vec<serenity::CreateAttachment>) -> Self {
+        self.attachments = attachments;
+        self
+    }
+
+    /// How long the controls keep working after the last interaction
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Translates a message into the language of the pages
+    fn tr(&self, key: &str) -> String {
+        self.sets[0].language.scope(|| i18n::tr(key))
+    }
+
+    fn components(
+        &self,
+        ids: &ComponentIds,
+        set: usize,
+        page: usize,
+        disabled: bool,
+    ) -> Vec<serenity::CreateActionRow> {
+        let pages = &self.sets[set];
+        let count = pages.pages.len();
+        let mut rows = vec![];
+        if count > 1 {
+            rows.push(serenity::CreateActionRow::Buttons(vec![
+                serenity::CreateButton::new(&ids.first)
+                    .emoji('⏮')
+                    .disabled(disabled || page == 0),
+                serenity::CreateButton::new(&ids.prev)
+                    .emoji('◀')
+                    .disabled(disabled),
+                serenity::CreateButton::new(&ids.jump)
+                    .label(format!("{}/{}", page + 1, count))
+                    .style(serenity::ButtonStyle::Secondary)
+                    .disabled(disabled),
+                serenity::CreateButton::new(&ids.next)
+                    .emoji('▶')
+                    .disabled(disabled),
+                serenity::CreateButton::new(&ids.last)
+                    .emoji('⏭')
+                    .disabled(disabled || page + 1 == count),
+            ]));
+        }
+        if pages.sections.len() > 1 && count > 1 {
+            let current = pages.section_of(page);
+            let options = pages
+                .sections
+                .iter()
+                .take(MAX_SELECT_OPTIONS)
+                .enumerate()
+                .map(|(i, (title, _))| {
+                    let title = title.chars().take(100).collect::<String>();
+                    serenity::CreateSelectMenuOption::new(title, i.to_string())
+                        .default_selection(current == Some(i))
+                })
+                .collect();
+            rows.push(serenity::CreateActionRow::SelectMenu(
+                serenity::CreateSelectMenu::new(
+                    &ids.section,
+                    serenity::CreateSelectMenuKind::String { options },
+                )
+                .placeholder(self.tr("pagination.jump_to_section"))
+                .disabled(disabled),
+            ));
+        }
+        if self.sets.len() > 1 {
+            let buttons = self
+                .sets
+                .iter()
+                .enumerate()
+                .take(5)
+                .map(|(i, pages)| {
+                    serenity::CreateButton::new(format!("{}{}", ids.sort, i))
+                        .label(&pages.label)
+                        .style(if i == set {
+                            serenity::ButtonStyle::Primary
+                        } else {
+                            serenity::ButtonStyle::Secondary
+                        })
+                        .disabled(disabled || i == set)
+                })
+                .collect();
+            rows.push(serenity::CreateActionRow::Buttons(buttons));
+        }
+        rows
+    }
+
+    /// Sends the first page and handles the controls until the timeout
+    pub async fn run<U, E>(self, ctx: poise::Context<'_, U, E>) -> Result<(), serenity::Error> {
+        // Define some unique identifiers for the controls
+        let ctx_id = ctx.id();
+        let ids = ComponentIds::new(ctx_id);
+        let (mut set, mut page) = (0, 0);
+
+        let reply = poise::CreateReply::default()
+            .embed(self.sets[set].pages[page].clone())
+            .components(self.components(&ids, set, page, false));
+        let reply = self
+            .attachments
+            .iter()
+            .cloned()
+            .fold(reply, |reply, attachment| reply.attachment(attachment));
+        let handle = ctx.send(reply).await?;
+        if self.sets.len() == 1 && self.sets[0].pages.len() == 1 {
+            return Ok(());
+        }
+
+        // Loop through incoming interactions with the controls
+        while let Some(press) = serenity::collector::ComponentInteractionCollector::new(ctx)
+            // Our IDs start with `ctx_id`. If they don't, some other command's control was used
+            .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+            .timeout(self.timeout)
+            .await
+        {
+            if press.user.id != ctx.author().id {
+                press
+                    .create_response(
+                        ctx.serenity_context(),
+                        serenity::CreateInteractionResponse::Message(
+                            serenity::CreateInteractionResponseMessage::new()
+                                .content(self.tr("pagination.only_author"))
+                                .ephemeral(true),
+                        ),
+                    )
+                    .await?;
+                continue;
+            }
+
+            let count = self.sets[set].pages.len();
+            let id = press.data.custom_id.as_str();
+            if id == ids.first {
+                page = 0;
+            } else if id == ids.prev {
+                page = page.checked_sub(1).unwrap_or(count - 1);
+            } else if id == ids.next {
+                page = (page + 1) % count;
+            } else if id == ids.last {
+                page = count - 1;
+            } else if id == ids.section {
+                if let serenity::ComponentInteractionDataKind::StringSelect { values } =
+                    &press.data.kind
+                    && let Some(section) = values.first().and_then(|value| value.parse().ok())
+                    && let Some((_, first)) = self.sets[set].sections.get::<usize>(section)
+                {
+                    page = *first;
+                }
+            } else if let Some(index) = id.strip_prefix(&ids.sort) {
+                let Some(index) = index.parse::<usize>().ok().filter(|i| *i < self.sets.len())
+                else {
+                    continue;
+                };
+                // Stay in the same section of the new ordering
+                page = match self.sets[set].section_of(page) {
+                    Some(section) => self.sets[index]
+                        .sections
+                        .get(section)
+                        .map_or(0, |(_, first)| *first),
+                    None => page.min(self.sets[index].pages.len() - 1),
+                };
+                set = index;
+            } else if id == ids.jump {
+                let Some((modal, target)) = self.ask_page(ctx, &press, &ids, count).await? else {
+                    continue;
+                };
+                page = target;
+                modal
+                    .create_response(ctx.serenity_context(), self.update(&ids, set, page))
+                    .await?;
+                continue;
+            } else {
+                // This is an unrelated interaction
+                continue;
             }
-        } else if press.data.custom_id == prev_button_id {
-            current_page = current_page.checked_sub(1).unwrap_or(embeds.len() - 1);
-        } else {
-            // This is an unrelated button interaction
-            continue;
+
+            // Update the message with the new page contents
+            press
+                .create_response(ctx.serenity_context(), self.update(&ids, set, page))
+                .await?;
         }
 
-        // Update the message with the new page contents
+        // Leave the current page up, but without working controls
+        let mut message = handle.message().await?.into_owned();
+        message
+            .edit(
+                ctx.serenity_context(),
+                serenity::EditMessage::new().components(self.components(&ids, set, page, true)),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    fn update(
+        &self,
+        ids: &ComponentIds,
+        set: usize,
+        page: usize,
+    ) -> serenity::CreateInteractionResponse {
+        serenity::CreateInteractionResponse::UpdateMessage(
+            serenity::CreateInteractionResponseMessage::new()
+                .embed(self.sets[set].pages[page].clone())
+                .components(self.components(ids, set, page, false)),
+        )
+    }
+
+    /// Opens the "go to page" dialog. Returns the submitted dialog, which still
+    /// has to be answered, and the chosen page, or `None` if it was closed
+    /// without an answer within [`MODAL_TIMEOUT`].
+    async fn ask_page<U, E>(
+        &self,
+        ctx: poise::Context<'_, U, E>,
+        press: &serenity::ComponentInteraction,
+        ids: &ComponentIds,
+        count: usize,
+    ) -> Result<Option<(serenity::ModalInteraction, usize)>, serenity::Error> {
+        let input = serenity::CreateInputText::new(
+            serenity::InputTextStyle::Short,
+            self.tr("pagination.page"),
+            "page",
+        )
+        .placeholder(format!("1-{}", count))
+        .required(true);
         press
             .create_response(
                 ctx.serenity_context(),
-                serenity::CreateInteractionResponse::UpdateMessage(
-                    serenity::CreateInteractionResponseMessage::new()
-                        .embed(embeds[current_page].clone()),
+                serenity::CreateInteractionResponse::Modal(
+                    serenity::CreateModal::new(&ids.modal, self.tr("pagination.go_to_page"))
+                        .components(vec![serenity::CreateActionRow::InputText(input)]),
                 ),
             )
             .await?;
+
+        let modal_id = ids.modal.clone();
+        let Some(modal) = serenity::collector::ModalInteractionCollector::new(ctx)
+            .filter(move |modal| modal.data.custom_id == modal_id)
+            .timeout(MODAL_TIMEOUT.min(self.timeout))
+            .await
+        else {
+            return Ok(None);
+        };
+        let input = modal
+            .data
+            .components
+            .iter()
+            .flat_map(|row| &row.components)
+            .find_map(|component| match component {
+                serenity::ActionRowComponent::InputText(input) => input.value.clone(),
+                _ => None,
+            })
+            .unwrap_or_default();
+        match input.trim().parse::<usize>() {
+            Ok(page) if (1..=count).contains(&page) => Ok(Some((modal, page - 1))),
+            _ => {
+                modal
+                    .create_response(
+                        ctx.serenity_context(),
+                        serenity::CreateInteractionResponse::Message(
+                            serenity::CreateInteractionResponseMessage::new()
+                                .content(self.sets[0].language.scope(|| {
+                                    i18n::tr_with("pagination.pick_page", &[("count", &count)])
+                                }))
+                                .ephemeral(true),
+                        ),
+                    )
+                    .await?;
+                Ok(None)
+            }
+        }
     }
+}
 
-    Ok(())
+/// Custom IDs of the controls, all starting with the invocation's ID
+struct ComponentIds {
+    first: String,
+    prev: String,
+    jump: String,
+    next: String,
+    last: String,
+    section: String,
+    sort: String,
+    modal: String,
 }
 
+impl ComponentIds {
+    fn new(ctx_id: u64) -> Self {
+        let id = |name: &str| format!("{}{}", ctx_id, name);
+        ComponentIds {
+            first: id("first"),
+            prev: id("prev"),
+            jump: id("jump"),
+            next: id("next"),
+            last: id("last"),
+            section: id("section"),
+            sort: id("sort"),
+            modal: id("modal"),
+        }
+    }
+}
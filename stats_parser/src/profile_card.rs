@@ -0,0 +1,235 @@
+use crate::charts::{FONT, palette_color, render};
+use crate::i18n::tr;
+use crate::metrics::DerivedMetric;
+use crate::totals::TotalStat;
+use crate::{MinecraftStats, fmt_time, readable_stat_name};
+use anyhow::Result;
+use image::RgbaImage;
+use plotters::prelude::*;
+use plotters::style::FontStyle;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Size of the card in pixels
+pub const CARD_SIZE: (u32, u32) = (800, 300);
+
+/// Each pixel of the 8x8 face is drawn as a square of this size
+const HEAD_PIXEL_SIZE: i32 = 20;
+
+/// Only ranks up to this place get a badge
+const MAX_BADGE_RANK: usize = 3;
+
+const BACKGROUND: RGBColor = RGBColor(0x2b, 0x2d, 0x31);
+const TEXT: RGBColor = RGBColor(0xf2, 0xf3, 0xf5);
+const MUTED_TEXT: RGBColor = RGBColor(0xb5, 0xba, 0xc1);
+
+/// Steve's face, used when no skin is cached for a player
+const DEFAULT_FACE: [&str; 8] = [
+    "HHHHHHHH", "HHHHHHHH", "HSSSSSSH", "SSSSSSSS", "SWBSSBWS", "SSSNNSSS", "SSMSSMSS", "SSMMMMSS",
+];
+
+fn default_face_color(pixel: char) -> RGBColor {
+    match pixel {
+        'H' => RGBColor(0x2f, 0x20, 0x0d),
+        'W' => RGBColor(0xff, 0xff, 0xff),
+        'B' => RGBColor(0x52, 0x3d, 0x89),
+        'N' => RGBColor(0x94, 0x5b, 0x43),
+        'M' => RGBColor(0x6a, 0x40, 0x30),
+        _ => RGBColor(0xb4, 0x84, 0x6d),
+    }
+}
+
+/// Everything shown on a player's profile card
+#[derive(Debug, Clone)]
+pub struct ProfileCard {
+    pub name: String,
+    pub playtime: String,
+    pub kill_death_ratio: String,
+    pub top_block: Option<String>,
+    pub top_mob: Option<String>,
+    /// Server ranks, e.g. "#1 Playtime"
+    pub badges: Vec<String>,
+    /// The 8x8 face of the player's skin, with the hat layer applied
+    pub face: Option<RgbaImage>,
+}
+
+impl ProfileCard {
+    /// Builds the card for a player. The skin is read from `<skins_dir>/<uuid>.png`
+    /// if it is cached, otherwise the default face is drawn.
+    pub fn new(
+        uuid: &str,
+        name: &str,
+        players: &[(String, Arc<MinecraftStats>)],
+        skins_dir: &Path,
+    ) -> Option<Self> {
+        let (_, stats) = players.iter().find(|(id, _)| id == uuid)?;
+        let stats = &stats.stats;
+        let top = |category: &str| {
+            stats
+                .category(category)?
+                .iter()
+                .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+                .map(|(key, value)| format!("{} ({})", readable_stat_name(category, key), value))
+        };
+        let kill_death_ratio = DerivedMetric::KillDeathRatio
+            .compute(stats)
+            .map(|value| DerivedMetric::KillDeathRatio.format(value))
+            .unwrap_or_else(|| tr("not_available"));
+        Some(ProfileCard {
+            name: name.to_string(),
+            playtime: fmt_time(TotalStat::Playtime.value(stats)),
+            kill_death_ratio,
+            top_block: top("minecraft:mined"),
+            top_mob: top("minecraft:killed"),
+            badges: rank_badges(uuid, players),
+            face: load_face(&skins_dir.join(format!("{}.png", uuid))),
+        })
+    }
+
+    /// Renders the card as a PNG
+    pub fn render(&self) -> Result<Vec<u8>> {
+        render(CARD_SIZE, |root| {
+            root.fill(&BACKGROUND)?;
+            self.draw_face(root, (40, 40))?;
+
+            let x = 40 + 8 * HEAD_PIXEL_SIZE + 40;
+            // `FontDesc::style` keeps the regular font, so the style is given up front
+            let title_style = (FONT, 40.0, FontStyle::Bold).into_font().color(&TEXT);
+            root.draw_text(&self.name, &title_style, (x, 36))?;
+
+            let label_style = (FONT, 20).into_font().color(&MUTED_TEXT);
+            let value_style = (FONT, 20).into_font().color(&TEXT);
+            let rows = [
+                (tr("card.playtime"), Some(&self.playtime)),
+                (tr("card.kill_death_ratio"), Some(&self.kill_death_ratio)),
+                (tr("card.top_block"), self.top_block.as_ref()),
+                (tr("card.top_mob"), self.top_mob.as_ref()),
+            ];
+            for (i, (label, value)) in rows.iter().enumerate() {
+                let y = 96 + i as i32 * 30;
+                root.draw_text(label, &label_style, (x, y))?;
+                let value = value.cloned().unwrap_or_else(|| tr("not_available"));
+                root.draw_text(&value, &value_style, (x + 120, y))?;
+            }
+
+            // Badges are translated, so they can't use the bold font
+            let badge_style = (FONT, 16).into_font().color(&WHITE);
+            let mut badge_x = 40;
+            for (i, badge) in self.badges.iter().enumerate() {
+                let (width, _) = root.estimate_text_size(badge, &badge_style)?;
+                let width = width as i32 + 20;
+                if badge_x + width > CARD_SIZE.0 as i32 - 40 {
+                    break;
+                }
+                root.draw(&Rectangle::new(
+                    [(badge_x, 240), (badge_x + width, 270)],
+                    palette_color(i).filled(),
+                ))?;
+                root.draw_text(badge, &badge_style, (badge_x + 10, 246))?;
+                badge_x += width + 10;
+            }
+            Ok(())
+        })
+    }
+
+    fn draw_face(
+        &self,
+        root: &DrawingArea<BitMapBackend, plotters::coord::Shift>,
+        (x, y): (i32, i32),
+    ) -> Result<()> {
+        for row in 0..8 {
+            for column in 0..8 {
+                let color = match &self.face {
+                    Some(face) => {
+                        let [r, g, b, _] = face.get_pixel(column, row).0;
+                        RGBColor(r, g, b)
+                    }
+                    None => default_face_color(
+                        DEFAULT_FACE[row as usize]
+                            .chars()
+                            .nth(column as usize)
+                            .unwrap_or('S'),
+                    ),
+                };
+                let left = x + column as i32 * HEAD_PIXEL_SIZE;
+                let top = y + row as i32 * HEAD_PIXEL_SIZE;
+                root.draw(&Rectangle::new(
+                    [(left, top), (left + HEAD_PIXEL_SIZE, top + HEAD_PIXEL_SIZE)],
+                    color.filled(),
+                ))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Cuts the face out of a skin texture and puts the hat layer on top.
+/// Returns `None` if the file is missing or isn't a skin.
+fn load_face(path: &Path) -> Option<RgbaImage> {
+    let skin = image::open(path).ok()?.to_rgba8();
+    if skin.width() < 64 || skin.height() < 32 {
+        return None;
+    }
+    let mut face = RgbaImage::new(8, 8);
+    for (x, y, pixel) in face.enumerate_pixels_mut() {
+        let base = skin.get_pixel(8 + x, 8 + y);
+        let hat = skin.get_pixel(40 + x, 8 + y);
+        *pixel = if hat.0[3] > 0 { *hat } else { *base };
+    }
+    Some(face)
+}
+
+/// Finds the headline stats where a player places in the top few of the server
+pub fn rank_badges(uuid: &str, players: &[(String, Arc<MinecraftStats>)]) -> Vec<String> {
+    TotalStat::ALL
+        .iter()
+        .filter_map(|stat| {
+            let value = stat.value(&players.iter().find(|(id, _)| id == uuid)?.1.stats);
+            if value == 0 {
+                return None;
+            }
+            let rank = 1 + players
+                .iter()
+                .filter(|(_, other)| stat.value(&other.stats) > value)
+                .count();
+            (rank <= MAX_BADGE_RANK).then(|| format!("#{} {}", rank, stat.get_name()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::charts::tests::assert_golden;
+    use image::Rgba;
+
+    fn card(face: Option<RgbaImage>) -> ProfileCard {
+        ProfileCard {
+            name: "Notch".to_string(),
+            playtime: "12h 30m".to_string(),
+            kill_death_ratio: "2.50".to_string(),
+            top_block: Some("Stone (1200)".to_string()),
+            top_mob: None,
+            badges: vec!["#1 Playtime".to_string(), "#3 Mob Kills".to_string()],
+            face,
+        }
+    }
+
+    #[test]
+    fn profile_card_matches_golden() {
+        assert_golden("profile_card", &card(None).render().unwrap());
+    }
+
+    #[test]
+    fn profile_card_with_skin_matches_golden() {
+        // A checkerboard face, so every pixel of it is visible in the image
+        let face = RgbaImage::from_fn(8, 8, |x, y| {
+            if (x + y) % 2 == 0 {
+                Rgba([0xd0, 0x40, 0x40, 0xff])
+            } else {
+                Rgba([0x40, 0x40, 0xd0, 0xff])
+            }
+        });
+        assert_golden("profile_card_skin", &card(Some(face)).render().unwrap());
+    }
+}
@@ -0,0 +1,51 @@
+use crate::embeds::report_pages;
+use anyhow::Result;
+use poise::serenity_prelude as serenity;
+use stats_parser::report::property_changes_report;
+use stats_parser::server_properties::ServerProperties;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Checks `server.properties` for changes and posts them to `channel`. The
+/// last properties seen are saved to `snapshot_path`, so changes made while the
+/// bot was offline are posted when it starts.
+pub async fn run_properties_watch(
+    http: Arc<serenity::Http>,
+    channel: serenity::ChannelId,
+    properties_path: PathBuf,
+    snapshot_path: PathBuf,
+) {
+    loop {
+        if let Err(e) = check_properties(&http, channel, &properties_path, &snapshot_path).await {
+            tracing::warn!("Could not check {}: {}", properties_path.display(), e);
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+async fn check_properties(
+    http: &serenity::Http,
+    channel: serenity::ChannelId,
+    properties_path: &Path,
+    snapshot_path: &Path,
+) -> Result<()> {
+    let current = ServerProperties::load(properties_path)?;
+    // The first properties seen are what later ones are compared against
+    let Some(previous) = ServerProperties::load_snapshot(snapshot_path)? else {
+        return current.save_snapshot(snapshot_path);
+    };
+    let changes = previous.changes(&current);
+    if changes.is_empty() {
+        return Ok(());
+    }
+    let pages = report_pages(&property_changes_report(&changes), serenity::Colour::ORANGE);
+    for page in pages.pages {
+        channel
+            .send_message(http, serenity::CreateMessage::new().embed(page))
+            .await?;
+    }
+    current.save_snapshot(snapshot_path)
+}
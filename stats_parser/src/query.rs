@@ -2,6 +2,8 @@
 use anyhow::{Result, anyhow};
 use bytes::{Buf, BufMut, Bytes, BytesMut};
 use rand::random;
+use stats_parser::i18n::{tr, tr_with};
+use stats_parser::report::{ReportRow, ReportSection, ReportValue, SectionStyle, StatsReport};
 use std::collections::HashMap;
 use std::time::Duration;
 use tokio::io;
@@ -53,6 +55,35 @@ pub struct FullStatResponse {
     pub players: Vec<String>,
 }
 
+/// Version, player count and online players of a queried server
+pub fn server_report(response: &FullStatResponse) -> StatsReport {
+    let players = response.players.iter().map(ReportRow::item).collect();
+    StatsReport::new(tr("server.title"))
+        .description(&response.motd)
+        .section(ReportSection::new(
+            tr("server.section"),
+            SectionStyle::Block,
+            vec![
+                ReportRow::new(tr("server.version"), ReportValue::text(&response.version)),
+                ReportRow::new(
+                    tr("server.players"),
+                    ReportValue::text(tr_with(
+                        "server.online",
+                        &[
+                            ("online", &response.num_players),
+                            ("max", &response.max_players),
+                        ],
+                    )),
+                ),
+            ],
+        ))
+        .section(ReportSection::new(
+            tr("server.players"),
+            SectionStyle::Block,
+            players,
+        ))
+}
+
 pub async fn stat_send(sock: &UdpSocket, bytes: &[u8]) -> io::Result<Bytes> {
     sock.send(bytes).await?;
     Box::pin(timeout(Duration::from_millis(250), recv_packet(sock))).await?
@@ -0,0 +1,111 @@
+use anyhow::{Result, bail};
+use bytes::{Buf, BufMut, BytesMut};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+const LOGIN: i32 = 3;
+const COMMAND: i32 = 2;
+const RESPONSE: i32 = 0;
+/// Sent after a command so the end of a response split over several packets can be found
+const END_MARKER: i32 = 100;
+/// Payloads the server sends are never longer than this
+const MAX_PACKET_SIZE: usize = 4096 + 10;
+const RCON_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Address and password of the server's RCON, which runs commands as the
+/// console. Needs `enable-rcon=true` in `server.properties`.
+#[derive(Debug, Clone)]
+pub struct RconConfig {
+    pub addr: String,
+    pub password: String,
+}
+
+impl RconConfig {
+    /// Reads `RCON_ADDR`, e.g. `127.0.0.1:25575`, and `RCON_PASSWORD`.
+    /// RCON is not available if either is unset.
+    pub fn from_env() -> Option<Self> {
+        Some(RconConfig {
+            addr: std::env::var("RCON_ADDR").ok()?,
+            password: std::env::var("RCON_PASSWORD").ok()?,
+        })
+    }
+
+    /// Logs in and runs one command, returning what the server replied
+    pub async fn run(&self, command: &str) -> Result<String> {
+        timeout(RCON_TIMEOUT, async {
+            let mut client = RconClient::connect(&self.addr, &self.password).await?;
+            client.command(command).await
+        })
+        .await?
+    }
+}
+
+pub struct RconClient {
+    stream: TcpStream,
+    next_id: i32,
+}
+
+impl RconClient {
+    pub async fn connect(addr: &str, password: &str) -> Result<Self> {
+        let mut client = RconClient {
+            stream: TcpStream::connect(addr).await?,
+            next_id: 1,
+        };
+        let id = client.send(LOGIN, password).await?;
+        // A wrong password is answered with an id of -1
+        let (response_id, _, _) = client.receive().await?;
+        if response_id != id {
+            bail!("RCON login failed, check RCON_PASSWORD");
+        }
+        Ok(client)
+    }
+
+    /// Runs a command, e.g. `whitelist add Steve`
+    pub async fn command(&mut self, command: &str) -> Result<String> {
+        let id = self.send(COMMAND, command).await?;
+        // Servers answer packets in order, so once the invalid packet is
+        // answered every part of the response has arrived
+        let end_id = self.send(END_MARKER, "").await?;
+        let mut response = String::new();
+        loop {
+            let (response_id, kind, payload) = self.receive().await?;
+            if response_id == end_id {
+                break;
+            }
+            if response_id == id && kind == RESPONSE {
+                response.push_str(&payload);
+            }
+        }
+        Ok(response)
+    }
+
+    async fn send(&mut self, kind: i32, payload: &str) -> Result<i32> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut packet = BytesMut::with_capacity(14 + payload.len());
+        // Length of what follows: id, type, payload and two NULs
+        packet.put_i32_le(10 + payload.len() as i32);
+        packet.put_i32_le(id);
+        packet.put_i32_le(kind);
+        packet.put_slice(payload.as_bytes());
+        packet.put_u16(0);
+        self.stream.write_all(&packet).await?;
+        Ok(id)
+    }
+
+    /// Reads a packet, returning its id, type and payload
+    async fn receive(&mut self) -> Result<(i32, i32, String)> {
+        let len = self.stream.read_i32_le().await?;
+        if !(10..=MAX_PACKET_SIZE as i32).contains(&len) {
+            bail!("Invalid RCON packet length {}", len);
+        }
+        let mut packet = BytesMut::zeroed(len as usize);
+        self.stream.read_exact(&mut packet).await?;
+        let id = packet.get_i32_le();
+        let kind = packet.get_i32_le();
+        let payload = String::from_utf8_lossy(&packet[..packet.len() - 2]).into_owned();
+        Ok((id, kind, payload))
+    }
+}
@@ -0,0 +1,1570 @@
+use crate::access_lists::{AccessLists, BanDetails, has_joined};
+use crate::deaths::DeathCause;
+use crate::i18n::{tr, tr_with};
+use crate::leaderboard::{LeaderboardTarget, leaderboard};
+use crate::metrics::{DerivedMetric, playtime_hours};
+use crate::scoreboard::{Objective, Scoreboard, Team};
+use crate::server_properties::{PropertyChange, ServerProperties};
+use crate::stat_key::CustomStat;
+use crate::store::StatsStore;
+use crate::superlatives::{player_highlights, server_superlatives};
+use crate::totals::{TotalStat, category_totals, server_totals};
+use crate::{
+    MinecraftStats, STAT_CATEGORIES, StatGroup, fmt_distance, fmt_time, readable_stat_name,
+};
+use anyhow::Context;
+use colored::Colorize;
+use poise::ChoiceParameter;
+use regex::{Regex, RegexBuilder};
+use serde_json::{Value, json};
+use std::fmt;
+use std::str::FromStr;
+
+/// Unit of a number in a report, which decides how it is formatted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Count,
+    /// Game ticks, shown as `HH:MM:SS`
+    Ticks,
+    /// Centimetres, shown in km
+    Centimetres,
+    Ratio,
+    PerHour,
+    CentimetresPerHour,
+}
+
+impl Unit {
+    /// Gets the unit of a raw stat, e.g. `minecraft:walk_one_cm` is in centimetres
+    pub fn of_stat(category: &str, key: &str) -> Unit {
+        if category != "minecraft:custom" {
+            Unit::Count
+        } else if key.ends_with("_one_cm") {
+            Unit::Centimetres
+        } else if key.ends_with("_time") || key.starts_with("minecraft:time_since") {
+            Unit::Ticks
+        } else {
+            Unit::Count
+        }
+    }
+
+    pub fn format(&self, value: f64) -> String {
+        let whole = value.max(0.0) as u64;
+        match self {
+            Unit::Count => format!("{}", value.round() as i64),
+            Unit::Ticks => fmt_time(whole),
+            Unit::Centimetres => fmt_distance(whole),
+            Unit::Ratio => format!("{:.2}", value),
+            Unit::PerHour => format!("{:.2}/h", value),
+            Unit::CentimetresPerHour => format!("{}/h", fmt_distance(whole)),
+        }
+    }
+
+    /// Name used in JSON output
+    pub fn key(&self) -> &'static str {
+        match self {
+            Unit::Count => "count",
+            Unit::Ticks => "ticks",
+            Unit::Centimetres => "centimetres",
+            Unit::Ratio => "ratio",
+            Unit::PerHour => "per_hour",
+            Unit::CentimetresPerHour => "centimetres_per_hour",
+        }
+    }
+}
+
+/// A single value in a report row
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReportValue {
+    Number {
+        value: f64,
+        unit: Unit,
+    },
+    /// Free text. May use Discord-style `**bold**`, which terminals drop.
+    Text(String),
+}
+
+impl ReportValue {
+    pub fn count(value: impl Into<f64>) -> Self {
+        ReportValue::Number {
+            value: value.into(),
+            unit: Unit::Count,
+        }
+    }
+
+    pub fn number(value: f64, unit: Unit) -> Self {
+        ReportValue::Number { value, unit }
+    }
+
+    pub fn text(text: impl Into<String>) -> Self {
+        ReportValue::Text(text.into())
+    }
+
+    pub fn format(&self) -> String {
+        match self {
+            ReportValue::Number { value, unit } => unit.format(*value),
+            ReportValue::Text(text) => text.clone(),
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        match self {
+            ReportValue::Number { value, unit } => json!({
+                "value": value,
+                "unit": unit.key(),
+                "display": unit.format(*value),
+            }),
+            ReportValue::Text(text) => json!({ "display": text.replace("**", "") }),
+        }
+    }
+}
+
+/// One labelled line of a report, with a value per column of its section
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportRow {
+    pub label: String,
+    /// Rows without values are shown as a bullet point
+    pub values: Vec<ReportValue>,
+    /// Index of the value to emphasise, e.g. the better player in a comparison
+    pub best: Option<usize>,
+    /// Extra line shown with the value, e.g. the top player of a server total
+    pub note: Option<String>,
+}
+
+impl ReportRow {
+    pub fn new(label: impl Into<String>, value: ReportValue) -> Self {
+        ReportRow {
+            label: label.into(),
+            values: vec![value],
+            best: None,
+            note: None,
+        }
+    }
+
+    /// A row with just a label, e.g. an item of a list
+    pub fn item(label: impl Into<String>) -> Self {
+        ReportRow {
+            label: label.into(),
+            values: vec![],
+            best: None,
+            note: None,
+        }
+    }
+
+    pub fn note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+}
+
+/// Order of the rows of a list. Rows without a number come last when sorting by value.
+#[derive(poise::ChoiceParameter, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RowOrder {
+    #[name = "Highest first"]
+    #[name = "value"]
+    #[default]
+    Value,
+
+    #[name = "Lowest first"]
+    #[name = "value_asc"]
+    ValueAscending,
+
+    #[name = "By name"]
+    #[name = "name"]
+    Alphabetical,
+}
+
+impl RowOrder {
+    /// Name of the order in the current language, e.g. `Highest first`
+    pub fn label(&self) -> String {
+        match self {
+            RowOrder::Value => tr("order.value"),
+            RowOrder::ValueAscending => tr("order.value_asc"),
+            RowOrder::Alphabetical => tr("order.name"),
+        }
+    }
+
+    /// Sorts rows by their first value or label. Ties keep their current order.
+    pub fn sort(&self, rows: &mut [ReportRow]) {
+        let value = |row: &ReportRow| match row.values.first() {
+            Some(ReportValue::Number { value, .. }) => Some(*value),
+            _ => None,
+        };
+        match self {
+            RowOrder::Value => rows.sort_by(|a, b| {
+                let (a, b) = (value(a), value(b));
+                b.is_some()
+                    .cmp(&a.is_some())
+                    .then_with(|| b.unwrap_or(0.0).total_cmp(&a.unwrap_or(0.0)))
+            }),
+            RowOrder::ValueAscending => rows.sort_by(|a, b| {
+                let (a, b) = (value(a), value(b));
+                b.is_some()
+                    .cmp(&a.is_some())
+                    .then_with(|| a.unwrap_or(0.0).total_cmp(&b.unwrap_or(0.0)))
+            }),
+            RowOrder::Alphabetical => rows.sort_by_cached_key(|row| row.label.to_lowercase()),
+        }
+    }
+}
+
+/// How a section is laid out. Only frontends with limited space, like Discord
+/// embeds, need to tell these apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SectionStyle {
+    /// A few short rows shown side by side
+    Inline,
+    /// A short list shown as one block
+    Block,
+    /// A long list that may be split over several pages
+    Paged,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportSection {
+    pub title: String,
+    /// Names of the value columns, e.g. the players in a comparison.
+    /// Empty for sections with a single value column.
+    pub columns: Vec<String>,
+    pub rows: Vec<ReportRow>,
+    pub style: SectionStyle,
+}
+
+impl ReportSection {
+    pub fn new(title: impl Into<String>, style: SectionStyle, rows: Vec<ReportRow>) -> Self {
+        ReportSection {
+            title: title.into(),
+            columns: vec![],
+            rows,
+            style,
+        }
+    }
+}
+
+/// Frontend-independent result of a command. The library builds these and
+/// each frontend renders them: Discord embeds, terminal tables or JSON.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsReport {
+    pub title: String,
+    pub description: Option<String>,
+    /// Shown at the bottom of every page
+    pub footer: Option<String>,
+    pub sections: Vec<ReportSection>,
+}
+
+impl StatsReport {
+    pub fn new(title: impl Into<String>) -> Self {
+        StatsReport {
+            title: title.into(),
+            description: None,
+            footer: None,
+            sections: vec![],
+        }
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn footer(mut self, footer: impl Into<String>) -> Self {
+        self.footer = Some(footer.into());
+        self
+    }
+
+    pub fn section(mut self, section: ReportSection) -> Self {
+        self.sections.push(section);
+        self
+    }
+
+    /// Finds a section by title, e.g. to trim it before rendering
+    pub fn section_mut(&mut self, title: &str) -> Option<&mut ReportSection> {
+        self.sections
+            .iter_mut()
+            .find(|section| section.title == title)
+    }
+
+    /// Sorts the rows of every paged section. Ties keep their current order.
+    pub fn sort_rows(&mut self, order: RowOrder) {
+        for section in &mut self.sections {
+            if section.style == SectionStyle::Paged {
+                order.sort(&mut section.rows);
+            }
+        }
+    }
+
+    /// Renders the report as JSON. Numbers keep their raw value and unit next
+    /// to the formatted text.
+    pub fn to_json(&self) -> Value {
+        let sections = self
+            .sections
+            .iter()
+            .map(|section| {
+                let rows = section
+                    .rows
+                    .iter()
+                    .map(|row| {
+                        json!({
+                            "label": row.label.replace("**", ""),
+                            "values": row.values.iter().map(ReportValue::to_json).collect::<Vec<_>>(),
+                            "best": row.best,
+                            "note": row.note,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                json!({ "title": section.title, "columns": section.columns, "rows": rows })
+            })
+            .collect::<Vec<_>>();
+        json!({
+            "title": self.title,
+            "description": self.description,
+            "footer": self.footer,
+            "sections": sections,
+        })
+    }
+
+    /// Renders the report as coloured tables for a terminal
+    pub fn to_terminal(&self) -> String {
+        let plain = |text: &str| text.replace("**", "");
+        let mut out = format!("{}\n", self.title.bold().green());
+        if let Some(description) = &self.description {
+            out.push_str(&format!("{}\n", plain(description)));
+        }
+        for section in &self.sections {
+            if section.rows.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("\n{}\n", section.title.bold()));
+            if section.rows.iter().all(|row| row.values.is_empty()) {
+                for row in &section.rows {
+                    out.push_str(&format!("- {}\n", plain(&row.label)));
+                }
+                continue;
+            }
+            let has_notes = section.rows.iter().any(|row| row.note.is_some());
+            let headers = (!section.columns.is_empty()).then(|| {
+                let mut headers = vec![String::new()];
+                headers.extend(section.columns.iter().cloned());
+                headers
+            });
+            let rows = section
+                .rows
+                .iter()
+                .map(|row| {
+                    let mut cells = vec![(plain(&row.label), false)];
+                    cells.extend(
+                        row.values
+                            .iter()
+                            .enumerate()
+                            .map(|(i, value)| (plain(&value.format()), row.best == Some(i))),
+                    );
+                    if has_notes {
+                        cells.push((row.note.as_deref().map(plain).unwrap_or_default(), false));
+                    }
+                    cells
+                })
+                .collect::<Vec<_>>();
+            out.push_str(&terminal_table(headers.as_deref(), &rows));
+        }
+        if let Some(footer) = &self.footer {
+            out.push_str(&format!("\n{}\n", plain(footer).dimmed()));
+        }
+        out
+    }
+}
+
+/// Lays out cells in aligned columns. The first column is coloured and
+/// emphasised cells are bold.
+fn terminal_table(headers: Option<&[String]>, rows: &[Vec<(String, bool)>]) -> String {
+    let mut widths = vec![0; rows.iter().map(Vec::len).max().unwrap_or(0)];
+    for cells in headers
+        .into_iter()
+        .map(|headers| headers.iter().collect::<Vec<_>>())
+        .chain(
+            rows.iter()
+                .map(|row| row.iter().map(|(cell, _)| cell).collect()),
+        )
+    {
+        for (width, cell) in widths.iter_mut().zip(cells) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut out = String::new();
+    if let Some(headers) = headers {
+        let line = headers
+            .iter()
+            .zip(&widths)
+            .map(|(header, width)| format!("{:<width$}", header, width = width))
+            .collect::<Vec<_>>()
+            .join("  ");
+        out.push_str(&format!("{}\n", line.trim_end().bold().underline()));
+    }
+    for row in rows {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(i, ((cell, best), width))| {
+                let cell = format!("{:<width$}", cell, width = width);
+                if i == 0 {
+                    cell.cyan().to_string()
+                } else if *best {
+                    cell.bold().to_string()
+                } else {
+                    cell
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+fn name_of(store: &StatsStore, uuid: &str) -> String {
+    store.username(uuid).unwrap_or_else(|| uuid.to_string())
+}
+
+/// Matches the readable name of a stat. Written as `/pattern/` it is a
+/// regex, otherwise a substring. Both ignore case.
+#[derive(Debug, Clone)]
+pub enum NameFilter {
+    Contains(String),
+    Regex(Regex),
+}
+
+impl NameFilter {
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            NameFilter::Contains(text) => name.to_lowercase().contains(text),
+            NameFilter::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
+
+impl FromStr for NameFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(filter: &str) -> anyhow::Result<Self> {
+        let filter = filter.trim();
+        match filter
+            .strip_prefix('/')
+            .and_then(|pattern| pattern.strip_suffix('/'))
+        {
+            Some(pattern) => {
+                let regex = RegexBuilder::new(pattern)
+                    .case_insensitive(true)
+                    .build()
+                    .with_context(|| format!("Invalid regex {}", filter))?;
+                Ok(NameFilter::Regex(regex))
+            }
+            None => Ok(NameFilter::Contains(filter.to_lowercase())),
+        }
+    }
+}
+
+impl fmt::Display for NameFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NameFilter::Contains(text) => write!(f, "\"{}\"", text),
+            NameFilter::Regex(regex) => write!(f, "/{}/", regex),
+        }
+    }
+}
+
+/// Which stats of a category listing to show and in what order
+#[derive(Debug, Clone, Default)]
+pub struct StatFilter {
+    pub order: RowOrder,
+    pub name: Option<NameFilter>,
+    pub group: Option<StatGroup>,
+    /// Leaves out stats below this value
+    pub min: Option<i64>,
+    /// Keeps only the first stats of each category, after sorting
+    pub top: Option<usize>,
+}
+
+impl StatFilter {
+    fn keeps(&self, key: &str, value: i64) -> bool {
+        self.group.is_none_or(|group| group.contains(key))
+            && self.min.is_none_or(|min| value >= min)
+    }
+
+    /// Describes the active options, e.g. `Ores, at least 10, top 5`
+    pub fn describe(&self) -> Option<String> {
+        let mut parts = vec![];
+        parts.extend(self.group.map(|group| group.name().to_string()));
+        parts.extend(
+            self.name
+                .as_ref()
+                .map(|name| tr_with("filter.matching", &[("name", name)])),
+        );
+        parts.extend(
+            self.min
+                .map(|min| tr_with("filter.at_least", &[("min", &min)])),
+        );
+        parts.extend(self.top.map(|top| tr_with("filter.top", &[("top", &top)])));
+        if self.order != RowOrder::Value {
+            parts.push(self.order.label().to_lowercase());
+        }
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+}
+
+/// Rows of a stats category, narrowed down and ordered by `filter`
+fn category_rows(stats: &MinecraftStats, category: &str, filter: &StatFilter) -> Vec<ReportRow> {
+    let mut values = stats
+        .stats
+        .category(category)
+        .into_iter()
+        .flatten()
+        .filter(|(key, value)| filter.keeps(key, **value))
+        .collect::<Vec<_>>();
+    // Stats files have no order of their own
+    values.sort_by_key(|(key, _)| *key);
+    let mut rows = values
+        .into_iter()
+        .map(|(key, value)| {
+            ReportRow::new(
+                readable_stat_name(category, key),
+                ReportValue::number(*value as f64, Unit::of_stat(category, key)),
+            )
+        })
+        .filter(|row| {
+            filter
+                .name
+                .as_ref()
+                .is_none_or(|name| name.matches(&row.label))
+        })
+        .collect::<Vec<_>>();
+    filter.order.sort(&mut rows);
+    if let Some(top) = filter.top {
+        rows.truncate(top);
+    }
+    rows
+}
+
+/// A player's stats, one section per category. With a category, only that one.
+/// Categories left empty by the filter are skipped.
+pub fn player_stats_report(
+    name: &str,
+    stats: &MinecraftStats,
+    category: Option<&str>,
+    filter: &StatFilter,
+) -> StatsReport {
+    let categories = match category {
+        Some(category) => vec![category],
+        None => stats.stats.category_keys().collect(),
+    };
+    let mut report = StatsReport::new(tr_with("stats.title", &[("player", &name)]));
+    if let Some(description) = filter.describe() {
+        report = report.description(tr_with("stats.showing", &[("filter", &description)]));
+    }
+    for category in categories {
+        let rows = category_rows(stats, category, filter);
+        if rows.is_empty() {
+            continue;
+        }
+        report = report.section(ReportSection::new(
+            tr_with(
+                "stats.section",
+                &[
+                    ("category", &MinecraftStats::get_readable_name(category)),
+                    ("player", &name),
+                ],
+            ),
+            SectionStyle::Paged,
+            rows,
+        ));
+    }
+    report
+}
+
+/// Playtime of every player, most first
+pub fn playtime_report(store: &StatsStore) -> StatsReport {
+    let mut players = store
+        .players()
+        .into_iter()
+        .map(|(uuid, stats)| {
+            let ticks = stats.stats.custom_stat(CustomStat::PlayTime);
+            (name_of(store, &uuid), ticks)
+        })
+        .collect::<Vec<_>>();
+    players.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let rows = players
+        .into_iter()
+        .map(|(name, ticks)| ReportRow::new(name, ReportValue::number(ticks as f64, Unit::Ticks)))
+        .collect();
+    StatsReport::new(tr("playtime.title")).section(ReportSection::new(
+        tr("playtime.players"),
+        SectionStyle::Inline,
+        rows,
+    ))
+}
+
+/// Index of the highlights section in [`profile_report`]'s sections, for
+/// frontends that only show a few of them
+pub const PROFILE_HIGHLIGHTS_SECTION: usize = 1;
+
+/// Headline stats and every highlight of a player
+pub fn profile_report(store: &StatsStore, uuid: &str, name: &str) -> Option<StatsReport> {
+    let player = store.get(uuid)?;
+    let stats = &player.stats;
+    let kill_death_ratio = match DerivedMetric::KillDeathRatio.compute(stats) {
+        Some(value) => ReportValue::number(value, Unit::Ratio),
+        None => ReportValue::text(tr("not_available")),
+    };
+    let overview = vec![
+        ReportRow::new(
+            tr("profile.playtime"),
+            ReportValue::number(stats.custom_stat(CustomStat::PlayTime) as f64, Unit::Ticks),
+        ),
+        ReportRow::new(
+            tr("profile.kills"),
+            ReportValue::count(stats.custom_stat(CustomStat::PlayerKills) as f64),
+        ),
+        ReportRow::new(
+            tr("profile.deaths"),
+            ReportValue::count(stats.custom_stat(CustomStat::Deaths) as f64),
+        ),
+        ReportRow::new(tr("profile.kill_death_ratio"), kill_death_ratio),
+    ];
+    let highlights = player_highlights(uuid, &store.players())
+        .iter()
+        .map(|highlight| ReportRow::item(highlight.to_string()))
+        .collect();
+    Some(
+        StatsReport::new(tr_with("profile.title", &[("player", &name)]))
+            .description(tr_with("profile.uuid", &[("uuid", &uuid)]))
+            .section(ReportSection::new(
+                tr("profile.overview"),
+                SectionStyle::Inline,
+                overview,
+            ))
+            .section(ReportSection::new(
+                tr("profile.highlights"),
+                SectionStyle::Block,
+                highlights,
+            )),
+    )
+}
+
+/// Players ranked by `target`, best first
+pub fn leaderboard_report(
+    store: &StatsStore,
+    target: &LeaderboardTarget,
+    min_hours: f64,
+) -> StatsReport {
+    let unit = target.unit();
+    let rows = leaderboard(&store.players(), target, min_hours)
+        .into_iter()
+        .enumerate()
+        .map(|(i, (uuid, value))| {
+            ReportRow::new(
+                format!("#{} {}", i + 1, name_of(store, &uuid)),
+                ReportValue::number(value, unit),
+            )
+        })
+        .collect::<Vec<_>>();
+    let mut description = if min_hours > 0.0 {
+        tr_with("leaderboard.min_playtime", &[("hours", &min_hours)])
+    } else {
+        tr("leaderboard.all_players")
+    };
+    if rows.is_empty() {
+        description.push_str("\n\n");
+        description.push_str(&tr("leaderboard.none_qualify"));
+    }
+    StatsReport::new(tr_with(
+        "leaderboard.title",
+        &[("target", &target.get_name())],
+    ))
+    .description(description)
+    .section(ReportSection::new(
+        tr("leaderboard.ranking"),
+        SectionStyle::Paged,
+        rows,
+    ))
+}
+
+/// Server-wide totals, with a breakdown of every category
+pub fn totals_report(store: &StatsStore) -> StatsReport {
+    let players = store.players();
+    let headline = server_totals(&players)
+        .into_iter()
+        .map(|total| {
+            let top = match &total.top {
+                Some((uuid, value)) => tr_with(
+                    "totals.top",
+                    &[
+                        ("player", &name_of(store, uuid)),
+                        ("value", &total.stat.format(*value)),
+                    ],
+                ),
+                None => tr("totals.top_none"),
+            };
+            ReportRow::new(
+                total.stat.get_name(),
+                ReportValue::number(total.total as f64, total.stat.unit()),
+            )
+            .note(top)
+        })
+        .collect();
+    let mut report = StatsReport::new(tr("totals.title"))
+        .description(tr_with("totals.summed", &[("count", &players.len())]))
+        .section(ReportSection::new(
+            tr("totals.section"),
+            SectionStyle::Inline,
+            headline,
+        ));
+    for category in STAT_CATEGORIES {
+        let rows = category_totals(&players, category)
+            .into_iter()
+            .map(|(key, value)| {
+                ReportRow::new(
+                    readable_stat_name(category, &key),
+                    ReportValue::number(value as f64, Unit::of_stat(category, &key)),
+                )
+            })
+            .collect();
+        report = report.section(ReportSection::new(
+            tr_with(
+                "totals.category",
+                &[("category", &MinecraftStats::get_readable_name(category))],
+            ),
+            SectionStyle::Paged,
+            rows,
+        ));
+    }
+    report
+}
+
+/// The top player of every stat
+pub fn superlatives_report(store: &StatsStore) -> StatsReport {
+    let rows = server_superlatives(&store.players())
+        .into_iter()
+        .map(|superlative| {
+            let unit = Unit::of_stat(&superlative.category, &superlative.stat);
+            ReportRow::new(
+                format!(
+                    "{} ({})",
+                    readable_stat_name(&superlative.category, &superlative.stat),
+                    MinecraftStats::get_readable_name(&superlative.category)
+                ),
+                ReportValue::text(format!(
+                    "{} ({})",
+                    name_of(store, &superlative.uuid),
+                    unit.format(superlative.value as f64)
+                )),
+            )
+        })
+        .collect::<Vec<_>>();
+    let mut report = StatsReport::new(tr("superlatives.title"));
+    if rows.is_empty() {
+        report = report.description(tr("superlatives.not_enough"));
+    }
+    report.section(ReportSection::new(
+        tr("superlatives.section"),
+        SectionStyle::Paged,
+        rows,
+    ))
+}
+
+/// Two players side by side, with the better value of each row marked.
+/// Derived metrics of players under `min_hours` of playtime are left out, and
+/// metrics that can't be computed, e.g. per hour without playtime, are N/A.
+pub fn compare_report(
+    (name_a, stats_a): (&str, &MinecraftStats),
+    (name_b, stats_b): (&str, &MinecraftStats),
+    min_hours: f64,
+) -> StatsReport {
+    // Values that are left out hold the text shown instead
+    let row = |label: String,
+               a: Result<f64, String>,
+               b: Result<f64, String>,
+               unit: Unit,
+               higher_is_better: bool| {
+        let best = match (&a, &b) {
+            (Ok(x), Ok(y)) if x != y => Some(if (x > y) == higher_is_better { 0 } else { 1 }),
+            _ => None,
+        };
+        let value = |value: Result<f64, String>| match value {
+            Ok(value) => ReportValue::number(value, unit),
+            Err(text) => ReportValue::text(text),
+        };
+        ReportRow {
+            label,
+            values: vec![value(a), value(b)],
+            best,
+            note: None,
+        }
+    };
+    let mut rows = TotalStat::ALL
+        .iter()
+        .map(|total| {
+            row(
+                total.get_name(),
+                Ok(total.value(&stats_a.stats) as f64),
+                Ok(total.value(&stats_b.stats) as f64),
+                total.unit(),
+                true,
+            )
+        })
+        .collect::<Vec<_>>();
+    for metric in DerivedMetric::ALL {
+        // Metrics of players under the threshold are too noisy to compare
+        let value = |stats: &MinecraftStats| match metric.compute(&stats.stats) {
+            None => Err(tr("not_available")),
+            Some(_) if playtime_hours(&stats.stats) < min_hours => {
+                Err(tr_with("compare.under_min", &[("hours", &min_hours)]))
+            }
+            Some(value) => Ok(value),
+        };
+        rows.push(row(
+            metric.get_name(),
+            value(stats_a),
+            value(stats_b),
+            metric.unit(),
+            metric.higher_is_better(),
+        ));
+    }
+    let mut section = ReportSection::new(tr("compare.section"), SectionStyle::Inline, rows);
+    section.columns = vec![name_a.to_string(), name_b.to_string()];
+    StatsReport::new(tr_with("compare.title", &[("a", &name_a), ("b", &name_b)])).section(section)
+}
+
+/// How a player died according to the logs, next to the deaths in their stats file
+pub fn deaths_report(
+    name: &str,
+    breakdown: &[(DeathCause, u64)],
+    recorded_deaths: u64,
+) -> StatsReport {
+    let logged_deaths: u64 = breakdown.iter().map(|(_, count)| count).sum();
+    let note = if recorded_deaths > logged_deaths {
+        tr_with(
+            "deaths.missing",
+            &[("count", &(recorded_deaths - logged_deaths))],
+        )
+    } else if recorded_deaths < logged_deaths {
+        tr("deaths.reset")
+    } else {
+        tr("deaths.match")
+    };
+    let causes = breakdown
+        .iter()
+        .map(|(cause, count)| ReportRow::new(cause.to_string(), ReportValue::count(*count as f64)))
+        .collect::<Vec<_>>();
+    let mut report = StatsReport::new(tr_with("deaths.title", &[("player", &name)])).footer(note);
+    if causes.is_empty() {
+        report = report.description(tr("deaths.none"));
+    }
+    report
+        .section(ReportSection::new(
+            tr("deaths.section"),
+            SectionStyle::Inline,
+            vec![
+                ReportRow::new(
+                    tr("deaths.in_logs"),
+                    ReportValue::count(logged_deaths as f64),
+                ),
+                ReportRow::new(
+                    tr("deaths.in_stats"),
+                    ReportValue::count(recorded_deaths as f64),
+                ),
+            ],
+        ))
+        .section(ReportSection::new(
+            tr("deaths.causes"),
+            SectionStyle::Paged,
+            causes,
+        ))
+}
+
+/// The classes of a section on one day
+pub fn timetable_report(section: &str, classes: &[String]) -> StatsReport {
+    let rows = classes.iter().map(ReportRow::item).collect();
+    StatsReport::new(tr_with("timetable.title", &[("section", &section)])).section(
+        ReportSection::new(tr("timetable.classes"), SectionStyle::Block, rows),
+    )
+}
+
+/// Every objective and team of the scoreboard
+pub fn scoreboard_report(scoreboard: &Scoreboard) -> StatsReport {
+    let objectives = scoreboard
+        .objectives
+        .iter()
+        .map(|objective| {
+            let mut note = tr_with(
+                "scoreboard.objective",
+                &[("name", &objective.name), ("criteria", &objective.criteria)],
+            );
+            let slots = scoreboard.slots_of(&objective.name);
+            if !slots.is_empty() {
+                note.push_str(", ");
+                note.push_str(&tr_with(
+                    "scoreboard.shown_in",
+                    &[("slots", &slots.join(", "))],
+                ));
+            }
+            let scores = scoreboard.ranking(&objective.name).len();
+            ReportRow::new(
+                objective.display_name.clone(),
+                ReportValue::text(tr_with("scoreboard.scores", &[("count", &scores)])),
+            )
+            .note(note)
+        })
+        .collect::<Vec<_>>();
+    let teams = scoreboard
+        .teams
+        .iter()
+        .map(|team| {
+            ReportRow::new(
+                team.display_name.clone(),
+                ReportValue::text(tr_with(
+                    "scoreboard.members",
+                    &[("count", &team.players.len())],
+                )),
+            )
+        })
+        .collect::<Vec<_>>();
+    let mut report = StatsReport::new(tr("scoreboard.title"));
+    if objectives.is_empty() && teams.is_empty() {
+        report = report.description(tr("scoreboard.empty"));
+    }
+    report
+        .section(ReportSection::new(
+            tr("scoreboard.objectives"),
+            SectionStyle::Paged,
+            objectives,
+        ))
+        .section(ReportSection::new(
+            tr("scoreboard.teams"),
+            SectionStyle::Paged,
+            teams,
+        ))
+}
+
+/// Every score of an objective, highest first
+pub fn objective_report(scoreboard: &Scoreboard, objective: &Objective) -> StatsReport {
+    let rows = scoreboard
+        .ranking(&objective.name)
+        .into_iter()
+        .enumerate()
+        .map(|(i, score)| {
+            ReportRow::new(
+                format!("#{} {}", i + 1, score.holder),
+                ReportValue::count(score.value),
+            )
+        })
+        .collect::<Vec<_>>();
+    let mut description = tr_with(
+        "scoreboard.objective",
+        &[("name", &objective.name), ("criteria", &objective.criteria)],
+    );
+    if rows.is_empty() {
+        description.push_str("\n\n");
+        description.push_str(&tr("scoreboard.no_scores"));
+    }
+    StatsReport::new(objective.display_name.clone())
+        .description(description)
+        .section(ReportSection::new(
+            tr("leaderboard.ranking"),
+            SectionStyle::Paged,
+            rows,
+        ))
+}
+
+/// The members of a team
+pub fn team_report(team: &Team) -> StatsReport {
+    let mut players = team.players.clone();
+    players.sort_by_key(|player| player.to_lowercase());
+    let mut report = StatsReport::new(team.display_name.clone());
+    if let Some(color) = &team.color {
+        report = report.description(tr_with("team.color", &[("color", color)]));
+    }
+    if players.is_empty() {
+        report = report.footer(tr("team.empty"));
+    }
+    report.section(ReportSection::new(
+        tr_with("team.members", &[("count", &players.len())]),
+        SectionStyle::Block,
+        players.iter().map(ReportRow::item).collect(),
+    ))
+}
+
+/// Playtime of a player, or that they have never joined
+fn playtime_value(store: &StatsStore, uuid: &str) -> ReportValue {
+    match store.get(uuid) {
+        Some(player) => ReportValue::number(
+            player.stats.custom_stat(CustomStat::PlayTime) as f64,
+            Unit::Ticks,
+        ),
+        None => ReportValue::text(tr("access.never_joined")),
+    }
+}
+
+/// The whitelist, split by who has joined, and the players who joined
+/// without being on it
+pub fn whitelist_report(lists: &AccessLists, store: &StatsStore) -> StatsReport {
+    let mut whitelist = lists.whitelist.iter().collect::<Vec<_>>();
+    whitelist.sort_by_key(|entry| entry.name.to_lowercase());
+    let (joined, never_joined): (Vec<_>, Vec<_>) = whitelist
+        .into_iter()
+        .partition(|entry| has_joined(store, &entry.uuid));
+    let joined = joined
+        .into_iter()
+        .map(|entry| ReportRow::new(entry.name.clone(), playtime_value(store, &entry.uuid)))
+        .collect::<Vec<_>>();
+    let never_joined = never_joined
+        .into_iter()
+        .map(|entry| ReportRow::item(entry.name.clone()))
+        .collect::<Vec<_>>();
+    let mut not_whitelisted = lists
+        .not_whitelisted(store)
+        .into_iter()
+        .map(|uuid| ReportRow::new(name_of(store, &uuid), playtime_value(store, &uuid)))
+        .collect::<Vec<_>>();
+    not_whitelisted.sort_by_key(|row| row.label.to_lowercase());
+
+    let description = if lists.whitelist.is_empty() {
+        tr("whitelist.empty")
+    } else {
+        tr_with(
+            "whitelist.summary",
+            &[
+                ("count", &lists.whitelist.len()),
+                ("never_joined", &never_joined.len()),
+            ],
+        )
+    };
+    StatsReport::new(tr("whitelist.title"))
+        .description(description)
+        .section(ReportSection::new(
+            tr("whitelist.joined"),
+            SectionStyle::Paged,
+            joined,
+        ))
+        .section(ReportSection::new(
+            tr("access.never_joined"),
+            SectionStyle::Paged,
+            never_joined,
+        ))
+        .section(ReportSection::new(
+            tr("whitelist.not_whitelisted"),
+            SectionStyle::Paged,
+            not_whitelisted,
+        ))
+}
+
+/// Operators and their permission levels, highest first
+pub fn ops_report(lists: &AccessLists, store: &StatsStore) -> StatsReport {
+    let mut ops = lists.ops.iter().collect::<Vec<_>>();
+    ops.sort_by(|a, b| {
+        b.level
+            .cmp(&a.level)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    let rows = ops
+        .into_iter()
+        .map(|op| {
+            let mut notes = vec![];
+            if op.bypasses_player_limit {
+                notes.push(tr("ops.bypasses_player_limit"));
+            }
+            if !has_joined(store, &op.uuid) {
+                notes.push(tr("access.never_joined"));
+            }
+            let row = ReportRow::new(
+                op.name.clone(),
+                ReportValue::text(tr_with("ops.level", &[("level", &op.level)])),
+            );
+            if notes.is_empty() {
+                row
+            } else {
+                row.note(notes.join(", "))
+            }
+        })
+        .collect::<Vec<_>>();
+    let mut report = StatsReport::new(tr("ops.title"));
+    if rows.is_empty() {
+        report = report.description(tr("ops.empty"));
+    }
+    report.section(ReportSection::new(
+        tr("ops.section"),
+        SectionStyle::Paged,
+        rows,
+    ))
+}
+
+/// Who banned, when and until when
+fn ban_note(ban: &BanDetails) -> String {
+    // Dates look like `2024-05-01 12:00:00 +0000`, the day is enough
+    let day = |date: &str| date.split(' ').next().unwrap_or_default().to_string();
+    let until = if ban.is_permanent() {
+        tr("bans.permanent")
+    } else {
+        tr_with("bans.until", &[("date", &day(&ban.expires))])
+    };
+    tr_with(
+        "bans.note",
+        &[
+            ("source", &ban.source),
+            ("date", &day(&ban.created)),
+            ("until", &until),
+        ],
+    )
+}
+
+/// Banned players and IPs, with the reasons for their bans
+pub fn bans_report(lists: &AccessLists) -> StatsReport {
+    let mut players = lists.banned_players.iter().collect::<Vec<_>>();
+    players.sort_by_key(|entry| entry.name.to_lowercase());
+    let players = players
+        .into_iter()
+        .map(|entry| {
+            ReportRow::new(
+                entry.name.clone(),
+                ReportValue::text(entry.ban.reason.clone()),
+            )
+            .note(ban_note(&entry.ban))
+        })
+        .collect::<Vec<_>>();
+    let ips = lists
+        .banned_ips
+        .iter()
+        .map(|entry| {
+            ReportRow::new(
+                entry.ip.clone(),
+                ReportValue::text(entry.ban.reason.clone()),
+            )
+            .note(ban_note(&entry.ban))
+        })
+        .collect::<Vec<_>>();
+    let mut report = StatsReport::new(tr("bans.title"));
+    if players.is_empty() && ips.is_empty() {
+        report = report.description(tr("bans.empty"));
+    }
+    report
+        .section(ReportSection::new(
+            tr("bans.players"),
+            SectionStyle::Paged,
+            players,
+        ))
+        .section(ReportSection::new(tr("bans.ips"), SectionStyle::Paged, ips))
+}
+
+/// The settings of `server.properties` players ask about most
+pub fn server_properties_report(properties: &ServerProperties) -> StatsReport {
+    let text =
+        |value: Option<String>| ReportValue::text(value.unwrap_or_else(|| tr("not_available")));
+    let on_off = |value: Option<bool>| {
+        text(value.map(|on| tr(if on { "settings.on" } else { "settings.off" })))
+    };
+    let rows = vec![
+        ReportRow::new(
+            tr("settings.difficulty"),
+            text(properties.difficulty().map(str::to_string)),
+        ),
+        ReportRow::new(
+            tr("settings.gamemode"),
+            text(properties.gamemode().map(str::to_string)),
+        ),
+        ReportRow::new(
+            tr("settings.view_distance"),
+            text(
+                properties
+                    .view_distance()
+                    .map(|chunks| tr_with("settings.chunks", &[("count", &chunks)])),
+            ),
+        ),
+        ReportRow::new(tr("settings.pvp"), on_off(properties.pvp())),
+        ReportRow::new(tr("settings.whitelist"), on_off(properties.whitelist())),
+        ReportRow::new(
+            tr("settings.query"),
+            on_off(Some(properties.query_enabled())),
+        ),
+        ReportRow::new(
+            tr("settings.query_port"),
+            text(Some(properties.query_port().to_string())),
+        ),
+    ];
+    StatsReport::new(tr("settings.title")).section(ReportSection::new(
+        tr("settings.section"),
+        SectionStyle::Inline,
+        rows,
+    ))
+}
+
+/// Settings of `server.properties` that changed, with their old and new values
+pub fn property_changes_report(changes: &[PropertyChange]) -> StatsReport {
+    let rows = changes
+        .iter()
+        .map(|change| {
+            let value = |value: &Option<String>| match value {
+                _ if change.is_secret() => tr("settings.hidden"),
+                Some(value) => format!("`{}`", value),
+                None => tr("settings.not_set"),
+            };
+            ReportRow::new(
+                change.key.clone(),
+                ReportValue::text(format!("{} → {}", value(&change.old), value(&change.new))),
+            )
+        })
+        .collect();
+    StatsReport::new(tr("settings.changed_title"))
+        .description(tr_with("settings.changed", &[("count", &changes.len())]))
+        .section(ReportSection::new(
+            tr("settings.changes"),
+            SectionStyle::Paged,
+            rows,
+        ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::Language;
+    use crate::schema::parse_stats;
+    use std::path::PathBuf;
+
+    const ALICE: &str = r#"{
+        "DataVersion": 3953,
+        "stats": {
+            "minecraft:mined": {
+                "minecraft:stone": 640,
+                "minecraft:diamond_ore": 12,
+                "minecraft:deepslate_iron_ore": 30,
+                "minecraft:dirt": 3
+            },
+            "minecraft:killed": { "minecraft:zombie": 5 },
+            "minecraft:custom": {
+                "minecraft:play_time": 144000,
+                "minecraft:player_kills": 6,
+                "minecraft:deaths": 3,
+                "minecraft:walk_one_cm": 250000
+            }
+        }
+    }"#;
+
+    const BOB: &str = r#"{
+        "DataVersion": 3953,
+        "stats": {
+            "minecraft:mined": { "minecraft:stone": 900 },
+            "minecraft:custom": { "minecraft:deaths": 2 }
+        }
+    }"#;
+
+    fn stats(json: &str) -> MinecraftStats {
+        parse_stats(json).unwrap().stats
+    }
+
+    fn labels(rows: &[ReportRow]) -> Vec<&str> {
+        rows.iter().map(|row| row.label.as_str()).collect()
+    }
+
+    fn number_row(label: &str, value: f64) -> ReportRow {
+        ReportRow::new(label, ReportValue::count(value))
+    }
+
+    /// A store of `(name, uuid, stats json)` players in a fresh directory
+    fn store(name: &str, players: &[(&str, &str, &str)]) -> StatsStore {
+        let dir = std::env::temp_dir().join(format!("report-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        let stats_dir = dir.join("stats");
+        std::fs::create_dir_all(&stats_dir).unwrap();
+        let usercache = players
+            .iter()
+            .map(|(name, uuid, json)| {
+                std::fs::write(stats_dir.join(format!("{}.json", uuid)), json).unwrap();
+                json!({ "name": name, "uuid": uuid, "expiresOn": "2030-01-01 00:00:00 +0000" })
+            })
+            .collect::<Vec<_>>();
+        let usercache_path: PathBuf = dir.join("usercache.json");
+        std::fs::write(&usercache_path, Value::from(usercache).to_string()).unwrap();
+        StatsStore::load(stats_dir, usercache_path).unwrap()
+    }
+
+    #[test]
+    fn report_json_keeps_raw_numbers_and_strips_bold() {
+        let report = StatsReport::new("Title")
+            .description("Description")
+            .footer("Footer")
+            .section(ReportSection::new(
+                "Section",
+                SectionStyle::Inline,
+                vec![
+                    ReportRow::new(
+                        "**Walked**",
+                        ReportValue::number(250_000.0, Unit::Centimetres),
+                    ),
+                    ReportRow::new("Best", ReportValue::text("**Alice**")).note("Note"),
+                ],
+            ));
+        let json = report.to_json();
+        assert_eq!(json["title"], "Title");
+        assert_eq!(json["description"], "Description");
+        assert_eq!(json["footer"], "Footer");
+        let rows = &json["sections"][0]["rows"];
+        assert_eq!(rows[0]["label"], "Walked");
+        assert_eq!(rows[0]["values"][0]["value"], 250_000.0);
+        assert_eq!(rows[0]["values"][0]["unit"], "centimetres");
+        assert_eq!(rows[0]["values"][0]["display"], fmt_distance(250_000));
+        assert_eq!(rows[1]["values"][0]["display"], "Alice");
+        assert_eq!(rows[1]["note"], "Note");
+    }
+
+    #[test]
+    fn report_terminal_lists_items_and_skips_empty_sections() {
+        let report = StatsReport::new("Title")
+            .section(ReportSection::new("Empty", SectionStyle::Paged, vec![]))
+            .section(ReportSection::new(
+                "Items",
+                SectionStyle::Block,
+                vec![ReportRow::item("**First**"), ReportRow::item("Second")],
+            ));
+        colored::control::set_override(false);
+        let text = report.to_terminal();
+        assert!(!text.contains("Empty"));
+        assert!(text.contains("Items\n- First\n- Second\n"));
+    }
+
+    #[test]
+    fn sort_rows_only_sorts_paged_sections() {
+        let rows = vec![number_row("a", 1.0), number_row("b", 3.0)];
+        let mut report = StatsReport::new("Title")
+            .section(ReportSection::new(
+                "Inline",
+                SectionStyle::Inline,
+                rows.clone(),
+            ))
+            .section(ReportSection::new("Paged", SectionStyle::Paged, rows));
+        report.sort_rows(RowOrder::Value);
+        assert_eq!(labels(&report.sections[0].rows), ["a", "b"]);
+        assert_eq!(
+            labels(&report.section_mut("Paged").unwrap().rows),
+            ["b", "a"]
+        );
+        assert!(report.section_mut("Missing").is_none());
+    }
+
+    #[test]
+    fn row_order_puts_rows_without_numbers_last() {
+        let mut rows = vec![
+            ReportRow::new("text", ReportValue::text("-")),
+            number_row("Beta", 2.0),
+            number_row("alpha", 5.0),
+            number_row("gamma", 2.0),
+        ];
+        RowOrder::Value.sort(&mut rows);
+        assert_eq!(labels(&rows), ["alpha", "Beta", "gamma", "text"]);
+        RowOrder::ValueAscending.sort(&mut rows);
+        assert_eq!(labels(&rows), ["Beta", "gamma", "alpha", "text"]);
+        RowOrder::Alphabetical.sort(&mut rows);
+        assert_eq!(labels(&rows), ["alpha", "Beta", "gamma", "text"]);
+    }
+
+    #[test]
+    fn name_filter_matches_substrings_and_regexes_ignoring_case() {
+        let filter = "  Ore ".parse::<NameFilter>().unwrap();
+        assert!(filter.matches("Diamond Ore Mined"));
+        assert!(!filter.matches("Stone Mined"));
+        assert_eq!(filter.to_string(), "\"ore\"");
+
+        let filter = "/^d.*ore/".parse::<NameFilter>().unwrap();
+        assert!(filter.matches("Diamond Ore Mined"));
+        assert!(filter.matches("deepslate iron ore"));
+        assert!(!filter.matches("Iron Ore Mined"));
+        assert_eq!(filter.to_string(), "/^d.*ore/");
+
+        assert!("/[/".parse::<NameFilter>().is_err());
+        // A lone slash is a substring, not an empty regex
+        assert!(matches!(
+            "/".parse::<NameFilter>(),
+            Ok(NameFilter::Contains(_))
+        ));
+    }
+
+    #[test]
+    fn stat_filter_describes_its_options() {
+        assert_eq!(StatFilter::default().describe(), None);
+        let filter = StatFilter {
+            order: RowOrder::Alphabetical,
+            name: Some("ore".parse().unwrap()),
+            group: Some(StatGroup::Ores),
+            min: Some(10),
+            top: Some(5),
+        };
+        assert_eq!(
+            filter.describe().unwrap(),
+            "Ores, matching \"ore\", at least 10, top 5, by name"
+        );
+    }
+
+    #[test]
+    fn player_stats_report_applies_the_filter() {
+        let alice = stats(ALICE);
+        let report = player_stats_report("Alice", &alice, None, &StatFilter::default());
+        assert_eq!(report.title, "Stats for Alice");
+        assert_eq!(report.description, None);
+        assert_eq!(report.sections.len(), 3);
+        assert!(
+            report
+                .sections
+                .iter()
+                .all(|s| s.style == SectionStyle::Paged)
+        );
+
+        let filter = StatFilter {
+            min: Some(10),
+            ..Default::default()
+        };
+        let report = player_stats_report("Alice", &alice, Some("minecraft:mined"), &filter);
+        assert_eq!(report.description.as_deref(), Some("Showing at least 10"));
+        assert_eq!(
+            labels(&report.sections[0].rows),
+            [
+                "Stone Mined",
+                "Deepslate Iron Ore Mined",
+                "Diamond Ore Mined"
+            ]
+        );
+
+        let filter = StatFilter {
+            order: RowOrder::Alphabetical,
+            group: Some(StatGroup::Ores),
+            top: Some(1),
+            ..Default::default()
+        };
+        let report = player_stats_report("Alice", &alice, None, &filter);
+        // Categories without ores are left out
+        assert_eq!(report.sections.len(), 1);
+        assert_eq!(
+            labels(&report.sections[0].rows),
+            ["Deepslate Iron Ore Mined"]
+        );
+
+        let filter = StatFilter {
+            name: Some("/^dirt/".parse().unwrap()),
+            ..Default::default()
+        };
+        let report = player_stats_report("Alice", &alice, Some("minecraft:mined"), &filter);
+        assert_eq!(labels(&report.sections[0].rows), ["Dirt Mined"]);
+    }
+
+    #[test]
+    fn compare_report_marks_the_better_player() {
+        let (alice, bob) = (stats(ALICE), stats(BOB));
+        let report = compare_report(("Alice", &alice), ("Bob", &bob), 1.0);
+        assert_eq!(report.title, "Alice vs Bob");
+        let section = &report.sections[0];
+        assert_eq!(section.columns, ["Alice", "Bob"]);
+        let row = |label: &str| {
+            section
+                .rows
+                .iter()
+                .find(|row| row.label == label)
+                .unwrap_or_else(|| panic!("No row {}", label))
+        };
+        assert_eq!(row("Blocks Mined").best, Some(1));
+        assert_eq!(row("Playtime").best, Some(0));
+        assert_eq!(row("Diamonds Mined").values[1], ReportValue::count(0));
+        assert_eq!(
+            row("PvP K/D Ratio").values[0],
+            ReportValue::number(2.0, Unit::Ratio)
+        );
+    }
+
+    #[test]
+    fn compare_report_tells_missing_values_from_low_playtime() {
+        let (alice, bob) = (stats(ALICE), stats(BOB));
+        // Alice has 2 hours of playtime and Bob has none
+        let report = compare_report(("Alice", &alice), ("Bob", &bob), 3.0);
+        let rows = &report.sections[0].rows;
+        let row = |label: &str| rows.iter().find(|row| row.label == label).unwrap();
+        let under_min = ReportValue::text("N/A (under 3 h)");
+        let missing = ReportValue::text("N/A");
+
+        // Bob's K/D exists, but he has too little playtime
+        assert_eq!(
+            row("PvP K/D Ratio").values,
+            [under_min.clone(), under_min.clone()]
+        );
+        // Bob has no playtime to divide by, whatever the minimum
+        assert_eq!(row("Deaths per Hour").values, [under_min, missing.clone()]);
+        assert_eq!(row("Deaths per Hour").best, None);
+
+        let report = compare_report(("Alice", &alice), ("Bob", &bob), 0.0);
+        let rows = &report.sections[0].rows;
+        let row = |label: &str| rows.iter().find(|row| row.label == label).unwrap();
+        assert_eq!(
+            row("Deaths per Hour").values[0],
+            ReportValue::number(1.5, Unit::PerHour)
+        );
+        assert_eq!(row("Deaths per Hour").values[1], missing);
+        assert_eq!(row("PvP K/D Ratio").best, Some(0));
+    }
+
+    #[test]
+    fn deaths_report_compares_logs_with_stats() {
+        let breakdown = [(DeathCause::Fall, 2), (DeathCause::Lava, 1)];
+        let report = deaths_report("Alice", &breakdown, 5);
+        assert_eq!(
+            report.footer,
+            Some(tr_with("deaths.missing", &[("count", &2)]))
+        );
+        assert_eq!(report.sections[0].rows[0].values[0], ReportValue::count(3));
+        assert_eq!(report.sections[1].rows.len(), 2);
+
+        let report = deaths_report("Alice", &[], 0);
+        assert_eq!(report.description, Some(tr("deaths.none")));
+        assert_eq!(report.footer, Some(tr("deaths.match")));
+    }
+
+    #[test]
+    fn deaths_report_is_translated() {
+        let breakdown = [
+            (DeathCause::Fall, 2),
+            (DeathCause::Player("Bob".to_string()), 1),
+        ];
+        let report = Language::Hindi.scope(|| deaths_report("Alice", &breakdown, 3));
+        assert_eq!(report.title, "Alice की मौतें");
+        assert_eq!(
+            labels(&report.sections[1].rows),
+            ["गिरने से चोट", "PvP (Bob)"]
+        );
+    }
+
+    #[test]
+    fn playtime_report_ranks_players_by_playtime() {
+        let store = store(
+            "playtime",
+            &[
+                ("Bob", "069a79f4-44e9-4726-a5be-fca90e38aaf5", BOB),
+                ("Alice", "853c80ef-3c37-49fd-aa49-938b674adae6", ALICE),
+            ],
+        );
+        let report = playtime_report(&store);
+        let rows = &report.sections[0].rows;
+        assert_eq!(labels(rows), ["Alice", "Bob"]);
+        assert_eq!(
+            rows[0].values[0],
+            ReportValue::number(144_000.0, Unit::Ticks)
+        );
+    }
+
+    #[test]
+    fn profile_report_uses_pvp_kills_and_deaths() {
+        let uuid = "853c80ef-3c37-49fd-aa49-938b674adae6";
+        let store = store("profile", &[("Alice", uuid, ALICE)]);
+        let report = profile_report(&store, uuid, "Alice").unwrap();
+        let overview = &report.sections[0].rows;
+        assert_eq!(overview[1].values[0], ReportValue::count(6));
+        assert_eq!(overview[2].values[0], ReportValue::count(3));
+        assert_eq!(overview[3].values[0], ReportValue::number(2.0, Unit::Ratio));
+        assert_eq!(
+            report.sections[PROFILE_HIGHLIGHTS_SECTION].title,
+            tr("profile.highlights")
+        );
+        assert!(profile_report(&store, "069a79f4-44e9-4726-a5be-fca90e38aaf5", "Bob").is_none());
+    }
+}
@@ -0,0 +1,448 @@
+use crate::{MinecraftStats, StatCategories};
+use anyhow::{Result, bail};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// First DataVersion with namespaced stat categories (17w47a, before 1.13)
+const NAMESPACED_DATA_VERSION: i32 = 1451;
+/// First DataVersion of the current layout (1.17)
+const CURRENT_DATA_VERSION: i32 = 2724;
+
+/// Ids that were renamed since, with their current ones
+const RENAMED_IDS: [(&str, &str); 2] = [
+    // Renamed in 1.16
+    ("minecraft:zombie_pigman", "minecraft:zombified_piglin"),
+    (
+        "minecraft:zombie_pigman_spawn_egg",
+        "minecraft:zombified_piglin_spawn_egg",
+    ),
+];
+
+/// Layouts of stats files, by the Minecraft versions that wrote them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsSchema {
+    /// Before 1.13: one flat object with keys like `stat.walkOneCm` and
+    /// `stat.mineBlock.minecraft.stone`, and no DataVersion
+    Flat,
+    /// 1.13 to 1.16: namespaced categories, with playtime still called
+    /// `minecraft:play_one_minute`
+    Namespaced,
+    /// 1.17 and later
+    Current,
+}
+
+impl StatsSchema {
+    pub fn for_data_version(data_version: Option<i32>) -> Self {
+        match data_version {
+            None => StatsSchema::Flat,
+            Some(version) if version < NAMESPACED_DATA_VERSION => StatsSchema::Flat,
+            Some(version) if version < CURRENT_DATA_VERSION => StatsSchema::Namespaced,
+            Some(_) => StatsSchema::Current,
+        }
+    }
+}
+
+/// A parsed stats file, upgraded to the current layout
+#[derive(Debug)]
+pub struct LoadedStats {
+    pub stats: MinecraftStats,
+    /// Layout the file was written in
+    pub schema: StatsSchema,
+    /// Keys that could not be mapped to a category, e.g. `achievement.openInventory`
+    pub unknown_keys: Vec<String>,
+}
+
+/// Parses a stats file of any Minecraft version. Old layouts are upgraded to
+/// the current one, and keys that have no place in it are reported rather
+/// than dropped.
+pub fn parse_stats(json: &str) -> Result<LoadedStats> {
+    let Value::Object(root) = serde_json::from_str::<Value>(json)? else {
+        bail!("Expected a JSON object");
+    };
+    let data_version = root
+        .get("DataVersion")
+        .and_then(Value::as_i64)
+        .map(|version| version as i32);
+    // Flat files have no DataVersion, but may still be recognised by their keys
+    let schema = if root.contains_key("stats") {
+        StatsSchema::for_data_version(data_version.or(Some(NAMESPACED_DATA_VERSION)))
+    } else {
+        StatsSchema::Flat
+    };
+
+    let mut unknown_keys = vec![];
+    let mut categories = match schema {
+        StatsSchema::Flat => upgrade_flat(&root, &mut unknown_keys),
+        StatsSchema::Namespaced | StatsSchema::Current => {
+            for key in root.keys() {
+                if key != "stats" && key != "DataVersion" {
+                    unknown_keys.push(key.clone());
+                }
+            }
+            let Some(Value::Object(stats)) = root.get("stats") else {
+                bail!("Expected `stats` to be an object");
+            };
+            read_categories(stats, &mut unknown_keys)
+        }
+    };
+    if schema != StatsSchema::Current {
+        if let Some(custom) = categories.get_mut("minecraft:custom")
+            && let Some(ticks) = custom.remove("minecraft:play_one_minute")
+        {
+            *custom.entry("minecraft:play_time".to_string()).or_default() += ticks;
+        }
+        for values in categories.values_mut() {
+            for (old, new) in RENAMED_IDS {
+                if let Some(value) = values.remove(old) {
+                    *values.entry(new.to_string()).or_default() += value;
+                }
+            }
+        }
+    }
+
+    let mut category = |key: &str| categories.remove(key);
+    let stats = StatCategories {
+        custom: category("minecraft:custom").unwrap_or_default(),
+        mined: category("minecraft:mined"),
+        crafted: category("minecraft:crafted"),
+        used: category("minecraft:used"),
+        broken: category("minecraft:broken"),
+        picked_up: category("minecraft:picked_up"),
+        dropped: category("minecraft:dropped"),
+        killed: category("minecraft:killed"),
+        killed_by: category("minecraft:killed_by"),
+        custom_entity: category("minecraft:custom_entity"),
+        // Whatever is left was added by mods or datapacks
+        modded: categories.into_iter().collect(),
+    };
+    Ok(LoadedStats {
+        stats: MinecraftStats {
+            stats,
+            data_version,
+        },
+        schema,
+        unknown_keys,
+    })
+}
+
+/// Minecraft keeps stats as 32-bit ints, so values past 2³¹ wrap around to
+/// negative numbers. Stats only ever go up, so those are read back unsigned.
+fn stat_value(value: &Value) -> Option<i64> {
+    let value = value.as_i64()?;
+    if (i32::MIN as i64..0).contains(&value) {
+        Some(value + (1 << 32))
+    } else {
+        Some(value)
+    }
+}
+
+/// Reads the namespaced categories, e.g. `{"minecraft:mined": {"minecraft:stone": 3}}`.
+/// Categories of mods and datapacks are kept too.
+fn read_categories(
+    stats: &Map<String, Value>,
+    unknown_keys: &mut Vec<String>,
+) -> HashMap<String, HashMap<String, i64>> {
+    let mut categories = HashMap::new();
+    for (category, values) in stats {
+        let values = match values {
+            Value::Object(values) => values,
+            _ => {
+                unknown_keys.push(category.clone());
+                continue;
+            }
+        };
+        let mut parsed = HashMap::new();
+        for (key, value) in values {
+            match stat_value(value) {
+                Some(value) => {
+                    parsed.insert(key.clone(), value);
+                }
+                None => unknown_keys.push(format!("{}/{}", category, key)),
+            }
+        }
+        categories.insert(category.clone(), parsed);
+    }
+    categories
+}
+
+/// Maps the keys of a pre-1.13 file onto namespaced categories, e.g.
+/// `stat.mineBlock.minecraft.stone` to `minecraft:stone` in `minecraft:mined`
+fn upgrade_flat(
+    root: &Map<String, Value>,
+    unknown_keys: &mut Vec<String>,
+) -> HashMap<String, HashMap<String, i64>> {
+    let prefixes = [
+        ("stat.mineBlock.", "minecraft:mined"),
+        ("stat.craftItem.", "minecraft:crafted"),
+        ("stat.useItem.", "minecraft:used"),
+        ("stat.breakItem.", "minecraft:broken"),
+        ("stat.pickup.", "minecraft:picked_up"),
+        ("stat.drop.", "minecraft:dropped"),
+        ("stat.killEntity.", "minecraft:killed"),
+        ("stat.entityKilledBy.", "minecraft:killed_by"),
+    ];
+    let mut categories = HashMap::<String, HashMap<String, i64>>::new();
+    for (key, value) in root {
+        let Some(value) = stat_value(value) else {
+            unknown_keys.push(key.clone());
+            continue;
+        };
+        let upgraded = prefixes
+            .iter()
+            .find_map(|(prefix, category)| {
+                let name = key.strip_prefix(prefix)?;
+                let stat = if category.ends_with("killed") || category.ends_with("killed_by") {
+                    legacy_entity_key(name)
+                } else {
+                    // Items were written as `minecraft.stone`
+                    name.replacen('.', ":", 1)
+                };
+                Some((category.to_string(), stat))
+            })
+            .or_else(|| {
+                let name = key.strip_prefix("stat.")?;
+                Some(("minecraft:custom".to_string(), legacy_custom_key(name)))
+            });
+        match upgraded {
+            Some((category, stat)) => {
+                *categories
+                    .entry(category)
+                    .or_default()
+                    .entry(stat)
+                    .or_default() += value;
+            }
+            // Achievements became advancements, which are kept in another file
+            None => unknown_keys.push(key.clone()),
+        }
+    }
+    categories
+}
+
+/// Upgrades a pre-1.13 custom stat, e.g. `walkOneCm` to `minecraft:walk_one_cm`
+fn legacy_custom_key(name: &str) -> String {
+    let renamed = match name {
+        "craftingTableInteraction" => "interact_with_crafting_table",
+        "furnaceInteraction" => "interact_with_furnace",
+        "brewingstandInteraction" => "interact_with_brewingstand",
+        "beaconInteraction" => "interact_with_beacon",
+        "dispenserInspected" => "inspect_dispenser",
+        "dropperInspected" => "inspect_dropper",
+        "hopperInspected" => "inspect_hopper",
+        "chestOpened" => "open_chest",
+        "enderchestOpened" => "open_enderchest",
+        "shulkerBoxOpened" => "open_shulker_box",
+        "trappedChestTriggered" => "trigger_trapped_chest",
+        "noteblockPlayed" => "play_noteblock",
+        "noteblockTuned" => "tune_noteblock",
+        "recordPlayed" => "play_record",
+        "flowerPotted" => "pot_flower",
+        "itemEnchanted" => "enchant_item",
+        "cakeSlicesEaten" => "eat_cake_slice",
+        "cauldronFilled" => "fill_cauldron",
+        "cauldronUsed" => "use_cauldron",
+        "armorCleaned" => "clean_armor",
+        "bannerCleaned" => "clean_banner",
+        "diveOneCm" => "walk_under_water_one_cm",
+        name => return format!("minecraft:{}", snake_case(name)),
+    };
+    format!("minecraft:{}", renamed)
+}
+
+/// Upgrades a pre-1.13 entity name, e.g. `PigZombie` to `minecraft:zombified_piglin`
+fn legacy_entity_key(name: &str) -> String {
+    let renamed = match name {
+        "PigZombie" => "zombified_piglin",
+        "LavaSlime" => "magma_cube",
+        "VillagerGolem" => "iron_golem",
+        "SnowMan" => "snow_golem",
+        "EntityHorse" => "horse",
+        "Ozelot" => "ocelot",
+        "MushroomCow" => "mooshroom",
+        "WitherBoss" => "wither",
+        "EnderDragon" => "ender_dragon",
+        // 1.11 and later already used the new ids, e.g. `minecraft.zombie`
+        name => {
+            return format!(
+                "minecraft:{}",
+                snake_case(name.trim_start_matches("minecraft."))
+            );
+        }
+    };
+    format!("minecraft:{}", renamed)
+}
+
+fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 1.12 file: flat keys, no DataVersion and pre-1.11 entity names
+    const FLAT: &str = r#"{
+        "stat.mineBlock.minecraft.stone": 640,
+        "stat.craftItem.minecraft.stick": 12,
+        "stat.useItem.minecraft.diamond_pickaxe": 300,
+        "stat.pickup.minecraft.cobblestone": 500,
+        "stat.killEntity.PigZombie": 3,
+        "stat.killEntity.minecraft.zombie_pigman": 2,
+        "stat.killEntity.LavaSlime": 4,
+        "stat.entityKilledBy.Zombie": 1,
+        "stat.walkOneCm": 150000,
+        "stat.diveOneCm": 900,
+        "stat.playOneMinute": 72000,
+        "stat.chestOpened": 7,
+        "stat.deaths": -1,
+        "achievement.openInventory": 1,
+        "achievement.exploreAllBiomes": {"value": 0, "progress": []}
+    }"#;
+
+    /// A 1.15 file: namespaced, but playtime is still `play_one_minute`
+    const NAMESPACED: &str = r#"{
+        "DataVersion": 2230,
+        "stats": {
+            "minecraft:custom": {
+                "minecraft:play_one_minute": 36000,
+                "minecraft:deaths": 2
+            },
+            "minecraft:killed": {
+                "minecraft:zombie_pigman": 5,
+                "minecraft:zombie": 1
+            },
+            "minecraft:used": { "minecraft:zombie_pigman_spawn_egg": 1 },
+            "create:custom": { "create:wrench_used": 3 },
+            "minecraft:broken": 4
+        },
+        "version": "1.15.2"
+    }"#;
+
+    #[test]
+    fn picks_schema_by_data_version() {
+        assert_eq!(StatsSchema::for_data_version(None), StatsSchema::Flat);
+        assert_eq!(StatsSchema::for_data_version(Some(1343)), StatsSchema::Flat);
+        assert_eq!(
+            StatsSchema::for_data_version(Some(2230)),
+            StatsSchema::Namespaced
+        );
+        assert_eq!(
+            StatsSchema::for_data_version(Some(3953)),
+            StatsSchema::Current
+        );
+    }
+
+    #[test]
+    fn upgrades_flat_files() {
+        let loaded = parse_stats(FLAT).unwrap();
+        assert_eq!(loaded.schema, StatsSchema::Flat);
+        assert_eq!(loaded.stats.data_version, None);
+        let stats = &loaded.stats.stats;
+        assert_eq!(stats.get("minecraft:mined", "minecraft:stone"), 640);
+        assert_eq!(stats.get("minecraft:crafted", "minecraft:stick"), 12);
+        assert_eq!(
+            stats.get("minecraft:used", "minecraft:diamond_pickaxe"),
+            300
+        );
+        assert_eq!(
+            stats.get("minecraft:picked_up", "minecraft:cobblestone"),
+            500
+        );
+        // Both spellings of zombified piglins end up under the current id
+        assert_eq!(
+            stats.get("minecraft:killed", "minecraft:zombified_piglin"),
+            5
+        );
+        assert_eq!(stats.get("minecraft:killed", "minecraft:zombie_pigman"), 0);
+        assert_eq!(stats.get("minecraft:killed", "minecraft:magma_cube"), 4);
+        assert_eq!(stats.get("minecraft:killed_by", "minecraft:zombie"), 1);
+        assert_eq!(
+            stats.get("minecraft:custom", "minecraft:walk_one_cm"),
+            150000
+        );
+        assert_eq!(
+            stats.get("minecraft:custom", "minecraft:walk_under_water_one_cm"),
+            900
+        );
+        assert_eq!(stats.get("minecraft:custom", "minecraft:play_time"), 72000);
+        assert_eq!(
+            stats.get("minecraft:custom", "minecraft:play_one_minute"),
+            0
+        );
+        assert_eq!(stats.get("minecraft:custom", "minecraft:open_chest"), 7);
+        // Wrapped around past 2³¹
+        assert_eq!(
+            stats.get("minecraft:custom", "minecraft:deaths"),
+            u32::MAX as i64
+        );
+        let mut unknown = loaded.unknown_keys.clone();
+        unknown.sort();
+        assert_eq!(
+            unknown,
+            ["achievement.exploreAllBiomes", "achievement.openInventory"]
+        );
+    }
+
+    #[test]
+    fn upgrades_namespaced_files() {
+        let loaded = parse_stats(NAMESPACED).unwrap();
+        assert_eq!(loaded.schema, StatsSchema::Namespaced);
+        assert_eq!(loaded.stats.data_version, Some(2230));
+        let stats = &loaded.stats.stats;
+        assert_eq!(stats.get("minecraft:custom", "minecraft:play_time"), 36000);
+        assert_eq!(stats.get("minecraft:custom", "minecraft:deaths"), 2);
+        assert_eq!(
+            stats.get("minecraft:killed", "minecraft:zombified_piglin"),
+            5
+        );
+        assert_eq!(stats.get("minecraft:killed", "minecraft:zombie_pigman"), 0);
+        assert_eq!(stats.get("minecraft:killed", "minecraft:zombie"), 1);
+        assert_eq!(
+            stats.get("minecraft:used", "minecraft:zombified_piglin_spawn_egg"),
+            1
+        );
+        assert_eq!(stats.get("create:custom", "create:wrench_used"), 3);
+        let mut unknown = loaded.unknown_keys.clone();
+        unknown.sort();
+        assert_eq!(unknown, ["minecraft:broken", "version"]);
+    }
+
+    #[test]
+    fn leaves_current_files_alone() {
+        let json = r#"{
+            "DataVersion": 3953,
+            "stats": {
+                "minecraft:custom": {
+                    "minecraft:play_time": 100,
+                    "minecraft:play_one_minute": 5
+                }
+            }
+        }"#;
+        let loaded = parse_stats(json).unwrap();
+        assert_eq!(loaded.schema, StatsSchema::Current);
+        let stats = &loaded.stats.stats;
+        assert_eq!(stats.get("minecraft:custom", "minecraft:play_time"), 100);
+        assert_eq!(
+            stats.get("minecraft:custom", "minecraft:play_one_minute"),
+            5
+        );
+        assert!(loaded.unknown_keys.is_empty());
+    }
+
+    #[test]
+    fn rejects_other_json() {
+        assert!(parse_stats("[]").is_err());
+        assert!(parse_stats(r#"{"DataVersion": 3953, "stats": []}"#).is_err());
+        assert!(parse_stats("{").is_err());
+    }
+}
@@ -0,0 +1,422 @@
+use crate::nbt::{self, Tag};
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// A scoreboard objective, e.g. one made with `/scoreboard objectives add deaths deathCount`
+#[derive(Debug, Clone)]
+pub struct Objective {
+    pub name: String,
+    /// What the game counts, e.g. `deathCount` or `minecraft.mined:minecraft.diamond_ore`.
+    /// Objectives only changed by commands and datapacks use `dummy`.
+    pub criteria: String,
+    pub display_name: String,
+    /// `integer` or `hearts`
+    pub render_type: String,
+}
+
+/// The score of one holder: a player's name, an entity's UUID, or a fake
+/// player used by datapacks, e.g. `#total`
+#[derive(Debug, Clone)]
+pub struct Score {
+    pub holder: String,
+    pub objective: String,
+    pub value: i32,
+    /// Whether `trigger` objectives are disabled for the holder
+    pub locked: bool,
+}
+
+impl Score {
+    /// Holders starting with `#` are never shown in game, and are used by
+    /// datapacks to keep totals and temporary values
+    pub fn is_hidden(&self) -> bool {
+        self.holder.starts_with('#')
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Team {
+    pub name: String,
+    pub display_name: String,
+    /// Names of the players and entities on the team
+    pub players: Vec<String>,
+    /// Colour of the members' names, e.g. `red`
+    pub color: Option<String>,
+    pub prefix: String,
+    pub suffix: String,
+}
+
+/// The contents of `world/data/scoreboard.dat`
+#[derive(Debug, Clone, Default)]
+pub struct Scoreboard {
+    pub objectives: Vec<Objective>,
+    pub scores: Vec<Score>,
+    pub teams: Vec<Team>,
+    /// Objective shown in each display slot, e.g. `sidebar` → `deaths`
+    pub display_slots: BTreeMap<String, String>,
+}
+
+impl Scoreboard {
+    pub fn load(path: &Path) -> Result<Self> {
+        let root =
+            nbt::read_file(path).with_context(|| format!("Could not read {}", path.display()))?;
+        Ok(Self::from_nbt(&root))
+    }
+
+    /// Reads the scoreboard from the root of `scoreboard.dat`. Entries missing
+    /// required fields are skipped.
+    pub fn from_nbt(root: &Tag) -> Self {
+        let data = root.get("data").unwrap_or(root);
+        let list = |key: &str| data.get(key).and_then(Tag::as_list).unwrap_or_default();
+        let string = |tag: &Tag, key: &str| tag.get(key).and_then(Tag::as_str).map(str::to_string);
+
+        let objectives = list("Objectives")
+            .iter()
+            .filter_map(|tag| {
+                let name = string(tag, "Name")?;
+                Some(Objective {
+                    display_name: tag
+                        .get("DisplayName")
+                        .and_then(plain_text)
+                        .unwrap_or_else(|| name.clone()),
+                    criteria: string(tag, "CriteriaName").unwrap_or_else(|| "dummy".to_string()),
+                    render_type: string(tag, "RenderType").unwrap_or_else(|| "integer".to_string()),
+                    name,
+                })
+            })
+            .collect();
+        let scores = list("PlayerScores")
+            .iter()
+            .filter_map(|tag| {
+                Some(Score {
+                    holder: string(tag, "Name")?,
+                    objective: string(tag, "Objective")?,
+                    value: tag.get("Score").and_then(Tag::as_i64)? as i32,
+                    locked: tag.get("Locked").and_then(Tag::as_i64).unwrap_or(0) != 0,
+                })
+            })
+            .collect();
+        let teams = list("Teams")
+            .iter()
+            .filter_map(|tag| {
+                let name = string(tag, "Name")?;
+                let text = |key: &str| tag.get(key).and_then(plain_text).unwrap_or_default();
+                Some(Team {
+                    display_name: tag
+                        .get("DisplayName")
+                        .and_then(plain_text)
+                        .unwrap_or_else(|| name.clone()),
+                    players: tag
+                        .get("Players")
+                        .and_then(Tag::as_list)
+                        .unwrap_or_default()
+                        .iter()
+                        .filter_map(|player| player.as_str().map(str::to_string))
+                        .collect(),
+                    color: string(tag, "TeamColor").filter(|color| color != "reset"),
+                    prefix: text("MemberNamePrefix"),
+                    suffix: text("MemberNameSuffix"),
+                    name,
+                })
+            })
+            .collect();
+        let display_slots = data
+            .get("DisplaySlots")
+            .and_then(Tag::as_compound)
+            .into_iter()
+            .flatten()
+            .filter_map(|(slot, objective)| {
+                Some((display_slot_name(slot), objective.as_str()?.to_string()))
+            })
+            .collect();
+        Scoreboard {
+            objectives,
+            scores,
+            teams,
+            display_slots,
+        }
+    }
+
+    pub fn objective(&self, name: &str) -> Option<&Objective> {
+        self.objectives
+            .iter()
+            .find(|objective| objective.name == name)
+    }
+
+    /// Scores of an objective, highest first. Hidden holders are left out.
+    pub fn ranking(&self, objective: &str) -> Vec<&Score> {
+        let mut scores = self
+            .scores
+            .iter()
+            .filter(|score| score.objective == objective && !score.is_hidden())
+            .collect::<Vec<_>>();
+        scores.sort_by(|a, b| b.value.cmp(&a.value).then_with(|| a.holder.cmp(&b.holder)));
+        scores
+    }
+
+    /// Finds a team by its name or display name, ignoring case
+    pub fn team(&self, name: &str) -> Option<&Team> {
+        self.teams.iter().find(|team| {
+            team.name.eq_ignore_ascii_case(name) || team.display_name.eq_ignore_ascii_case(name)
+        })
+    }
+
+    /// Display slots an objective is shown in, e.g. `sidebar`
+    pub fn slots_of(&self, objective: &str) -> Vec<&str> {
+        self.display_slots
+            .iter()
+            .filter(|(_, shown)| *shown == objective)
+            .map(|(slot, _)| slot.as_str())
+            .collect()
+    }
+}
+
+/// Colours of the team sidebar slots, in the order of their numbers
+const TEAM_COLORS: [&str; 16] = [
+    "black",
+    "dark_blue",
+    "dark_green",
+    "dark_aqua",
+    "dark_red",
+    "dark_purple",
+    "gold",
+    "gray",
+    "dark_gray",
+    "blue",
+    "green",
+    "aqua",
+    "red",
+    "light_purple",
+    "yellow",
+    "white",
+];
+
+/// Names a display slot the way `/scoreboard objectives setdisplay` does.
+/// Before 1.20.2 slots were saved by number, e.g. `slot_1` for `sidebar`.
+fn display_slot_name(slot: &str) -> String {
+    let Some(number) = slot
+        .strip_prefix("slot_")
+        .and_then(|number| number.parse::<usize>().ok())
+    else {
+        return slot.to_string();
+    };
+    match number {
+        0 => "list".to_string(),
+        1 => "sidebar".to_string(),
+        2 => "below_name".to_string(),
+        n => match TEAM_COLORS.get(n - 3) {
+            Some(color) => format!("sidebar.team.{}", color),
+            None => slot.to_string(),
+        },
+    }
+}
+
+/// Gets the plain text of a text component, e.g. `Deaths` for
+/// `{"text":"Deaths","color":"red"}`. Older versions store components as
+/// JSON strings and newer ones as NBT.
+fn plain_text(tag: &Tag) -> Option<String> {
+    match tag {
+        Tag::String(text) => match serde_json::from_str::<serde_json::Value>(text) {
+            Ok(json) => Some(json_text(&json)),
+            // Plain strings are components too
+            Err(_) => Some(text.clone()),
+        },
+        Tag::Compound(_) => {
+            let mut text = tag
+                .get("text")
+                .or_else(|| tag.get("translate"))
+                .and_then(Tag::as_str)
+                .unwrap_or_default()
+                .to_string();
+            for child in tag.get("extra").and_then(Tag::as_list).unwrap_or_default() {
+                text.push_str(&plain_text(child)?);
+            }
+            Some(text)
+        }
+        Tag::List(children) => children.iter().map(plain_text).collect(),
+        _ => None,
+    }
+}
+
+fn json_text(json: &serde_json::Value) -> String {
+    match json {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Array(children) => children.iter().map(json_text).collect(),
+        serde_json::Value::Object(component) => {
+            let mut text = component
+                .get("text")
+                .or_else(|| component.get("translate"))
+                .and_then(|text| text.as_str())
+                .unwrap_or_default()
+                .to_string();
+            for child in component
+                .get("extra")
+                .and_then(|extra| extra.as_array())
+                .into_iter()
+                .flatten()
+            {
+                text.push_str(&json_text(child));
+            }
+            text
+        }
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nbt::tests::{compound, encode};
+
+    fn string(value: &str) -> Tag {
+        Tag::String(value.to_string())
+    }
+
+    fn score(holder: &str, objective: &str, value: i32) -> Tag {
+        compound([
+            ("Name", string(holder)),
+            ("Objective", string(objective)),
+            ("Score", Tag::Int(value)),
+            ("Locked", Tag::Byte(1)),
+        ])
+    }
+
+    /// A scoreboard.dat as written by 1.20.1, with JSON text components and
+    /// numbered display slots
+    fn scoreboard_dat() -> Vec<u8> {
+        let data = compound([
+            (
+                "Objectives",
+                Tag::List(vec![
+                    compound([
+                        ("Name", string("deaths")),
+                        ("CriteriaName", string("deathCount")),
+                        (
+                            "DisplayName",
+                            string(r#"{"text":"Dea","extra":[{"text":"ths"}],"color":"red"}"#),
+                        ),
+                        ("RenderType", string("hearts")),
+                    ]),
+                    compound([("Name", string("kills"))]),
+                    // No name, so skipped
+                    compound([("CriteriaName", string("dummy"))]),
+                ]),
+            ),
+            (
+                "PlayerScores",
+                Tag::List(vec![
+                    score("Steve", "deaths", 3),
+                    score("Alex", "deaths", 7),
+                    score("#total", "deaths", 10),
+                    score("Steve", "kills", 1),
+                    // No score, so skipped
+                    compound([("Name", string("Alex")), ("Objective", string("kills"))]),
+                ]),
+            ),
+            (
+                "Teams",
+                Tag::List(vec![compound([
+                    ("Name", string("red")),
+                    ("DisplayName", string(r#"{"text":"Red Team"}"#)),
+                    ("Players", Tag::List(vec![string("Steve"), string("Alex")])),
+                    ("TeamColor", string("red")),
+                    ("MemberNamePrefix", string(r#"{"text":"[R] "}"#)),
+                    ("MemberNameSuffix", string("")),
+                ])]),
+            ),
+            (
+                "DisplaySlots",
+                compound([
+                    ("slot_1", string("deaths")),
+                    ("slot_0", string("kills")),
+                    ("slot_15", string("deaths")),
+                ]),
+            ),
+        ]);
+        encode(&compound([("data", data), ("DataVersion", Tag::Int(3465))]))
+    }
+
+    #[test]
+    fn reads_scoreboard_dat() {
+        let root = nbt::parse(&scoreboard_dat()).unwrap();
+        let scoreboard = Scoreboard::from_nbt(&root);
+
+        let objectives = scoreboard
+            .objectives
+            .iter()
+            .map(|objective| {
+                (
+                    objective.name.as_str(),
+                    objective.criteria.as_str(),
+                    objective.display_name.as_str(),
+                    objective.render_type.as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            objectives,
+            [
+                ("deaths", "deathCount", "Deaths", "hearts"),
+                ("kills", "dummy", "kills", "integer"),
+            ]
+        );
+
+        assert_eq!(scoreboard.scores.len(), 4);
+        assert!(scoreboard.scores.iter().all(|score| score.locked));
+        let ranking = scoreboard
+            .ranking("deaths")
+            .iter()
+            .map(|score| (score.holder.as_str(), score.value))
+            .collect::<Vec<_>>();
+        assert_eq!(ranking, [("Alex", 7), ("Steve", 3)]);
+        assert!(
+            scoreboard
+                .scores
+                .iter()
+                .any(|score| score.holder == "#total" && score.is_hidden())
+        );
+
+        let team = scoreboard.team("red team").unwrap();
+        assert_eq!(team.name, "red");
+        assert_eq!(team.display_name, "Red Team");
+        assert_eq!(team.players, ["Steve", "Alex"]);
+        assert_eq!(team.color.as_deref(), Some("red"));
+        assert_eq!(team.prefix, "[R] ");
+        assert_eq!(team.suffix, "");
+        assert!(scoreboard.team("blue").is_none());
+
+        assert_eq!(
+            scoreboard.slots_of("deaths"),
+            ["sidebar", "sidebar.team.red"]
+        );
+        assert_eq!(scoreboard.slots_of("kills"), ["list"]);
+    }
+
+    #[test]
+    fn reads_nbt_text_components() {
+        let root = compound([(
+            "Objectives",
+            Tag::List(vec![compound([
+                ("Name", string("deaths")),
+                (
+                    "DisplayName",
+                    compound([
+                        ("text", string("Dea")),
+                        ("extra", Tag::List(vec![string("ths")])),
+                    ]),
+                ),
+            ])]),
+        )]);
+        let scoreboard = Scoreboard::from_nbt(&root);
+        assert_eq!(scoreboard.objectives[0].display_name, "Deaths");
+    }
+
+    #[test]
+    fn names_display_slots() {
+        assert_eq!(display_slot_name("slot_2"), "below_name");
+        assert_eq!(display_slot_name("slot_3"), "sidebar.team.black");
+        assert_eq!(display_slot_name("slot_18"), "sidebar.team.white");
+        assert_eq!(display_slot_name("slot_19"), "slot_19");
+        assert_eq!(display_slot_name("sidebar"), "sidebar");
+    }
+}
@@ -0,0 +1,317 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Port of servers without `server-port`
+pub const DEFAULT_SERVER_PORT: u16 = 25565;
+
+/// The settings of a server from its `server.properties`. The server reads the
+/// file at startup and writes it back with every setting filled in, so changes
+/// take effect on the next restart.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerProperties {
+    pub values: BTreeMap<String, String>,
+}
+
+impl ServerProperties {
+    /// Parses a Java properties file. Lines are `key=value`, `key: value` or
+    /// `key value`, and lines starting with `#` or `!` are comments.
+    pub fn parse(text: &str) -> Self {
+        let mut values = BTreeMap::new();
+        let mut lines = text.lines();
+        while let Some(line) = lines.next() {
+            let mut line = line.trim_start().to_string();
+            if line.is_empty() || line.starts_with('#') || line.starts_with('!') {
+                continue;
+            }
+            // An odd number of trailing backslashes continues the line
+            while line.chars().rev().take_while(|c| *c == '\\').count() % 2 == 1 {
+                line.pop();
+                match lines.next() {
+                    Some(next) => line.push_str(next.trim_start()),
+                    None => break,
+                }
+            }
+            let (key, value) = split_entry(&line);
+            values.insert(unescape(key), unescape(value));
+        }
+        ServerProperties { values }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read {}", path.display()))?;
+        Ok(Self::parse(&text))
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    /// Gets a `true`/`false` setting
+    pub fn flag(&self, key: &str) -> Option<bool> {
+        self.get(key)?.trim().parse().ok()
+    }
+
+    /// Gets a number setting, e.g. `view-distance`
+    pub fn number<T: std::str::FromStr>(&self, key: &str) -> Option<T> {
+        self.get(key)?.trim().parse().ok()
+    }
+
+    /// `peaceful`, `easy`, `normal` or `hard`
+    pub fn difficulty(&self) -> Option<&str> {
+        self.get("difficulty")
+    }
+
+    /// Game mode of new players: `survival`, `creative`, `adventure` or `spectator`
+    pub fn gamemode(&self) -> Option<&str> {
+        self.get("gamemode")
+    }
+
+    /// Render distance in chunks
+    pub fn view_distance(&self) -> Option<u32> {
+        self.number("view-distance")
+    }
+
+    pub fn pvp(&self) -> Option<bool> {
+        self.flag("pvp")
+    }
+
+    /// Whether only whitelisted players can join. The setting is still called
+    /// `white-list` in the file.
+    pub fn whitelist(&self) -> Option<bool> {
+        self.flag("white-list")
+    }
+
+    pub fn server_port(&self) -> u16 {
+        self.number("server-port").unwrap_or(DEFAULT_SERVER_PORT)
+    }
+
+    /// Whether the server answers the UDP query protocol, used by the `server` command
+    pub fn query_enabled(&self) -> bool {
+        self.flag("enable-query").unwrap_or(false)
+    }
+
+    /// Port of the query protocol, which is the game's port unless set
+    pub fn query_port(&self) -> u16 {
+        self.number("query.port")
+            .unwrap_or_else(|| self.server_port())
+    }
+
+    /// Settings that were added, removed or changed in `newer`, by key
+    pub fn changes(&self, newer: &ServerProperties) -> Vec<PropertyChange> {
+        let mut keys = self.values.keys().collect::<Vec<_>>();
+        keys.extend(newer.values.keys());
+        keys.sort();
+        keys.dedup();
+        keys.into_iter()
+            .filter(|key| self.values.get(*key) != newer.values.get(*key))
+            .map(|key| PropertyChange {
+                key: key.clone(),
+                old: self.values.get(key).cloned(),
+                new: newer.values.get(key).cloned(),
+            })
+            .collect()
+    }
+
+    /// Loads properties saved by [`ServerProperties::save_snapshot`], or `None`
+    /// if none were saved yet
+    pub fn load_snapshot(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&std::fs::read_to_string(path)?)?))
+    }
+
+    /// Saves the properties to compare against later, e.g. after a restart
+    pub fn save_snapshot(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// A setting that differs between two versions of `server.properties`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropertyChange {
+    pub key: String,
+    /// `None` if the setting was added
+    pub old: Option<String>,
+    /// `None` if the setting was removed
+    pub new: Option<String>,
+}
+
+impl PropertyChange {
+    /// Passwords and secrets, whose values should not be shown
+    pub fn is_secret(&self) -> bool {
+        self.key.contains("password") || self.key.contains("secret")
+    }
+}
+
+/// Splits a line at the first unescaped `=`, `:` or whitespace. Whitespace
+/// around the separator is skipped.
+fn split_entry(line: &str) -> (&str, &str) {
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == '=' || c == ':' || c.is_whitespace() {
+            let rest = line[i..].trim_start();
+            // Only one separator is skipped, so `a==b` has the value `=b`
+            let value = rest.strip_prefix(['=', ':']).unwrap_or(rest).trim_start();
+            return (&line[..i], value);
+        }
+    }
+    (line, "")
+}
+
+/// Resolves escapes like `\:`, `\n` and `\u00A7`
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('f') => out.push('\x0c'),
+            Some('u') => {
+                let hex = chars.by_ref().take(4).collect::<String>();
+                match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                    Some(c) => out.push(c),
+                    None => out.push_str(&hex),
+                }
+            }
+            Some(c) => out.push(c),
+            None => {}
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn properties(values: &[(&str, &str)]) -> ServerProperties {
+        ServerProperties {
+            values: values
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn parses_server_properties() {
+        let text = "\
+#Minecraft server properties
+#Mon Oct 19 12:00:00 UTC 2026
+! also a comment
+difficulty=hard
+  view-distance = 12
+pvp:false
+gamemode survival
+motd=\\u00A7cWelcome\\u00a7r to the \\
+    server
+level-name=
+white-list
+";
+        let properties = ServerProperties::parse(text);
+        assert_eq!(properties.values.len(), 7);
+        assert_eq!(properties.difficulty(), Some("hard"));
+        assert_eq!(properties.view_distance(), Some(12));
+        assert_eq!(properties.pvp(), Some(false));
+        assert_eq!(properties.gamemode(), Some("survival"));
+        assert_eq!(properties.get("motd"), Some("§cWelcome§r to the server"));
+        assert_eq!(properties.get("level-name"), Some(""));
+        assert_eq!(properties.get("white-list"), Some(""));
+        assert_eq!(properties.whitelist(), None);
+        assert_eq!(properties.server_port(), DEFAULT_SERVER_PORT);
+        assert_eq!(properties.query_port(), DEFAULT_SERVER_PORT);
+        assert!(!properties.query_enabled());
+    }
+
+    #[test]
+    fn continues_lines_on_odd_backslashes() {
+        let properties = ServerProperties::parse("a=one\\\n  two\nb=path\\\\\nc=three\\");
+        assert_eq!(properties.get("a"), Some("onetwo"));
+        assert_eq!(properties.get("b"), Some("path\\"));
+        assert_eq!(properties.get("c"), Some("three"));
+    }
+
+    #[test]
+    fn splits_entries() {
+        assert_eq!(split_entry("a=b"), ("a", "b"));
+        assert_eq!(split_entry("a==b"), ("a", "=b"));
+        assert_eq!(split_entry("a:b"), ("a", "b"));
+        assert_eq!(split_entry("a : b"), ("a", "b"));
+        assert_eq!(split_entry("a b c"), ("a", "b c"));
+        assert_eq!(split_entry("a\tb"), ("a", "b"));
+        assert_eq!(split_entry("a = =b"), ("a", "=b"));
+        assert_eq!(split_entry("a\\=b=c"), ("a\\=b", "c"));
+        assert_eq!(split_entry("a\\ b c"), ("a\\ b", "c"));
+        assert_eq!(split_entry("a"), ("a", ""));
+    }
+
+    #[test]
+    fn unescapes_values() {
+        assert_eq!(unescape("a\\=b\\:c\\ d"), "a=b:c d");
+        assert_eq!(unescape("\\t\\n\\r\\f"), "\t\n\r\x0c");
+        assert_eq!(unescape("\\u00e9t\\u00E9"), "été");
+        assert_eq!(unescape("C:\\\\Games"), "C:\\Games");
+        assert_eq!(unescape("\\q"), "q");
+        // Invalid escapes are kept as text
+        assert_eq!(unescape("\\uzz"), "zz");
+        assert_eq!(unescape("end\\"), "end");
+    }
+
+    #[test]
+    fn lists_changes() {
+        let old = properties(&[("difficulty", "easy"), ("pvp", "true"), ("motd", "Hi")]);
+        let new = properties(&[
+            ("difficulty", "hard"),
+            ("pvp", "true"),
+            ("rcon.password", "x"),
+        ]);
+        assert_eq!(
+            old.changes(&new),
+            [
+                PropertyChange {
+                    key: "difficulty".to_string(),
+                    old: Some("easy".to_string()),
+                    new: Some("hard".to_string()),
+                },
+                PropertyChange {
+                    key: "motd".to_string(),
+                    old: Some("Hi".to_string()),
+                    new: None,
+                },
+                PropertyChange {
+                    key: "rcon.password".to_string(),
+                    old: None,
+                    new: Some("x".to_string()),
+                },
+            ]
+        );
+        assert!(new.changes(&new).is_empty());
+    }
+
+    #[test]
+    fn hides_secrets() {
+        let change = |key: &str| PropertyChange {
+            key: key.to_string(),
+            old: None,
+            new: None,
+        };
+        assert!(change("rcon.password").is_secret());
+        assert!(change("management-server-secret").is_secret());
+        assert!(!change("motd").is_secret());
+    }
+}
@@ -0,0 +1,62 @@
+use crate::MinecraftStats;
+use crate::stat_key::CustomStat;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Playtime of every player at a point in time. Stats files only hold the
+/// current totals, so these are recorded periodically to chart history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaytimeSnapshot {
+    /// Unix timestamp in seconds
+    pub taken_at: u64,
+    /// Playtime in ticks, keyed by UUID
+    pub playtime: HashMap<String, i64>,
+}
+
+impl PlaytimeSnapshot {
+    pub fn take(players: &[(String, Arc<MinecraftStats>)]) -> Self {
+        PlaytimeSnapshot {
+            taken_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            playtime: players
+                .iter()
+                .map(|(uuid, stats)| (uuid.clone(), stats.stats.custom_stat(CustomStat::PlayTime)))
+                .collect(),
+        }
+    }
+}
+
+/// Appends a snapshot to a JSON lines file, creating it if needed
+pub fn append_snapshot(path: &Path, snapshot: &PlaytimeSnapshot) -> Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(snapshot)?)?;
+    Ok(())
+}
+
+/// Loads every snapshot from a JSON lines file. A missing file has no snapshots.
+pub fn load_snapshots(path: &Path) -> Result<Vec<PlaytimeSnapshot>> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect()
+}
+
+/// Gets a player's playtime over time as `(unix seconds, ticks)`
+pub fn playtime_history(snapshots: &[PlaytimeSnapshot], uuid: &str) -> Vec<(u64, i64)> {
+    snapshots
+        .iter()
+        .filter_map(|snapshot| Some((snapshot.taken_at, *snapshot.playtime.get(uuid)?)))
+        .collect()
+}
@@ -0,0 +1,249 @@
+use anyhow::{Result, anyhow};
+use std::fmt;
+use std::str::FromStr;
+
+/// Namespace of vanilla stats and categories
+pub const MINECRAFT_NAMESPACE: &str = "minecraft";
+
+/// A single stat, e.g. `minecraft:stone` in `minecraft:mined`. Modded stats
+/// and categories keep their own namespace, e.g. `create:...`.
+///
+/// Written like a scoreboard criterion: `minecraft.mined:minecraft.stone`.
+/// When parsing, namespaces may be left out for `minecraft`, e.g. `mined:stone`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StatKey {
+    /// Category key, e.g. `minecraft:mined`
+    pub category: String,
+    pub namespace: String,
+    pub path: String,
+}
+
+impl StatKey {
+    pub fn new(
+        category: impl Into<String>,
+        namespace: impl Into<String>,
+        path: impl Into<String>,
+    ) -> Self {
+        StatKey {
+            category: category.into(),
+            namespace: namespace.into(),
+            path: path.into(),
+        }
+    }
+
+    /// Builds a key from the category and stat as written in stats files,
+    /// e.g. `("minecraft:mined", "minecraft:stone")`
+    pub fn from_ids(category: &str, id: &str) -> Self {
+        let (namespace, path) = split_id(id, ':');
+        StatKey::new(category, namespace, path)
+    }
+
+    /// Gets the stat as written in stats files, e.g. `minecraft:stone`
+    pub fn id(&self) -> String {
+        format!("{}:{}", self.namespace, self.path)
+    }
+}
+
+/// Splits `namespace<separator>path`, defaulting to the `minecraft` namespace
+fn split_id(id: &str, separator: char) -> (&str, &str) {
+    id.split_once(separator)
+        .unwrap_or((MINECRAFT_NAMESPACE, id))
+}
+
+impl fmt::Display for StatKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (category_namespace, category_path) = split_id(&self.category, ':');
+        write!(
+            f,
+            "{}.{}:{}.{}",
+            category_namespace, category_path, self.namespace, self.path
+        )
+    }
+}
+
+impl FromStr for StatKey {
+    type Err = anyhow::Error;
+
+    fn from_str(key: &str) -> Result<Self> {
+        let (category, stat) = key
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Expected category:stat, got {}", key))?;
+        let (category_namespace, category_path) = split_id(category, '.');
+        let (namespace, path) = split_id(stat, '.');
+        if [category_namespace, category_path, namespace, path]
+            .iter()
+            .any(|part| part.is_empty())
+        {
+            return Err(anyhow!("Expected category:stat, got {}", key));
+        }
+        Ok(StatKey::new(
+            format!("{}:{}", category_namespace, category_path),
+            namespace,
+            path,
+        ))
+    }
+}
+
+macro_rules! custom_stats {
+    ($($variant:ident => $key:literal,)*) => {
+        /// Well-known stats of the `minecraft:custom` category
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum CustomStat {
+            $($variant,)*
+        }
+
+        impl CustomStat {
+            /// Every custom stat, in the order of the Minecraft Wiki
+            pub const ALL: &[CustomStat] = &[$(CustomStat::$variant,)*];
+
+            /// Gets the key of the stat, e.g. `minecraft:play_time`
+            pub fn id(&self) -> &'static str {
+                match self {
+                    $(CustomStat::$variant => $key,)*
+                }
+            }
+        }
+    };
+}
+
+// Grouped as on the Minecraft Wiki
+custom_stats! {
+    // Movement statistics
+    AviateOneCm => "minecraft:aviate_one_cm",
+    BoatOneCm => "minecraft:boat_one_cm",
+    ClimbOneCm => "minecraft:climb_one_cm",
+    CrouchOneCm => "minecraft:crouch_one_cm",
+    FallOneCm => "minecraft:fall_one_cm",
+    FlyOneCm => "minecraft:fly_one_cm",
+    HorseOneCm => "minecraft:horse_one_cm",
+    MinecartOneCm => "minecraft:minecart_one_cm",
+    PigOneCm => "minecraft:pig_one_cm",
+    SprintOneCm => "minecraft:sprint_one_cm",
+    StriderOneCm => "minecraft:strider_one_cm",
+    SwimOneCm => "minecraft:swim_one_cm",
+    WalkOnWaterOneCm => "minecraft:walk_on_water_one_cm",
+    WalkUnderWaterOneCm => "minecraft:walk_under_water_one_cm",
+    WalkOneCm => "minecraft:walk_one_cm",
+
+    // Interaction statistics
+    AnimalsBred => "minecraft:animals_bred",
+    BellRing => "minecraft:bell_ring",
+    EatCakeSlice => "minecraft:eat_cake_slice",
+    CleanArmor => "minecraft:clean_armor",
+    CleanBanner => "minecraft:clean_banner",
+    CleanShulkerBox => "minecraft:clean_shulker_box",
+    EnchantItem => "minecraft:enchant_item",
+    FillCauldron => "minecraft:fill_cauldron",
+    FishCaught => "minecraft:fish_caught",
+    InspectDispenser => "minecraft:inspect_dispenser",
+    InspectDropper => "minecraft:inspect_dropper",
+    InspectHopper => "minecraft:inspect_hopper",
+    InteractWithAnvil => "minecraft:interact_with_anvil",
+    InteractWithBeacon => "minecraft:interact_with_beacon",
+    InteractWithBlastFurnace => "minecraft:interact_with_blast_furnace",
+    InteractWithBrewingstand => "minecraft:interact_with_brewingstand",
+    InteractWithCampfire => "minecraft:interact_with_campfire",
+    InteractWithCartographyTable => "minecraft:interact_with_cartography_table",
+    InteractWithCraftingTable => "minecraft:interact_with_crafting_table",
+    InteractWithFurnace => "minecraft:interact_with_furnace",
+    InteractWithGrindstone => "minecraft:interact_with_grindstone",
+    InteractWithLectern => "minecraft:interact_with_lectern",
+    InteractWithLoom => "minecraft:interact_with_loom",
+    InteractWithSmithingTable => "minecraft:interact_with_smithing_table",
+    InteractWithSmoker => "minecraft:interact_with_smoker",
+    InteractWithStonecutter => "minecraft:interact_with_stonecutter",
+    OpenBarrel => "minecraft:open_barrel",
+    OpenChest => "minecraft:open_chest",
+    OpenEnderchest => "minecraft:open_enderchest",
+    OpenShulkerBox => "minecraft:open_shulker_box",
+    PlayNoteblock => "minecraft:play_noteblock",
+    PlayRecord => "minecraft:play_record",
+    PotFlower => "minecraft:pot_flower",
+    SleepInBed => "minecraft:sleep_in_bed",
+    TalkedToVillager => "minecraft:talked_to_villager",
+    TargetHit => "minecraft:target_hit",
+    TradedWithVillager => "minecraft:traded_with_villager",
+    TriggerTrappedChest => "minecraft:trigger_trapped_chest",
+    TuneNoteblock => "minecraft:tune_noteblock",
+    UseCauldron => "minecraft:use_cauldron",
+
+    // Combat statistics
+    DamageAbsorbed => "minecraft:damage_absorbed",
+    DamageBlockedByShield => "minecraft:damage_blocked_by_shield",
+    DamageDealt => "minecraft:damage_dealt",
+    DamageDealtAbsorbed => "minecraft:damage_dealt_absorbed",
+    DamageDealtResisted => "minecraft:damage_dealt_resisted",
+    DamageResisted => "minecraft:damage_resisted",
+    DamageTaken => "minecraft:damage_taken",
+    Deaths => "minecraft:deaths",
+    MobKills => "minecraft:mob_kills",
+    PlayerKills => "minecraft:player_kills",
+
+    // Miscellaneous statistics
+    CrouchTime => "minecraft:crouch_time",
+    Jump => "minecraft:jump",
+    LeaveGame => "minecraft:leave_game",
+    PlayTime => "minecraft:play_time",
+    SneakTime => "minecraft:sneak_time",
+    TimeSinceDeath => "minecraft:time_since_death",
+    TimeSinceRest => "minecraft:time_since_rest",
+    TotalWorldTime => "minecraft:total_world_time",
+
+    // Raid statistics
+    RaidTrigger => "minecraft:raid_trigger",
+    RaidWin => "minecraft:raid_win",
+}
+
+impl CustomStat {
+    pub fn from_id(id: &str) -> Option<Self> {
+        CustomStat::ALL.iter().copied().find(|stat| stat.id() == id)
+    }
+
+    pub fn key(&self) -> StatKey {
+        StatKey::from_ids("minecraft:custom", self.id())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_custom_stat_names;
+    use std::collections::HashSet;
+
+    #[test]
+    fn custom_stats_are_known_to_the_game() {
+        let names = create_custom_stat_names();
+        for stat in CustomStat::ALL {
+            assert!(
+                names.contains_key(stat.id()),
+                "{} is not a known stat",
+                stat.id()
+            );
+        }
+    }
+
+    #[test]
+    fn custom_stat_ids_are_unique() {
+        let ids = CustomStat::ALL
+            .iter()
+            .map(CustomStat::id)
+            .collect::<HashSet<_>>();
+        assert_eq!(ids.len(), CustomStat::ALL.len());
+        for stat in CustomStat::ALL {
+            assert_eq!(CustomStat::from_id(stat.id()), Some(*stat));
+        }
+    }
+
+    #[test]
+    fn parses_criteria() {
+        let key = "mined:stone".parse::<StatKey>().unwrap();
+        assert_eq!(key, StatKey::from_ids("minecraft:mined", "minecraft:stone"));
+        assert_eq!(key.to_string(), "minecraft.mined:minecraft.stone");
+        assert_eq!(
+            CustomStat::OpenChest.key().to_string(),
+            "minecraft.custom:minecraft.open_chest"
+        );
+        assert!("mined:".parse::<StatKey>().is_err());
+        assert!("stone".parse::<StatKey>().is_err());
+    }
+}
@@ -0,0 +1,221 @@
+use crate::schema::parse_stats;
+use crate::users::{NameHistory, NameRecord, UserDirectory};
+use crate::uuids::{self, offline_uuid};
+use crate::{MinecraftStats, UserCache};
+use anyhow::Result;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+/// In-memory copy of every player's stats and the usercache.
+/// Loaded once at startup and kept up to date by [`StatsStore::watch`].
+pub struct StatsStore {
+    stats_dir: PathBuf,
+    usercache_path: PathBuf,
+    /// Where names seen in the usercache are kept, if anywhere
+    name_history_path: Option<PathBuf>,
+    /// Parsed stats files, keyed by UUID
+    players: RwLock<HashMap<String, Arc<MinecraftStats>>>,
+    /// Keys of each stats file that could not be read, last reported
+    unknown_keys: RwLock<HashMap<String, Vec<String>>>,
+    users: RwLock<UserDirectory>,
+}
+
+impl StatsStore {
+    /// Loads every stats file in `stats_dir` and the usercache at `usercache_path`.
+    /// Files that fail to parse are skipped and reported.
+    pub fn load(stats_dir: impl Into<PathBuf>, usercache_path: impl Into<PathBuf>) -> Result<Self> {
+        Self::open(stats_dir.into(), usercache_path.into(), None)
+    }
+
+    /// Like [`StatsStore::load`], but also keeps the names seen in the usercache
+    /// at `name_history_path`, so players who fall out of it keep their names
+    pub fn load_with_name_history(
+        stats_dir: impl Into<PathBuf>,
+        usercache_path: impl Into<PathBuf>,
+        name_history_path: impl Into<PathBuf>,
+    ) -> Result<Self> {
+        Self::open(
+            stats_dir.into(),
+            usercache_path.into(),
+            Some(name_history_path.into()),
+        )
+    }
+
+    fn open(
+        stats_dir: PathBuf,
+        usercache_path: PathBuf,
+        name_history_path: Option<PathBuf>,
+    ) -> Result<Self> {
+        let history = match &name_history_path {
+            Some(path) => NameHistory::load(path)?,
+            None => NameHistory::default(),
+        };
+        let store = StatsStore {
+            stats_dir,
+            usercache_path,
+            name_history_path,
+            players: RwLock::new(HashMap::new()),
+            unknown_keys: RwLock::new(HashMap::new()),
+            users: RwLock::new(UserDirectory::new(vec![], history)),
+        };
+        store.reload_usercache()?;
+        for entry in fs::read_dir(&store.stats_dir)? {
+            let path = entry?.path();
+            if let Err(e) = store.reload_stats_file(&path) {
+                tracing::warn!("Could not load {}: {}", path.display(), e);
+            }
+        }
+        Ok(store)
+    }
+
+    /// Starts watching the stats directory and the usercache for changes.
+    /// Only the files that changed are parsed again. The store stops updating
+    /// once the returned watcher is dropped.
+    pub fn watch(self: &Arc<Self>) -> Result<RecommendedWatcher> {
+        let store = Arc::clone(self);
+        // Events have absolute paths
+        let stats_dir = std::path::absolute(&self.stats_dir)?;
+        let usercache_dir = std::path::absolute(self.usercache_dir())?;
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+            let event = match res {
+                Ok(event) => event,
+                Err(e) => {
+                    tracing::warn!("Stats watcher error: {}", e);
+                    return;
+                }
+            };
+            if matches!(event.kind, EventKind::Access(_)) {
+                return;
+            }
+            for path in event.paths {
+                let dir = path.parent().and_then(|dir| std::path::absolute(dir).ok());
+                // The usercache usually sits next to whitelist.json and other
+                // files the server writes, which are not stats files
+                let result = if dir.as_ref() == Some(&usercache_dir)
+                    && path.file_name() == store.usercache_path.file_name()
+                {
+                    store.reload_usercache()
+                } else if dir.as_ref() == Some(&stats_dir) {
+                    store.reload_stats_file(&path)
+                } else {
+                    continue;
+                };
+                if let Err(e) = result {
+                    tracing::warn!("Could not reload {}: {}", path.display(), e);
+                }
+            }
+        })?;
+        watcher.watch(&self.stats_dir, RecursiveMode::NonRecursive)?;
+        // Watch the directory rather than the file, so the watch survives the
+        // server replacing the file
+        watcher.watch(self.usercache_dir(), RecursiveMode::NonRecursive)?;
+        Ok(watcher)
+    }
+
+    fn usercache_dir(&self) -> &Path {
+        match self.usercache_path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        }
+    }
+
+    /// Parses a single stats file again, or forgets the player if it was deleted.
+    /// A file that fails to parse (e.g. one that is still being written) keeps
+    /// the previously loaded stats.
+    fn reload_stats_file(&self, path: &Path) -> Result<()> {
+        if path.extension().is_none_or(|ext| ext != "json") {
+            return Ok(());
+        }
+        let Some(uuid) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            return Ok(());
+        };
+        if !path.exists() {
+            self.players.write().unwrap().remove(uuid);
+            self.unknown_keys.write().unwrap().remove(uuid);
+            return Ok(());
+        }
+        let loaded = parse_stats(&fs::read_to_string(path)?)?;
+        // Only mention unknown keys when they change, not on every save
+        let mut unknown_keys = self.unknown_keys.write().unwrap();
+        if !loaded.unknown_keys.is_empty() && unknown_keys.get(uuid) != Some(&loaded.unknown_keys) {
+            tracing::warn!(
+                "Unknown keys in {}: {}",
+                path.display(),
+                loaded.unknown_keys.join(", ")
+            );
+        }
+        unknown_keys.insert(uuid.to_string(), loaded.unknown_keys);
+        self.players
+            .write()
+            .unwrap()
+            .insert(uuid.to_string(), Arc::new(loaded.stats));
+        Ok(())
+    }
+
+    fn reload_usercache(&self) -> Result<()> {
+        if !self.usercache_path.exists() {
+            return Ok(());
+        }
+        let usercache = serde_json::from_str(&fs::read_to_string(&self.usercache_path)?)?;
+        let mut users = self.users.write().unwrap();
+        if users.update(usercache)
+            && let Some(path) = &self.name_history_path
+        {
+            users.history().save(path)?;
+        }
+        Ok(())
+    }
+
+    /// Gets the stats of a player by UUID, with or without dashes
+    pub fn get(&self, uuid: &str) -> Option<Arc<MinecraftStats>> {
+        let uuid = uuids::normalise(uuid).unwrap_or_else(|| uuid.to_string());
+        self.players.read().unwrap().get(&uuid).cloned()
+    }
+
+    /// Gets the stats of every player, sorted by UUID
+    pub fn players(&self) -> Vec<(String, Arc<MinecraftStats>)> {
+        let mut players = self
+            .players
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(uuid, stats)| (uuid.clone(), Arc::clone(stats)))
+            .collect::<Vec<_>>();
+        players.sort_by(|a, b| a.0.cmp(&b.0));
+        players
+    }
+
+    pub fn usercache(&self) -> Vec<UserCache> {
+        self.users.read().unwrap().usercache().cloned().collect()
+    }
+
+    /// Gets the name of every player in the usercache
+    pub fn usernames(&self) -> Vec<String> {
+        self.users.read().unwrap().names()
+    }
+
+    /// Gets the name of a player from the usercache, or else the name history
+    pub fn username(&self, uuid: &str) -> Option<String> {
+        self.users.read().unwrap().name(uuid).map(str::to_string)
+    }
+
+    /// Finds the UUID of a username, ignoring case. Players missing from the
+    /// usercache are found by their offline-mode UUID if they have a stats
+    /// file, but then the name's case must match.
+    pub fn uuid(&self, username: &str) -> Option<String> {
+        if let Some(uuid) = self.users.read().unwrap().uuid(username) {
+            return Some(uuid.to_string());
+        }
+        let uuid = offline_uuid(username);
+        self.get(&uuid).map(|_| uuid)
+    }
+
+    /// Every name a player was seen with, oldest first. Only kept by stores
+    /// loaded with [`StatsStore::load_with_name_history`].
+    pub fn name_history(&self, uuid: &str) -> Vec<NameRecord> {
+        self.users.read().unwrap().history().names(uuid).to_vec()
+    }
+}
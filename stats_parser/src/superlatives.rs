@@ -0,0 +1,245 @@
+use crate::i18n::tr_with;
+use crate::{MinecraftStats, STAT_CATEGORIES, fmt_stat_value, readable_stat_name};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Circumference of the Earth in centimetres
+const EARTH_CIRCUMFERENCE_CM: f64 = 4_007_500_000.0;
+
+/// Stats that grow just by being online, so they don't make interesting facts
+const BORING_STATS: [&str; 5] = [
+    "minecraft:play_time",
+    "minecraft:total_world_time",
+    "minecraft:time_since_death",
+    "minecraft:time_since_rest",
+    "minecraft:leave_game",
+];
+
+/// A notable fact about a single player
+#[derive(Debug, Clone)]
+pub enum Highlight {
+    /// The player has the highest value of this stat on the server
+    RankedFirst {
+        category: String,
+        stat: String,
+        value: i64,
+    },
+    MostMined {
+        block: String,
+        count: i64,
+    },
+    FavouriteMob {
+        mob: String,
+        count: i64,
+    },
+    /// The custom stat the fewest other players have, e.g. `minecraft:clean_banner`
+    WeirdestStat {
+        stat: String,
+        value: i64,
+        /// How many players on the server have this stat at all
+        players: usize,
+    },
+    /// Every distance travelled, in laps around the Earth
+    EarthLaps {
+        laps: f64,
+    },
+}
+
+impl fmt::Display for Highlight {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Highlight::RankedFirst {
+                category,
+                stat,
+                value,
+            } => tr_with(
+                "highlight.ranked_first",
+                &[
+                    ("stat", &readable_stat_name(category, stat)),
+                    ("value", &fmt_stat_value(category, stat, *value)),
+                ],
+            ),
+            Highlight::MostMined { block, count } => tr_with(
+                "highlight.most_mined",
+                &[
+                    ("block", &readable_stat_name("minecraft:mined", block)),
+                    ("count", count),
+                ],
+            ),
+            Highlight::FavouriteMob { mob, count } => tr_with(
+                "highlight.favourite_mob",
+                &[
+                    ("mob", &readable_stat_name("minecraft:killed", mob)),
+                    ("count", count),
+                ],
+            ),
+            Highlight::WeirdestStat {
+                stat,
+                value,
+                players,
+            } => tr_with(
+                "highlight.weirdest_stat",
+                &[
+                    ("stat", &readable_stat_name("minecraft:custom", stat)),
+                    ("value", &fmt_stat_value("minecraft:custom", stat, *value)),
+                    ("players", players),
+                ],
+            ),
+            Highlight::EarthLaps { laps } => {
+                tr_with("highlight.earth_laps", &[("laps", &format!("{:.4}", laps))])
+            }
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// The top player of a single stat, for `/superlatives`
+#[derive(Debug, Clone)]
+pub struct Superlative {
+    pub category: String,
+    pub stat: String,
+    pub uuid: String,
+    pub value: i64,
+}
+
+/// Finds the top player of every stat. Stats only one player has, and ties
+/// for first place, are left out.
+pub fn server_superlatives(players: &[(String, Arc<MinecraftStats>)]) -> Vec<Superlative> {
+    let mut superlatives = vec![];
+    for category in STAT_CATEGORIES {
+        // stat -> (uuid of the leader, best value, runner up value, players with the stat)
+        let mut leaders: HashMap<&str, (&str, i64, i64, usize)> = HashMap::new();
+        for (uuid, stats) in players {
+            for (stat, value) in stats.stats.category(category).into_iter().flatten() {
+                if *value <= 0 || BORING_STATS.contains(&stat.as_str()) {
+                    continue;
+                }
+                let entry = leaders.entry(stat).or_insert((uuid, 0, 0, 0));
+                entry.3 += 1;
+                if *value > entry.1 {
+                    entry.2 = entry.1;
+                    entry.0 = uuid;
+                    entry.1 = *value;
+                } else if *value > entry.2 {
+                    entry.2 = *value;
+                }
+            }
+        }
+        superlatives.extend(
+            leaders
+                .into_iter()
+                .filter(|(_, (_, best, runner_up, count))| *count > 1 && best > runner_up)
+                .map(|(stat, (uuid, value, _, _))| Superlative {
+                    category: category.to_string(),
+                    stat: stat.to_string(),
+                    uuid: uuid.to_string(),
+                    value,
+                }),
+        );
+    }
+    superlatives.sort_by(|a, b| {
+        let category_index = |c: &str| STAT_CATEGORIES.iter().position(|key| *key == c);
+        category_index(&a.category)
+            .cmp(&category_index(&b.category))
+            .then_with(|| a.stat.cmp(&b.stat))
+    });
+    superlatives
+}
+
+/// Finds every notable fact about a player. Returns an empty list for an unknown UUID.
+pub fn player_highlights(uuid: &str, players: &[(String, Arc<MinecraftStats>)]) -> Vec<Highlight> {
+    let Some((_, player)) = players.iter().find(|(id, _)| id == uuid) else {
+        return vec![];
+    };
+    let stats = &player.stats;
+    let mut highlights = server_superlatives(players)
+        .into_iter()
+        .filter(|superlative| superlative.uuid == uuid)
+        .map(|superlative| Highlight::RankedFirst {
+            category: superlative.category,
+            stat: superlative.stat,
+            value: superlative.value,
+        })
+        .collect::<Vec<_>>();
+
+    if let Some((block, count)) = top_entry(stats.mined.as_ref()) {
+        highlights.push(Highlight::MostMined { block, count });
+    }
+    if let Some((mob, count)) = top_entry(stats.killed.as_ref()) {
+        highlights.push(Highlight::FavouriteMob { mob, count });
+    }
+
+    let weirdest = stats
+        .custom
+        .iter()
+        .filter(|(stat, value)| **value > 0 && !BORING_STATS.contains(&stat.as_str()))
+        .map(|(stat, value)| {
+            let count = players
+                .iter()
+                .filter(|(_, other)| other.stats.get("minecraft:custom", stat) > 0)
+                .count();
+            (stat, *value, count)
+        })
+        .min_by(|a, b| a.2.cmp(&b.2).then_with(|| b.1.cmp(&a.1)));
+    // Only worth mentioning if most of the server hasn't done it
+    if let Some((stat, value, count)) = weirdest
+        && count * 2 <= players.len()
+    {
+        highlights.push(Highlight::WeirdestStat {
+            stat: stat.clone(),
+            value,
+            players: count,
+        });
+    }
+
+    let distance_cm: u64 = stats
+        .custom
+        .iter()
+        .filter(|(stat, _)| stat.ends_with("_one_cm"))
+        // Negative distances only come from corrupted files
+        .map(|(_, value)| u64::try_from(*value).unwrap_or(0))
+        .sum();
+    if distance_cm > 0 {
+        highlights.push(Highlight::EarthLaps {
+            laps: distance_cm as f64 / EARTH_CIRCUMFERENCE_CM,
+        });
+    }
+    highlights
+}
+
+fn top_entry(stats: Option<&HashMap<String, i64>>) -> Option<(String, i64)> {
+    stats?
+        .iter()
+        .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(key, value)| (key.clone(), *value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::parse_stats;
+
+    fn player(uuid: &str, json: &str) -> (String, Arc<MinecraftStats>) {
+        (uuid.to_string(), Arc::new(parse_stats(json).unwrap().stats))
+    }
+
+    #[test]
+    fn negative_distances_count_as_zero() {
+        let players = [player(
+            "a",
+            r#"{"DataVersion": 3953, "stats": {"minecraft:custom": {
+                "minecraft:walk_one_cm": -4007500000,
+                "minecraft:fly_one_cm": 4007500000
+            }}}"#,
+        )];
+        let laps =
+            player_highlights("a", &players)
+                .into_iter()
+                .find_map(|highlight| match highlight {
+                    Highlight::EarthLaps { laps } => Some(laps),
+                    _ => None,
+                });
+        assert_eq!(laps, Some(1.0));
+    }
+}
@@ -0,0 +1,315 @@
+use crate::query::stat_full;
+use axum::Router;
+use axum::extract::State;
+use axum::http::{StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use prometheus::{
+    Encoder, GaugeVec, Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
+    Opts, Registry, TextEncoder,
+};
+use stats_parser::stat_key::CustomStat;
+use stats_parser::store::StatsStore;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How often the Minecraft server is queried for the player gauges
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Prometheus metrics for the Minecraft server and the bot
+pub struct Telemetry {
+    registry: Registry,
+    store: Arc<StatsStore>,
+    server: Option<ServerMetrics>,
+    player_playtime: GaugeVec,
+    player_deaths: IntGaugeVec,
+    commands: IntCounterVec,
+    command_errors: IntCounterVec,
+}
+
+/// Gauges of the Minecraft server, which is queried at `host:port`
+struct ServerMetrics {
+    host: String,
+    port: u16,
+    up: IntGauge,
+    players_online: IntGauge,
+    players_max: IntGauge,
+    query_duration: Histogram,
+    query_failures: IntCounter,
+}
+
+impl ServerMetrics {
+    fn new(registry: &Registry, host: String, port: u16) -> prometheus::Result<Self> {
+        let up = IntGauge::new(
+            "minecraft_server_up",
+            "Whether the last query of the server succeeded",
+        )?;
+        let players_online =
+            IntGauge::new("minecraft_players_online", "Players online on the server")?;
+        let players_max = IntGauge::new("minecraft_players_max", "Player slots on the server")?;
+        let query_duration = Histogram::with_opts(HistogramOpts::new(
+            "minecraft_query_duration_seconds",
+            "Time taken by full stat queries of the server",
+        ))?;
+        let query_failures = IntCounter::new(
+            "minecraft_query_failures_total",
+            "Full stat queries of the server that failed",
+        )?;
+        registry.register(Box::new(up.clone()))?;
+        registry.register(Box::new(players_online.clone()))?;
+        registry.register(Box::new(players_max.clone()))?;
+        registry.register(Box::new(query_duration.clone()))?;
+        registry.register(Box::new(query_failures.clone()))?;
+        Ok(ServerMetrics {
+            host,
+            port,
+            up,
+            players_online,
+            players_max,
+            query_duration,
+            query_failures,
+        })
+    }
+}
+
+impl Telemetry {
+    /// Sets up the metrics. Without a `(host, port)` to query, e.g. when
+    /// `enable-query` is off, the server is not polled and its metrics are
+    /// left out rather than reported as down.
+    pub fn new(store: Arc<StatsStore>, server: Option<(String, u16)>) -> prometheus::Result<Self> {
+        let registry = Registry::new();
+        let server = match server {
+            Some((host, port)) => Some(ServerMetrics::new(&registry, host, port)?),
+            None => None,
+        };
+        let player_playtime = GaugeVec::new(
+            Opts::new("minecraft_player_playtime_seconds", "Playtime of a player"),
+            &["player"],
+        )?;
+        let player_deaths = IntGaugeVec::new(
+            Opts::new("minecraft_player_deaths", "Deaths of a player"),
+            &["player"],
+        )?;
+        let commands = IntCounterVec::new(
+            Opts::new("bot_command_invocations_total", "Commands run on the bot"),
+            &["command"],
+        )?;
+        let command_errors = IntCounterVec::new(
+            Opts::new("bot_command_errors_total", "Commands that failed"),
+            &["command"],
+        )?;
+        registry.register(Box::new(player_playtime.clone()))?;
+        registry.register(Box::new(player_deaths.clone()))?;
+        registry.register(Box::new(commands.clone()))?;
+        registry.register(Box::new(command_errors.clone()))?;
+        Ok(Telemetry {
+            registry,
+            store,
+            server,
+            player_playtime,
+            player_deaths,
+            commands,
+            command_errors,
+        })
+    }
+
+    /// Queries the server once and records the result
+    pub async fn poll_server(&self) {
+        let Some(server) = &self.server else {
+            return;
+        };
+        let start = Instant::now();
+        let response = stat_full(&server.host, server.port).await;
+        server.query_duration.observe(start.elapsed().as_secs_f64());
+        match response {
+            Ok(response) => {
+                server.up.set(1);
+                server.players_online.set(response.num_players as i64);
+                server.players_max.set(response.max_players as i64);
+            }
+            Err(_) => {
+                server.up.set(0);
+                server.query_failures.inc();
+            }
+        }
+    }
+
+    pub fn command_invoked(&self, command: &str) {
+        self.commands.with_label_values(&[command]).inc();
+    }
+
+    pub fn command_failed(&self, command: &str) {
+        self.command_errors.with_label_values(&[command]).inc();
+    }
+
+    /// Encodes every metric in the Prometheus text format. Per-player gauges
+    /// are read from the stats store at this point.
+    pub fn gather(&self) -> prometheus::Result<String> {
+        self.player_playtime.reset();
+        self.player_deaths.reset();
+        for (uuid, stats) in self.store.players() {
+            let player = self.store.username(&uuid).unwrap_or(uuid);
+            let ticks = stats.stats.custom_stat(CustomStat::PlayTime);
+            self.player_playtime
+                .with_label_values(&[&player])
+                .set(ticks as f64 / 20.0);
+            self.player_deaths
+                .with_label_values(&[&player])
+                .set(stats.stats.custom_stat(CustomStat::Deaths));
+        }
+        let mut buf = vec![];
+        TextEncoder::new().encode(&self.registry.gather(), &mut buf)?;
+        Ok(String::from_utf8_lossy(&buf).into_owned())
+    }
+}
+
+/// Serves `/metrics` on `addr` and polls the server, if any, in the background
+pub async fn run_metrics(addr: SocketAddr, telemetry: Arc<Telemetry>) -> anyhow::Result<()> {
+    if telemetry.server.is_some() {
+        let poller = Arc::clone(&telemetry);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(POLL_INTERVAL);
+            loop {
+                interval.tick().await;
+                poller.poll_server().await;
+            }
+        });
+    }
+    let app = Router::new()
+        .route("/metrics", get(metrics))
+        .with_state(telemetry);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+async fn metrics(State(telemetry): State<Arc<Telemetry>>) -> Response {
+    match telemetry.gather() {
+        Ok(body) => ([(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)], body).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::UdpSocket;
+
+    const UUID: &str = "853c80ef-3c37-49fd-aa49-938b674adae6";
+
+    /// A store with one player, Alice, who played an hour and died 3 times
+    fn store(name: &str) -> Arc<StatsStore> {
+        let dir = std::env::temp_dir().join(format!("telemetry-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("stats")).unwrap();
+        std::fs::write(
+            dir.join("stats").join(format!("{}.json", UUID)),
+            r#"{"DataVersion": 3953, "stats": {"minecraft:custom": {
+                "minecraft:play_time": 72000, "minecraft:deaths": 3
+            }}}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("usercache.json"),
+            format!(
+                r#"[{{"name": "Alice", "uuid": "{}", "expiresOn": "2030-01-01 00:00:00 +0000"}}]"#,
+                UUID
+            ),
+        )
+        .unwrap();
+        Arc::new(StatsStore::load(dir.join("stats"), dir.join("usercache.json")).unwrap())
+    }
+
+    /// Answers one handshake and one full stat request like a vanilla server
+    /// with `players` online out of 20
+    async fn mock_server(players: &[&str]) -> u16 {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let port = socket.local_addr().unwrap().port();
+        let players = players
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 1500];
+            for _ in 0..2 {
+                let (len, client) = socket.recv_from(&mut buf).await.unwrap();
+                let request = &buf[..len];
+                assert_eq!(request[..2], [0xfe, 0xfd]);
+                let (packet_type, session) = (request[2], &request[3..7]);
+                let mut response = vec![packet_type];
+                response.extend_from_slice(session);
+                if packet_type == 9 {
+                    response.extend_from_slice(b"9513307\0");
+                } else {
+                    response.extend_from_slice(b"splitnum\0\x80\0");
+                    let num_players = players.len().to_string();
+                    let values = [
+                        ("hostname", "A Minecraft Server"),
+                        ("gametype", "SMP"),
+                        ("game_id", "MINECRAFT"),
+                        ("version", "1.21.4"),
+                        ("plugins", ""),
+                        ("map", "world"),
+                        ("numplayers", &num_players),
+                        ("maxplayers", "20"),
+                        ("hostport", "25565"),
+                        ("hostip", "127.0.0.1"),
+                    ];
+                    for (key, value) in values {
+                        response.extend_from_slice(format!("{}\0{}\0", key, value).as_bytes());
+                    }
+                    response.extend_from_slice(b"\0\x01player_\0\0");
+                    for player in &players {
+                        response.extend_from_slice(format!("{}\0", player).as_bytes());
+                    }
+                    response.push(0);
+                }
+                socket.send_to(&response, client).await.unwrap();
+            }
+        });
+        port
+    }
+
+    #[tokio::test]
+    async fn poll_server_records_the_query_response() {
+        let port = mock_server(&["Alice", "Bob"]).await;
+        let telemetry =
+            Telemetry::new(store("poll"), Some(("127.0.0.1".to_string(), port))).unwrap();
+        telemetry.poll_server().await;
+        telemetry.command_invoked("profile");
+        telemetry.command_invoked("profile");
+        telemetry.command_failed("profile");
+
+        let metrics = telemetry.gather().unwrap();
+        let lines = metrics.lines().collect::<Vec<_>>();
+        for expected in [
+            "minecraft_server_up 1",
+            "minecraft_players_online 2",
+            "minecraft_players_max 20",
+            "minecraft_query_duration_seconds_count 1",
+            "minecraft_query_failures_total 0",
+            "minecraft_player_playtime_seconds{player=\"Alice\"} 3600",
+            "minecraft_player_deaths{player=\"Alice\"} 3",
+            "bot_command_invocations_total{command=\"profile\"} 2",
+            "bot_command_errors_total{command=\"profile\"} 1",
+        ] {
+            assert!(
+                lines.contains(&expected),
+                "{} missing from\n{}",
+                expected,
+                metrics
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn server_metrics_are_left_out_without_a_query_port() {
+        let telemetry = Telemetry::new(store("no-query"), None).unwrap();
+        telemetry.poll_server().await;
+        let metrics = telemetry.gather().unwrap();
+        assert!(!metrics.contains("minecraft_server_up"));
+        assert!(!metrics.contains("minecraft_players_online"));
+        assert!(metrics.contains("minecraft_player_deaths{player=\"Alice\"} 3"));
+    }
+}
@@ -0,0 +1,135 @@
+use crate::i18n::tr;
+use crate::report::Unit;
+use crate::stat_key::CustomStat;
+use crate::{MinecraftStats, StatCategories};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Headline stats summed over every player for `/totals`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TotalStat {
+    Playtime,
+    BlocksMined,
+    DiamondsMined,
+    MobsKilled,
+    Deaths,
+    DistanceWalked,
+    DistanceFlown,
+    DistanceBoated,
+    VillagerTrades,
+}
+
+impl TotalStat {
+    pub const ALL: [TotalStat; 9] = [
+        TotalStat::Playtime,
+        TotalStat::BlocksMined,
+        TotalStat::DiamondsMined,
+        TotalStat::MobsKilled,
+        TotalStat::Deaths,
+        TotalStat::DistanceWalked,
+        TotalStat::DistanceFlown,
+        TotalStat::DistanceBoated,
+        TotalStat::VillagerTrades,
+    ];
+
+    pub fn get_name(&self) -> String {
+        tr(match self {
+            TotalStat::Playtime => "total_stat.playtime",
+            TotalStat::BlocksMined => "total_stat.blocks_mined",
+            TotalStat::DiamondsMined => "total_stat.diamonds_mined",
+            TotalStat::MobsKilled => "total_stat.mobs_killed",
+            TotalStat::Deaths => "total_stat.deaths",
+            TotalStat::DistanceWalked => "total_stat.distance_walked",
+            TotalStat::DistanceFlown => "total_stat.distance_flown",
+            TotalStat::DistanceBoated => "total_stat.distance_boated",
+            TotalStat::VillagerTrades => "total_stat.villager_trades",
+        })
+    }
+
+    /// Gets this stat for a single player
+    pub fn value(&self, stats: &StatCategories) -> u64 {
+        // Negative values only come from corrupted files, so count as zero
+        let custom = |stat: CustomStat| u64::try_from(stats.custom_stat(stat)).unwrap_or(0);
+        match self {
+            TotalStat::Playtime => custom(CustomStat::PlayTime),
+            TotalStat::BlocksMined => stats.category_total("minecraft:mined"),
+            TotalStat::DiamondsMined => u64::try_from(
+                stats.get("minecraft:mined", "minecraft:diamond_ore")
+                    + stats.get("minecraft:mined", "minecraft:deepslate_diamond_ore"),
+            )
+            .unwrap_or(0),
+            TotalStat::MobsKilled => custom(CustomStat::MobKills),
+            TotalStat::Deaths => custom(CustomStat::Deaths),
+            TotalStat::DistanceWalked => custom(CustomStat::WalkOneCm),
+            TotalStat::DistanceFlown => {
+                custom(CustomStat::FlyOneCm) + custom(CustomStat::AviateOneCm)
+            }
+            TotalStat::DistanceBoated => custom(CustomStat::BoatOneCm),
+            TotalStat::VillagerTrades => custom(CustomStat::TradedWithVillager),
+        }
+    }
+
+    pub fn unit(&self) -> Unit {
+        match self {
+            TotalStat::Playtime => Unit::Ticks,
+            TotalStat::DistanceWalked | TotalStat::DistanceFlown | TotalStat::DistanceBoated => {
+                Unit::Centimetres
+            }
+            _ => Unit::Count,
+        }
+    }
+
+    /// Formats a value of this stat, e.g. ticks as `HH:MM:SS` and centimetres as km
+    pub fn format(&self, value: u64) -> String {
+        self.unit().format(value as f64)
+    }
+}
+
+/// A server-wide total and the player who contributed the most to it
+#[derive(Debug, Clone)]
+pub struct ServerTotal {
+    pub stat: TotalStat,
+    pub total: u64,
+    /// UUID and value of the top contributor, if anyone has a non-zero value
+    pub top: Option<(String, u64)>,
+}
+
+/// Sums every [`TotalStat`] over all players
+pub fn server_totals(players: &[(String, Arc<MinecraftStats>)]) -> Vec<ServerTotal> {
+    TotalStat::ALL
+        .iter()
+        .map(|stat| {
+            let mut total = 0;
+            let mut top: Option<(String, u64)> = None;
+            for (uuid, stats) in players {
+                let value = stat.value(&stats.stats);
+                total += value;
+                if value > 0 && top.as_ref().is_none_or(|(_, best)| value > *best) {
+                    top = Some((uuid.clone(), value));
+                }
+            }
+            ServerTotal {
+                stat: *stat,
+                total,
+                top,
+            }
+        })
+        .collect()
+}
+
+/// Sums every stat of a category (e.g. `minecraft:mined`) over all players,
+/// sorted by total (descending)
+pub fn category_totals(
+    players: &[(String, Arc<MinecraftStats>)],
+    category: &str,
+) -> Vec<(String, u64)> {
+    let mut totals: HashMap<String, u64> = HashMap::new();
+    for (_, stats) in players {
+        for (key, value) in stats.stats.category(category).into_iter().flatten() {
+            *totals.entry(key.clone()).or_default() += u64::try_from(*value).unwrap_or(0);
+        }
+    }
+    let mut totals = totals.into_iter().collect::<Vec<_>>();
+    totals.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    totals
+}
@@ -0,0 +1,330 @@
+use crate::UserCache;
+use crate::uuids;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A name a player was seen with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NameRecord {
+    pub name: String,
+    /// Unix timestamp in seconds of when the name was first seen
+    pub seen_at: u64,
+}
+
+/// Every name each player was seen with in the usercache, oldest first, by
+/// UUID. The server forgets players that haven't joined for a while, so this
+/// keeps their names around.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NameHistory {
+    players: BTreeMap<String, Vec<NameRecord>>,
+}
+
+impl NameHistory {
+    /// Loads the history, or an empty one if the file does not exist yet
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read {}", path.display()))?;
+        serde_json::from_str(&json).with_context(|| format!("Could not parse {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Records the current name of a player. Returns whether it is new, i.e.
+    /// the player is new or changed their name.
+    pub fn record(&mut self, uuid: &str, name: &str, seen_at: u64) -> bool {
+        let names = self.players.entry(key(uuid)).or_default();
+        if names.last().is_some_and(|last| last.name == name) {
+            return false;
+        }
+        names.push(NameRecord {
+            name: name.to_string(),
+            seen_at,
+        });
+        true
+    }
+
+    /// Names of a player, oldest first
+    pub fn names(&self, uuid: &str) -> &[NameRecord] {
+        self.players
+            .get(&key(uuid))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// The last name seen of a player
+    pub fn last_name(&self, uuid: &str) -> Option<&str> {
+        self.names(uuid).last().map(|record| record.name.as_str())
+    }
+}
+
+/// The usercache, indexed by UUID and by name, backed by a [`NameHistory`] for
+/// players who have fallen out of it
+#[derive(Debug, Clone, Default)]
+pub struct UserDirectory {
+    /// Usercache entries by UUID, see [`key`]
+    entries: HashMap<String, UserCache>,
+    /// UUIDs by lowercase name, from the usercache and then the history
+    uuids: HashMap<String, String>,
+    history: NameHistory,
+}
+
+impl UserDirectory {
+    pub fn new(usercache: Vec<UserCache>, history: NameHistory) -> Self {
+        let mut directory = UserDirectory {
+            history,
+            ..Default::default()
+        };
+        directory.update(usercache);
+        directory
+    }
+
+    /// Replaces the usercache, recording new names in the history. Returns
+    /// whether the history changed and should be saved.
+    pub fn update(&mut self, usercache: Vec<UserCache>) -> bool {
+        let now = unix_time();
+        let mut changed = false;
+        for entry in &usercache {
+            // Expired entries were last seen when they expired, not now, so
+            // they don't take names back from players seen since
+            let seen_at = entry
+                .expires_at()
+                .map_or(now, |expires_at| expires_at.min(now));
+            changed |= self.history.record(&entry.uuid, &entry.name, seen_at);
+        }
+        self.entries = usercache
+            .into_iter()
+            .map(|entry| (key(&entry.uuid), entry))
+            .collect();
+        // Older names first, so the latest holder of a name wins, then the
+        // usercache over the history. Expired entries are left to the history,
+        // as their names may have been taken by someone else since.
+        let mut records = self
+            .history
+            .players
+            .iter()
+            .flat_map(|(uuid, names)| names.iter().map(move |record| (uuid, record)))
+            .collect::<Vec<_>>();
+        records.sort_by_key(|(_, record)| record.seen_at);
+        self.uuids = records
+            .into_iter()
+            .map(|(uuid, record)| (record.name.to_lowercase(), uuid.clone()))
+            .collect();
+        let mut current = self
+            .entries
+            .values()
+            .filter(|entry| !entry.is_expired(now))
+            .collect::<Vec<_>>();
+        // The entry renewed last belongs to the player who joined last
+        current.sort_by_key(|entry| entry.expires_at());
+        for entry in current {
+            self.uuids
+                .insert(entry.name.to_lowercase(), key(&entry.uuid));
+        }
+        changed
+    }
+
+    /// Gets the usercache entry of a player
+    pub fn entry(&self, uuid: &str) -> Option<&UserCache> {
+        self.entries.get(&key(uuid))
+    }
+
+    /// Gets the name of a player: the one in the usercache, or else the last one seen
+    pub fn name(&self, uuid: &str) -> Option<&str> {
+        match self.entry(uuid) {
+            Some(entry) => Some(&entry.name),
+            None => self.history.last_name(uuid),
+        }
+    }
+
+    /// Finds the UUID of a name, ignoring case. Names no longer in the
+    /// usercache are found in the history.
+    pub fn uuid(&self, name: &str) -> Option<&str> {
+        self.uuids.get(&name.to_lowercase()).map(String::as_str)
+    }
+
+    /// Names of the players in the usercache
+    pub fn names(&self) -> Vec<String> {
+        self.entries
+            .values()
+            .map(|entry| entry.name.clone())
+            .collect()
+    }
+
+    /// Entries of the usercache, in no particular order
+    pub fn usercache(&self) -> impl Iterator<Item = &UserCache> {
+        self.entries.values()
+    }
+
+    pub fn history(&self) -> &NameHistory {
+        &self.history
+    }
+}
+
+/// UUIDs are kept lowercase with dashes, like the names of stats files
+fn key(uuid: &str) -> String {
+    uuids::normalise(uuid).unwrap_or_else(|| uuid.to_lowercase())
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Parses a usercache date, e.g. `2025-04-22 06:01:59 +0000`, into a Unix
+/// timestamp in seconds
+pub fn parse_timestamp(text: &str) -> Option<u64> {
+    let mut parts = text.split_whitespace();
+    let mut date = parts.next()?.splitn(3, '-');
+    let mut time = parts.next()?.splitn(3, ':');
+    let offset = parts.next().unwrap_or("+0000");
+
+    let year: i64 = date.next()?.parse().ok()?;
+    let month: i64 = date.next()?.parse().ok()?;
+    let day: i64 = date.next()?.parse().ok()?;
+    let hour: i64 = time.next()?.parse().ok()?;
+    let minute: i64 = time.next()?.parse().ok()?;
+    let second: i64 = time.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let (sign, offset) = match offset.split_at_checked(1)? {
+        ("+", digits) => (1, digits),
+        ("-", digits) => (-1, digits),
+        _ => return None,
+    };
+    if offset.len() != 4 {
+        return None;
+    }
+    let offset_minutes: i64 =
+        offset[..2].parse::<i64>().ok()? * 60 + offset[2..].parse::<i64>().ok()?;
+
+    // Days since 1970-01-01 in the proleptic Gregorian calendar, from
+    // https://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    let seconds = days * 86_400 + hour * 3600 + minute * 60 + second - sign * offset_minutes * 60;
+    u64::try_from(seconds).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE: &str = "853c80ef-3c37-49fd-aa49-938b674adae6";
+    const BOB: &str = "069a79f4-44e9-4726-a5be-fca90e38aaf5";
+
+    fn entry(name: &str, uuid: &str, expires_on: &str) -> UserCache {
+        UserCache {
+            name: name.to_string(),
+            uuid: uuid.to_string(),
+            expires_on: expires_on.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_timestamps_with_offsets() {
+        assert_eq!(parse_timestamp("1970-01-01 00:00:00 +0000"), Some(0));
+        assert_eq!(
+            parse_timestamp("2025-04-22 06:01:59 +0000"),
+            Some(1_745_301_719)
+        );
+        assert_eq!(
+            parse_timestamp("2024-02-29 23:59:59 -0530"),
+            Some(1_709_270_999)
+        );
+        assert_eq!(
+            parse_timestamp("2025-04-22 11:31:59 +0530"),
+            Some(1_745_301_719)
+        );
+        assert_eq!(parse_timestamp("2025-04-22 06:01:59"), Some(1_745_301_719));
+    }
+
+    #[test]
+    fn rejects_invalid_timestamps() {
+        assert_eq!(parse_timestamp("2025-13-01 00:00:00 +0000"), None);
+        assert_eq!(parse_timestamp("2025-00-01 00:00:00 +0000"), None);
+        assert_eq!(parse_timestamp("2025-04-32 00:00:00 +0000"), None);
+        assert_eq!(parse_timestamp("2025-04-22 06:01:59 0000"), None);
+        assert_eq!(parse_timestamp("2025-04-22 06:01:59 +00"), None);
+        assert_eq!(parse_timestamp("2025-04-22"), None);
+        assert_eq!(parse_timestamp(""), None);
+    }
+
+    #[test]
+    fn records_renames_once() {
+        let mut history = NameHistory::default();
+        assert!(history.record(ALICE, "Alice", 10));
+        assert!(!history.record(ALICE, "Alice", 20));
+        assert!(history.record(&ALICE.to_uppercase().replace('-', ""), "Alicia", 30));
+        assert!(!history.record(ALICE, "Alicia", 40));
+        let names = history
+            .names(ALICE)
+            .iter()
+            .map(|record| (record.name.as_str(), record.seen_at))
+            .collect::<Vec<_>>();
+        assert_eq!(names, [("Alice", 10), ("Alicia", 30)]);
+        assert_eq!(history.last_name(ALICE), Some("Alicia"));
+    }
+
+    #[test]
+    fn latest_holder_of_a_name_wins() {
+        let mut history = NameHistory::default();
+        history.record(ALICE, "Steve", 10);
+        history.record(ALICE, "Alice", 20);
+        history.record(BOB, "Steve", 30);
+        let directory = UserDirectory::new(vec![], history.clone());
+        assert_eq!(directory.uuid("steve"), Some(BOB));
+        assert_eq!(directory.uuid("ALICE"), Some(ALICE));
+        assert_eq!(directory.name(ALICE), Some("Alice"));
+
+        // The name moves back, and the usercache knows about it first
+        let mut directory = UserDirectory::new(vec![], history);
+        let changed = directory.update(vec![
+            entry("Steve", ALICE, "2999-01-01 00:00:00 +0000"),
+            entry("Bobby", BOB, "2999-01-01 00:00:00 +0000"),
+        ]);
+        assert!(changed);
+        assert_eq!(directory.uuid("steve"), Some(ALICE));
+        assert_eq!(directory.uuid("bobby"), Some(BOB));
+    }
+
+    #[test]
+    fn expired_entries_leave_names_to_the_history() {
+        let mut history = NameHistory::default();
+        // Alice took the name in 2020, long after Bob's entry expired
+        history.record(ALICE, "Steve", 1_600_000_000);
+        let directory = UserDirectory::new(
+            vec![entry("Steve", BOB, "2000-01-01 00:00:00 +0000")],
+            history,
+        );
+        assert_eq!(directory.uuid("steve"), Some(ALICE));
+        assert!(directory.entry(BOB).unwrap().is_expired(unix_time()));
+        assert_eq!(directory.name(BOB), Some("Steve"));
+        assert_eq!(directory.history.players[&key(BOB)][0].seen_at, 946_684_800);
+
+        let directory = UserDirectory::new(
+            vec![
+                entry("Steve", ALICE, "2000-01-01 00:00:00 +0000"),
+                entry("Steve", BOB, "2999-01-01 00:00:00 +0000"),
+            ],
+            NameHistory::default(),
+        );
+        assert_eq!(directory.uuid("steve"), Some(BOB));
+    }
+}
@@ -0,0 +1,122 @@
+use md5::{Digest, Md5};
+
+/// What kind of account a player UUID belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UuidKind {
+    /// A Mojang account, from a server with `online-mode=true`. These are
+    /// random version 4 UUIDs.
+    Online,
+    /// A player of a server with `online-mode=false`, whose UUID is derived
+    /// from their name, see [`offline_uuid`]. These are version 3 UUIDs.
+    Offline,
+    /// Any other version, e.g. from a plugin or a mod
+    Other(u8),
+}
+
+/// Formats a UUID the way stats files are named: lowercase with dashes, e.g.
+/// `045f283a-8e2c-3096-a1a6-e2ac4589885d`. Accepts UUIDs with or without
+/// dashes in any case, and returns `None` for anything else.
+pub fn normalise(uuid: &str) -> Option<String> {
+    let hex = uuid.replace('-', "").to_lowercase();
+    if hex.len() != 32 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    // Dashes may only be missing altogether or be in the usual places
+    let dashes = uuid.match_indices('-').map(|(i, _)| i);
+    if uuid.contains('-') && !dashes.eq([8, 13, 18, 23]) {
+        return None;
+    }
+    Some(format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    ))
+}
+
+/// Formats a UUID without dashes, the way Mojang's API returns them
+pub fn undashed(uuid: &str) -> Option<String> {
+    Some(normalise(uuid)?.replace('-', ""))
+}
+
+/// Gets the version of a UUID, the first digit of its third group
+pub fn version(uuid: &str) -> Option<u8> {
+    let uuid = normalise(uuid)?;
+    u8::from_str_radix(&uuid[14..15], 16).ok()
+}
+
+pub fn kind(uuid: &str) -> Option<UuidKind> {
+    Some(match version(uuid)? {
+        4 => UuidKind::Online,
+        3 => UuidKind::Offline,
+        version => UuidKind::Other(version),
+    })
+}
+
+/// Computes the UUID an offline-mode server gives a player: the name-based
+/// version 3 UUID of `OfflinePlayer:<name>`, like Java's
+/// `UUID.nameUUIDFromBytes`. Names are case-sensitive, so `Steve` and `steve`
+/// are different players.
+pub fn offline_uuid(name: &str) -> String {
+    let mut bytes: [u8; 16] = Md5::digest(format!("OfflinePlayer:{}", name)).into();
+    bytes[6] = (bytes[6] & 0x0f) | 0x30;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = bytes
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    normalise(&hex).unwrap_or(hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalises_uuids() {
+        let uuid = "069a79f4-44e9-4726-a5be-fca90e38aaf5";
+        for input in [
+            uuid,
+            "069A79F444E94726A5BEFCA90E38AAF5",
+            &uuid.to_uppercase(),
+        ] {
+            assert_eq!(normalise(input).as_deref(), Some(uuid));
+        }
+        let undashed = undashed(uuid).unwrap();
+        assert_eq!(undashed, "069a79f444e94726a5befca90e38aaf5");
+        assert_eq!(normalise(&undashed).as_deref(), Some(uuid));
+    }
+
+    #[test]
+    fn rejects_misplaced_dashes() {
+        assert_eq!(normalise("069a79f444e9-4726-a5be-fca90e38-aaf5"), None);
+        assert_eq!(normalise("069a79f4-44e94726-a5be-fca90e38aaf5"), None);
+        assert_eq!(normalise("069a79f4-44e9-4726-a5be-fca90e38aaf"), None);
+        assert_eq!(normalise("not a uuid"), None);
+    }
+
+    #[test]
+    fn tells_kinds_apart() {
+        assert_eq!(
+            kind("069a79f4-44e9-4726-a5be-fca90e38aaf5"),
+            Some(UuidKind::Online)
+        );
+        assert_eq!(kind(&offline_uuid("Notch")), Some(UuidKind::Offline));
+        assert_eq!(version("069a79f4-44e9-c726-a5be-fca90e38aaf5"), Some(12));
+        assert_eq!(
+            kind("069a79f4-44e9-c726-a5be-fca90e38aaf5"),
+            Some(UuidKind::Other(12))
+        );
+    }
+
+    #[test]
+    fn computes_offline_uuids() {
+        assert_eq!(
+            offline_uuid("Notch"),
+            "b50ad385-829d-3141-a216-7e7d7539ba7f"
+        );
+        assert_ne!(offline_uuid("notch"), offline_uuid("Notch"));
+    }
+}